@@ -1656,6 +1656,31 @@ impl LazyFrame {
         }))
     }
 
+    /// Apply `set_op` between the `List` columns `lhs` and `rhs` and add the result as the
+    /// column `output`, like the set operations of the `list` expression namespace.
+    ///
+    /// The streaming engine runs this as an operator on every batch of the stream, without
+    /// collecting the columns first. As both operands are columns of the same batch, they
+    /// always have the same length. To use a length-1 literal as an operand, add it with
+    /// [`LazyFrame::with_column`] first, which broadcasts it to the length of the frame; that
+    /// projection itself is not streamable and runs in memory before the node.
+    #[cfg(feature = "list_sets")]
+    pub fn list_set_operation(
+        self,
+        lhs: &str,
+        rhs: &str,
+        set_op: SetOperation,
+        output: &str,
+    ) -> Self {
+        self.map_private(DslFunction::FunctionNode(FunctionNode::ListSetOperation {
+            lhs: Arc::from(lhs),
+            rhs: Arc::from(rhs),
+            set_op,
+            output: Arc::from(output),
+            schema: Default::default(),
+        }))
+    }
+
     #[cfg(feature = "merge_sorted")]
     pub fn merge_sorted(self, other: LazyFrame, key: &str) -> PolarsResult<LazyFrame> {
         // The two DataFrames are temporary concatenated
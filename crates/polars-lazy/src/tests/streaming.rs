@@ -428,3 +428,92 @@ fn test_streaming_full_outer_join() -> PolarsResult<()> {
 
     Ok(())
 }
+
+/// A frame of several chunks, so that the source emits several batches, with the `List`
+/// columns `a` and `b`.
+#[cfg(feature = "list_sets")]
+fn list_set_batches() -> PolarsResult<DataFrame> {
+    let batch = |offset: i32| -> PolarsResult<DataFrame> {
+        let rows = |f: &dyn Fn(i32) -> Vec<i32>| {
+            (offset..offset + 5)
+                .map(|i| Series::new("", f(i)))
+                .collect::<Vec<_>>()
+        };
+        df![
+            "a" => rows(&|i| vec![i, i + 1, i % 3]),
+            "b" => rows(&|i| vec![i + 1, i % 2]),
+        ]
+    };
+    let mut df = batch(0)?;
+    for offset in [5, 10, 15] {
+        df.vstack_mut(&batch(offset)?)?;
+    }
+    assert!(df.n_chunks() > 1);
+    Ok(df)
+}
+
+#[test]
+#[cfg(feature = "list_sets")]
+fn test_streaming_list_set_operation() -> PolarsResult<()> {
+    let df = list_set_batches()?;
+    // The literal is of length 1 and broadcast against every batch.
+    let literal = lit(Series::new("", [Series::new("", [0i32, 1, 2])]));
+    let q = df.lazy().select([
+        col("a").list().union(col("b")).alias("union"),
+        col("a").list().set_difference(col("b")).alias("difference"),
+        col("a")
+            .list()
+            .set_intersection(literal)
+            .alias("intersection"),
+    ]);
+    assert_streaming_with_default(q, false, false);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "list_sets")]
+fn test_streaming_list_set_operation_node() -> PolarsResult<()> {
+    use polars_ops::prelude::list_set_operation;
+
+    let df = list_set_batches()?;
+    let q = df
+        .lazy()
+        .list_set_operation("a", "b", SetOperation::Union, "union")
+        .list_set_operation("a", "b", SetOperation::IsDisjoint, "is_disjoint");
+    assert!(q.describe_plan()?.contains("LIST SET OPERATION: union"));
+    assert_streaming_with_default(q.clone(), true, false);
+
+    let out = q.collect()?;
+    let eager = list_set_batches()?;
+    let expected = list_set_operation(
+        eager.column("a")?.list()?,
+        eager.column("b")?.list()?,
+        SetOperation::Union,
+        Default::default(),
+    )?;
+    assert!(out.column("union")?.equals(&expected.into_series()));
+    assert_eq!(out.column("is_disjoint")?.dtype(), &DataType::Boolean);
+
+    // Only the output is selected, so its operands are pruned after the node.
+    let q = list_set_batches()?
+        .lazy()
+        .list_set_operation("a", "b", SetOperation::Difference, "difference")
+        .select([col("difference")]);
+    assert_streaming_with_default(q, true, false);
+
+    // A literal operand is added as a column first. That projection is not streamable, so it
+    // runs in memory and the node streams the broadcast column after it.
+    let q = list_set_batches()?
+        .lazy()
+        .with_column(lit(Series::new("", [Series::new("", [0i32, 1, 2])])).alias("literal"))
+        .list_set_operation("a", "literal", SetOperation::Intersection, "intersection");
+    assert_streaming_with_default(q, false, false);
+
+    // A non-list operand is reported when the plan is resolved.
+    let mut q = list_set_batches()?
+        .lazy()
+        .with_column(lit(1i32).alias("scalar"))
+        .list_set_operation("a", "scalar", SetOperation::Union, "union");
+    assert!(q.schema().is_err());
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use arrow::array::{Array, BinaryArray, ListArray};
+use arrow::offset::Offset;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use polars_core::prelude::*;
+use polars_ops::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+const N_ROWS: usize = 10_000;
+const WIDTHS: [usize; 3] = [4, 32, 256];
+const SET_OPS: [SetOperation; 4] = [
+    SetOperation::Union,
+    SetOperation::Intersection,
+    SetOperation::Difference,
+    SetOperation::SymmetricDifference,
+];
+
+/// A list column of `n_rows` rows of `width` random elements, drawn from a domain that is
+/// twice as wide so the operands partially overlap.
+fn list_column(n_rows: usize, width: usize, inner: &DataType, rng: &mut SmallRng) -> ListChunked {
+    let rows = (0..n_rows)
+        .map(|_| {
+            let row = (0..width)
+                .map(|_| rng.gen_range(0..2 * width as i64))
+                .collect::<Vec<_>>();
+            let row = Series::new("", row);
+            match inner {
+                DataType::String => row.cast(&DataType::String).unwrap(),
+                DataType::Boolean => row.i64().unwrap().equal(0).into_series(),
+                _ => row,
+            }
+        })
+        .collect::<Vec<_>>();
+    Series::new("", rows).list().unwrap().clone()
+}
+
+fn bench_list_sets(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (name, inner) in [
+        ("i64", DataType::Int64),
+        ("str", DataType::String),
+        ("bool", DataType::Boolean),
+    ] {
+        let mut group = c.benchmark_group(format!("list_sets_{name}"));
+        for width in WIDTHS {
+            let a = list_column(N_ROWS, width, &inner, &mut rng);
+            let b = list_column(N_ROWS, width, &inner, &mut rng);
+            let b_broadcast = list_column(1, width, &inner, &mut rng);
+            for set_op in SET_OPS {
+                let id = BenchmarkId::new(set_op.to_string(), width);
+                group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                    bench.iter(|| list_set_operation(a, b, set_op, Default::default()).unwrap())
+                });
+                let id = BenchmarkId::new(format!("{set_op}_broadcast"), width);
+                group.bench_with_input(id, &(&a, &b_broadcast), |bench, (a, b)| {
+                    bench.iter(|| list_set_operation(a, b, set_op, Default::default()).unwrap())
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+fn bench_is_equal(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_is_equal");
+    for width in [32, 1024] {
+        let a = list_column(N_ROWS / 10, width, &DataType::Int64, &mut rng);
+        let b = list_column(N_ROWS / 10, width, &DataType::Int64, &mut rng);
+        // The same sets in reversed order.
+        let a_reversed = a.lst_reverse();
+        for (name, b) in [("equal", &a_reversed), ("unequal", &b)] {
+            group.bench_with_input(BenchmarkId::new(name, width), &(&a, b), |bench, (a, b)| {
+                bench.iter(|| {
+                    boolean_list_set_operation(
+                        a,
+                        b,
+                        SetOperation::IsEqual,
+                        false,
+                        Default::default(),
+                    )
+                    .unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_sorted(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_sorted");
+    // Sorted and duplicate-free rows, as `list_set_operation_sorted` requires. The elements
+    // are spread out, so that the hash path can't take its bitset shortcut.
+    let mut sorted_column = |width: usize| {
+        let rows = (0..N_ROWS)
+            .map(|_| {
+                let mut row = (0..width)
+                    .map(|_| rng.gen_range(0..4 * width as i64) << 20)
+                    .collect::<Vec<_>>();
+                row.sort_unstable();
+                row.dedup();
+                Series::new("", row)
+            })
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    for width in WIDTHS {
+        let a = sorted_column(width);
+        let b = sorted_column(width);
+        for set_op in SET_OPS {
+            let id = BenchmarkId::new(format!("{set_op}_hash"), width);
+            group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| list_set_operation(a, b, set_op, Default::default()).unwrap())
+            });
+            let id = BenchmarkId::new(format!("{set_op}_merge"), width);
+            group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| list_set_operation_sorted(a, b, set_op).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_sorted_intersection(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_sorted_intersection");
+    // Long rows of integers, which are intersected with SIMD under the `simd` feature. Compare
+    // builds with and without it to see the speedup.
+    for width in [32, 256, 4096] {
+        let mut sorted_column = || {
+            let rows = (0..N_ROWS * 32 / width)
+                .map(|_| {
+                    let mut row = (0..width)
+                        .map(|_| rng.gen_range(0..2 * width as i64))
+                        .collect::<Vec<_>>();
+                    row.sort_unstable();
+                    row.dedup();
+                    Series::new("", row)
+                })
+                .collect::<Vec<_>>();
+            Series::new("", rows).list().unwrap().clone()
+        };
+        let a = sorted_column();
+        let b = sorted_column();
+        for (name, inner) in [("i64", DataType::Int64), ("i32", DataType::Int32)] {
+            let list_dtype = DataType::List(Box::new(inner));
+            let a = a.cast(&list_dtype).unwrap().list().unwrap().clone();
+            let b = b.cast(&list_dtype).unwrap().list().unwrap().clone();
+            let id = BenchmarkId::new(name, width);
+            group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| list_set_operation_sorted(a, b, SetOperation::Intersection).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_union_strategy(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_union_strategy");
+    for width in WIDTHS {
+        // Spread out elements, so that the hash strategy can't take its bitset shortcut.
+        let mut spread_column = || {
+            list_column(N_ROWS, width, &DataType::Int64, &mut rng)
+                .apply_to_inner(&|s| Ok(s.i64().unwrap().apply_values(|v| v << 20).into_series()))
+                .unwrap()
+        };
+        let a = spread_column();
+        let b = spread_column();
+        for strategy in [UnionStrategy::Hash, UnionStrategy::SortDedup] {
+            let options = SetOpOptions::default().with_union_strategy(strategy);
+            let id = BenchmarkId::new(format!("{strategy:?}"), width);
+            group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| list_set_operation(a, b, SetOperation::Union, options).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_dos_resistant_hashing(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_dos_resistant_hashing");
+    for width in WIDTHS {
+        let a = list_column(N_ROWS, width, &DataType::String, &mut rng);
+        let b = list_column(N_ROWS, width, &DataType::String, &mut rng);
+        for (name, enabled) in [("default", false), ("siphash", true)] {
+            let options = SetOpOptions::default().with_dos_resistant_hashing(enabled);
+            group.bench_with_input(BenchmarkId::new(name, width), &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| list_set_operation(a, b, SetOperation::Union, options).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// A list column of `width` random elements per row, of which the values are `Binary` with
+/// offsets of type `O`, as imported over FFI.
+fn offset_binary_column<O: Offset>(n_rows: usize, width: usize, rng: &mut SmallRng) -> ListChunked {
+    let values = (0..n_rows * width)
+        .map(|_| rng.gen_range(0..2 * width as i64).to_string().into_bytes())
+        .collect::<Vec<_>>();
+    let values = BinaryArray::<O>::from_iter_values(values.iter()).boxed();
+    let offsets = (0..=n_rows as i64)
+        .map(|row| row * width as i64)
+        .collect::<Vec<_>>();
+    let arr = ListArray::<i64>::new(
+        ListArray::<i64>::default_datatype(values.data_type().clone()),
+        offsets.try_into().unwrap(),
+        values,
+        None,
+    );
+    // SAFETY: the dtype is derived from the array.
+    unsafe { ListChunked::from_chunks("", vec![arr.boxed()]) }
+}
+
+fn bench_small_binary(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_small_binary");
+    for width in WIDTHS {
+        let a = offset_binary_column::<i32>(N_ROWS, width, &mut rng);
+        // The same values with i64 offsets, so that both sides are promoted to views.
+        let b_seed = rng.gen();
+        let b = offset_binary_column::<i32>(N_ROWS, width, &mut SmallRng::seed_from_u64(b_seed));
+        let large_b =
+            offset_binary_column::<i64>(N_ROWS, width, &mut SmallRng::seed_from_u64(b_seed));
+        for (name, b) in [("direct", &b), ("promote", &large_b)] {
+            group.bench_with_input(BenchmarkId::new(name, width), &(&a, b), |bench, (a, b)| {
+                bench.iter(|| {
+                    list_set_operation(a, b, SetOperation::Union, Default::default()).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_runs(c: &mut Criterion) {
+    const RUN_LEN: usize = 100;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("list_sets_runs");
+    for width in WIDTHS {
+        // Every distinct row repeated `RUN_LEN` times.
+        let mut runs_column = || {
+            let distinct = list_column(N_ROWS / RUN_LEN, width, &DataType::Int64, &mut rng);
+            let idx = (0..N_ROWS)
+                .map(|row| (row / RUN_LEN) as IdxSize)
+                .collect::<Vec<_>>();
+            distinct.take(&IdxCa::from_vec("", idx)).unwrap()
+        };
+        let a = runs_column();
+        let b = runs_column();
+        for (name, runs) in [("plain", false), ("runs", true)] {
+            let id = BenchmarkId::new(name, width);
+            group.bench_with_input(id, &(&a, &b), |bench, (a, b)| {
+                bench.iter(|| {
+                    let out = if runs {
+                        list_set_operation_runs(a, b, SetOperation::Union, Default::default())
+                    } else {
+                        list_set_operation(a, b, SetOperation::Union, Default::default())
+                    };
+                    out.unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_tiny_calls(c: &mut Criterion) {
+    const N_CALLS: usize = 100_000;
+    let mut group = c.benchmark_group("list_sets_tiny_calls");
+    // Spread out elements, so that the hash sets rather than bitsets are used.
+    let list = |row: [i64; 4]| {
+        Series::new("", [Series::new("", row)])
+            .list()
+            .unwrap()
+            .clone()
+    };
+    let a = list([1, 1000, 2000, 3000]);
+    let b = list([3000, 4000, 1, 5000]);
+    for (name, pooled) in [("fresh", false), ("pooled", true)] {
+        group.bench_function(name, |bench| {
+            bench.iter(|| {
+                for _ in 0..N_CALLS {
+                    let out = if pooled {
+                        list_set_operation_pooled(&a, &b, SetOperation::Union, Default::default())
+                    } else {
+                        list_set_operation(&a, &b, SetOperation::Union, Default::default())
+                    };
+                    out.unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_list_sets,
+    bench_is_equal,
+    bench_sorted,
+    bench_sorted_intersection,
+    bench_union_strategy,
+    bench_dos_resistant_hashing,
+    bench_small_binary,
+    bench_runs,
+    bench_tiny_calls
+);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use polars_core::prelude::*;
+use polars_ops::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+const N_ELEMENTS: usize = 1 << 20;
+const WIDTHS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// A list column of `Int32` rows of `width` random elements, `N_ELEMENTS` in total, drawn from
+/// a domain of `spread` times the width, so the operands partially overlap.
+fn list_column(width: usize, spread: usize, rng: &mut SmallRng) -> ListChunked {
+    let rows = (0..N_ELEMENTS / width)
+        .map(|_| {
+            let row = (0..width)
+                .map(|_| rng.gen_range(0..(spread * width) as i32))
+                .collect::<Vec<_>>();
+            Series::new("", row)
+        })
+        .collect::<Vec<_>>();
+    Series::new("", rows).list().unwrap().clone()
+}
+
+fn bench_roaring(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Dense rows, of which the hash path takes the bitset fast path, and sparse rows.
+    for (density, spread) in [("dense", 2), ("sparse", 1000)] {
+        for set_op in [SetOperation::Union, SetOperation::Intersection] {
+            let name = format!("list_sets_roaring_{density}_{set_op}");
+            let mut group = c.benchmark_group(name);
+            for width in WIDTHS {
+                let a = list_column(width, spread, &mut rng);
+                let b = list_column(width, spread, &mut rng);
+                let input = (&a, &b);
+                group.bench_with_input(BenchmarkId::new("hash", width), &input, |bench, (a, b)| {
+                    bench.iter(|| list_set_operation(a, b, set_op, Default::default()).unwrap())
+                });
+                group.bench_with_input(
+                    BenchmarkId::new("roaring", width),
+                    &input,
+                    |bench, (a, b)| {
+                        bench.iter(|| list_set_operation_roaring(a, b, set_op).unwrap())
+                    },
+                );
+            }
+            group.finish();
+        }
+    }
+}
+
+criterion_group!(benches, bench_roaring);
+criterion_main!(benches);
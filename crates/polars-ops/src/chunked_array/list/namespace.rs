@@ -275,6 +275,34 @@ pub trait ListNameSpaceImpl: AsList {
         Ok(self.same_type(out))
     }
 
+    /// Apply `set_op` between the lists of `self` and `other` row by row, the method form of
+    /// [`list_set_operation`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use polars_core::prelude::*;
+    /// # use polars_ops::prelude::*;
+    /// let a = Series::new("a", [Series::new("", [1i32, 2, 3])]);
+    /// let b = Series::new("b", [Series::new("", [3i32, 2, 4])]);
+    ///
+    /// let out = a
+    ///     .list()?
+    ///     .lst_set_operation(b.list()?, SetOperation::Intersection, Default::default())?;
+    /// let row = out.get_as_series(0).unwrap();
+    /// assert_eq!(Vec::from(row.i32()?), &[Some(2), Some(3)]);
+    /// # Ok::<(), PolarsError>(())
+    /// ```
+    #[cfg(feature = "list_sets")]
+    fn lst_set_operation(
+        &self,
+        other: &ListChunked,
+        set_op: SetOperation,
+        options: SetOpOptions,
+    ) -> PolarsResult<ListChunked> {
+        list_set_operation(self.as_list(), other, set_op, options)
+    }
+
     fn lst_arg_min(&self) -> IdxCa {
         let ca = self.as_list();
         ca.apply_amortized_generic(|opt_s| {
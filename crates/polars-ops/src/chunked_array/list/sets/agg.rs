@@ -0,0 +1,141 @@
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+use super::SetOperation;
+
+struct GroupedSetOperation<'a> {
+    offsets: &'a [i64],
+    validity: Option<&'a Bitmap>,
+    values_validity: Option<&'a Bitmap>,
+    groups: &'a GroupsProxy,
+    set_op: SetOperation,
+    max_distinct: Option<usize>,
+    nulls_last: bool,
+}
+
+impl ManyKernel for GroupedSetOperation<'_> {
+    type Output = (ListIdxBuilder, MutableBitmap);
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let values = values[0];
+        let mut out = ListIdxBuilder::new(self.groups.len());
+        let mut validity = MutableBitmap::with_capacity(self.groups.len());
+        // Element -> (index of its first occurrence, number of rows it occurs in, last row,
+        // whether it occurs in the first valid row).
+        let mut counts = PlIndexMap::default();
+
+        let mut fold_group = |rows: &mut dyn Iterator<Item = usize>| -> PolarsResult<()> {
+            counts.clear();
+            let mut n_valid = 0;
+            for row in rows {
+                if matches!(self.validity, Some(v) if !v.get_bit(row)) {
+                    continue;
+                }
+                for i in span(self.offsets, row) {
+                    // SAFETY: the offsets of a list array are in bounds of its values.
+                    let key = unsafe { values.key_unchecked(i) };
+                    let (_, count, last_row, in_first) =
+                        counts
+                            .entry(key)
+                            .or_insert((i, 0usize, usize::MAX, n_valid == 0));
+                    if *last_row != row {
+                        *count += 1;
+                        *last_row = row;
+                    }
+                    *in_first |= n_valid == 0;
+                }
+                if let Some(max_distinct) = self.max_distinct {
+                    polars_ensure!(
+                        counts.len() <= max_distinct,
+                        ComputeError: "list 'set' aggregation exceeded `max_distinct`: a group holds more than {} distinct elements",
+                        max_distinct
+                    );
+                }
+                n_valid += 1;
+            }
+
+            let keep = |count: usize, in_first: bool| match self.set_op {
+                SetOperation::Union => true,
+                SetOperation::Intersection => count == n_valid,
+                SetOperation::Difference => in_first && count == 1,
+                SetOperation::SymmetricDifference => count % 2 == 1,
+                op => unreachable!("boolean operation {op} has no list output"),
+            };
+            let is_null = |i: usize| matches!(self.values_validity, Some(v) if !v.get_bit(i));
+            // The null element, if any, is moved to the front or the back.
+            let null = counts
+                .values()
+                .find(|(i, ..)| is_null(*i))
+                .filter(|&&(_, count, _, in_first)| keep(count, in_first))
+                .map(|&(i, ..)| i);
+            if let (Some(i), false) = (null, self.nulls_last) {
+                out.push(i);
+            }
+            for &(i, count, _, in_first) in counts.values() {
+                if keep(count, in_first) && !is_null(i) {
+                    out.push(i);
+                }
+            }
+            if let (Some(i), true) = (null, self.nulls_last) {
+                out.push(i);
+            }
+            validity.push(n_valid > 0);
+            out.finish_row();
+            Ok(())
+        };
+
+        for group in self.groups.iter() {
+            match group {
+                GroupsIndicator::Idx((_, idx)) => {
+                    fold_group(&mut idx.iter().map(|&row| row as usize))?
+                },
+                GroupsIndicator::Slice([first, len]) => {
+                    fold_group(&mut (first as usize..(first + len) as usize))?
+                },
+            }
+        }
+        Ok((out, validity))
+    }
+}
+
+/// Aggregate the lists of every group of `groups` into a single list by folding `set_op` over
+/// the rows of the group, e.g. the union of all lists of a group.
+///
+/// The fold of [`SetOperation::Difference`] keeps the elements of the first row of a group
+/// that occur in no other row, the fold of [`SetOperation::SymmetricDifference`] keeps the
+/// elements that occur in an odd number of rows. Null rows are ignored and groups without a
+/// valid row are null. Elements keep their order of first appearance within the group, except
+/// for the null element, which is put first, or last if `nulls_last` is set. This makes the
+/// output reproducible, e.g. of the union of a whole column as a single group.
+///
+/// If `max_distinct` is set, a group that accumulates more distinct elements than that is an
+/// error. This bounds the memory spent on untrusted input.
+pub fn list_set_operation_agg(
+    a: &ListChunked,
+    groups: &GroupsProxy,
+    set_op: SetOperation,
+    max_distinct: Option<usize>,
+    nulls_last: bool,
+) -> PolarsResult<ListChunked> {
+    polars_ensure!(
+        !set_op.is_boolean(),
+        InvalidOperation: "'{}' is a boolean list 'set' operation and can't be aggregated", set_op
+    );
+    let a = a.rechunk();
+    let arr = a.downcast_iter().next().unwrap();
+    let kernel = GroupedSetOperation {
+        offsets: arr.offsets().as_slice(),
+        validity: arr.validity(),
+        values_validity: arr.values().validity(),
+        groups,
+        set_op,
+        max_distinct,
+        nulls_last,
+    };
+    let (builder, validity) = with_many_set_values(&[arr.values().as_ref()], kernel)?;
+    // SAFETY: we gather from the values of `arr` itself.
+    let out = unsafe { builder.finish(arr.values().as_ref(), validity.into()) };
+    // SAFETY: the output values are gathered from `a`, so the dtype is unchanged.
+    Ok(unsafe { a.with_chunks(vec![out.boxed()]) })
+}
@@ -0,0 +1,84 @@
+use arrow::compute::concatenate::concatenate;
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+use super::{combine_many_validities, prepare_many_operands};
+
+struct AtLeastK<'a> {
+    offsets: Vec<&'a [i64]>,
+    n_rows: usize,
+    k: usize,
+}
+
+impl ManyKernel for AtLeastK<'_> {
+    type Output = ListIdxBuilder;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let mut out = ListIdxBuilder::new(self.n_rows);
+        // Element -> (index of its first occurrence, number of columns it occurs in, last column).
+        let mut counts = PlIndexMap::default();
+
+        for row in 0..self.n_rows {
+            counts.clear();
+            let mut base = 0;
+            for (col, (values, offsets)) in values.iter().zip(&self.offsets).enumerate() {
+                for i in span(offsets, row) {
+                    // SAFETY: the offsets of a list array are in bounds of its values.
+                    let key = unsafe { values.key_unchecked(i) };
+                    let (_, count, last_col) =
+                        counts.entry(key).or_insert((base + i, 0usize, usize::MAX));
+                    if *last_col != col {
+                        *count += 1;
+                        *last_col = col;
+                    }
+                }
+                base += values.len();
+            }
+            counts
+                .values()
+                .filter(|(_, count, _)| *count >= self.k)
+                .for_each(|(i, _, _)| out.push(*i));
+            out.finish_row();
+        }
+        Ok(out)
+    }
+}
+
+/// Keep per row the distinct elements that appear in at least `k` of the `columns`.
+///
+/// With `k == columns.len()` this is the intersection of all columns and with `k == 1` it is
+/// their union. Columns of length 1 are broadcast and a null row in any of the columns results
+/// in a null row. Elements keep their order of first appearance.
+pub fn list_set_at_least_k(columns: &[&ListChunked], k: usize) -> PolarsResult<ListChunked> {
+    polars_ensure!(!columns.is_empty(), ComputeError: "`list_set_at_least_k` needs at least one column");
+    polars_ensure!(
+        k >= 1 && k <= columns.len(),
+        ComputeError: "`k` must be between 1 and the number of columns ({}), got {}", columns.len(), k
+    );
+    let (columns, len) = prepare_many_operands(columns)?;
+    if len == 0 {
+        return Ok(columns[0].clone());
+    }
+
+    let arrays = columns
+        .iter()
+        .map(|ca| ca.downcast_iter().next().unwrap())
+        .collect::<Vec<_>>();
+    let kernel = AtLeastK {
+        offsets: arrays.iter().map(|arr| arr.offsets().as_slice()).collect(),
+        n_rows: len,
+        k,
+    };
+    let inner_values = arrays
+        .iter()
+        .map(|arr| arr.values().as_ref())
+        .collect::<Vec<_>>();
+    let builder = with_many_set_values(&inner_values, kernel)?;
+
+    let values = concatenate(&inner_values)?;
+    let validity = combine_many_validities(&arrays, len);
+    // SAFETY: `values` are the concatenated values of all columns.
+    let arr = unsafe { builder.finish(values.as_ref(), validity) };
+    // SAFETY: the output values are gathered from the columns, so the dtype is unchanged.
+    Ok(unsafe { columns[0].with_chunks(vec![arr.boxed()]) })
+}
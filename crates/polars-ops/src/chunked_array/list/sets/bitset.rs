@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use arrow::array::{Array, MutableArray, MutablePrimitiveArray, PrimitiveArray};
+use arrow::bitmap::MutableBitmap;
+use arrow::types::{NativeType, PrimitiveType};
+use num_traits::ToPrimitive;
+
+use super::SetOperation;
+
+/// Scratch space of [`small_domain_set_operation`] that is reused between rows.
+#[derive(Default)]
+pub(super) struct DomainBitsets {
+    in_a: MutableBitmap,
+    in_b: MutableBitmap,
+    seen: MutableBitmap,
+}
+
+impl DomainBitsets {
+    pub(super) fn reset(&mut self, len: usize) {
+        for bitmap in [&mut self.in_a, &mut self.in_b, &mut self.seen] {
+            bitmap.clear();
+            bitmap.extend_constant(len, false);
+        }
+    }
+}
+
+fn row_values<T: NativeType>(
+    arr: &PrimitiveArray<T>,
+    span: Range<usize>,
+) -> impl Iterator<Item = Option<T>> + '_ {
+    span.map(move |i| arr.is_valid(i).then(|| arr.value(i)))
+}
+
+/// Apply `set_op` on a single row of integers with bitsets over the domain `min..=max` of the
+/// row, if that domain spans at most `max_bits_per_element` bits per element of the row, see
+/// [`SetOpOptions::bitset_bits_per_element`](super::SetOpOptions::bitset_bits_per_element).
+///
+/// Returns the new length of `out`, or `None` if the row should use the hash set path. The
+/// output is in the same order as the one of the hash set path: elements in order of their
+/// first occurrence, the elements of `a` before those of `b`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn small_domain_set_operation<T>(
+    a: &PrimitiveArray<T>,
+    span_a: Range<usize>,
+    b: &PrimitiveArray<T>,
+    span_b: Range<usize>,
+    set_op: SetOperation,
+    out: &mut MutablePrimitiveArray<T>,
+    bitsets: &mut DomainBitsets,
+    max_bits_per_element: usize,
+) -> Option<usize>
+where
+    T: NativeType + ToPrimitive,
+{
+    if matches!(
+        T::PRIMITIVE,
+        PrimitiveType::Float16 | PrimitiveType::Float32 | PrimitiveType::Float64
+    ) {
+        return None;
+    }
+    let n_elements = span_a.len() + span_b.len();
+    let (min, max) = row_values(a, span_a.clone())
+        .chain(row_values(b, span_b.clone()))
+        .flatten()
+        .filter_map(|v| v.to_i64())
+        .fold((i64::MAX, i64::MIN), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        // Only nulls (or no elements at all), that is cheap in any path.
+        return None;
+    }
+    let domain = max.checked_sub(min)?.to_usize()?.checked_add(1)?;
+    if domain > n_elements.saturating_mul(max_bits_per_element) {
+        return None;
+    }
+    // Values that don't fit an i64 are out of the domain; bail out on them.
+    let bit = |v: T| -> Option<usize> { Some((v.to_i64()? - min) as usize) };
+
+    bitsets.reset(domain);
+    let (mut null_in_a, mut null_in_b, mut null_seen) = (false, false, false);
+    for v in row_values(a, span_a.clone()) {
+        match v {
+            Some(v) => bitsets.in_a.set(bit(v)?, true),
+            None => null_in_a = true,
+        }
+    }
+    for v in row_values(b, span_b.clone()) {
+        match v {
+            Some(v) => bitsets.in_b.set(bit(v)?, true),
+            None => null_in_b = true,
+        }
+    }
+
+    let mut emit = |v: Option<T>, keep_valid: &dyn Fn(usize) -> bool, keep_null: bool| match v {
+        Some(v) => {
+            let bit = (v.to_i64().unwrap() - min) as usize;
+            if keep_valid(bit) && !bitsets.seen.get(bit) {
+                bitsets.seen.set(bit, true);
+                out.push(Some(v));
+            }
+        },
+        None => {
+            if keep_null && !null_seen {
+                null_seen = true;
+                out.push(None);
+            }
+        },
+    };
+
+    let in_a = |bit: usize| bitsets.in_a.get(bit);
+    let in_b = |bit: usize| bitsets.in_b.get(bit);
+    match set_op {
+        SetOperation::Intersection => {
+            for v in row_values(a, span_a) {
+                emit(v, &in_b, null_in_b);
+            }
+        },
+        SetOperation::Union => {
+            for v in row_values(a, span_a).chain(row_values(b, span_b)) {
+                emit(v, &|_| true, true);
+            }
+        },
+        SetOperation::Difference => {
+            for v in row_values(a, span_a) {
+                emit(v, &|bit| !in_b(bit), !null_in_b);
+            }
+        },
+        SetOperation::SymmetricDifference => {
+            for v in row_values(a, span_a) {
+                emit(v, &|bit| !in_b(bit), !null_in_b);
+            }
+            for v in row_values(b, span_b) {
+                emit(v, &|bit| !in_a(bit), !null_in_a);
+            }
+        },
+        op => unreachable!("boolean operation {op} has no list output"),
+    }
+    Some(out.len())
+}
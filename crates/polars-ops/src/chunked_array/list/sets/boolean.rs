@@ -0,0 +1,258 @@
+use std::hash::Hash;
+use std::ops::Range;
+
+use arrow::array::{Array, BooleanArray};
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use arrow::compute::utils::combine_validities_and;
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::pairwise::{row_spans, with_set_values, PairKernel, SetValues};
+use super::{combine_validities, prepare_operands, SetOperation};
+
+/// How null elements take part in the operations of [`boolean_list_set_operation`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NullLogic {
+    /// A null element is a regular member of a set that only equals other nulls.
+    #[default]
+    SetMember,
+    /// A null element is an unknown value, as in SQL: the result is null if it depends on what
+    /// the null elements are. E.g. `[1, null]` may or may not be a subset of `[1, 2]`, but it is
+    /// certainly not a subset of `[3]`.
+    ThreeValued,
+}
+
+/// Whether all elements of `a` are in `b`, where `null_a` and `null_b` say whether the lists
+/// have unknown elements besides those in the sets. `None` if that depends on the unknowns.
+fn three_valued_subset<K: Hash + Eq>(
+    (a, null_a): (&PlHashSet<K>, bool),
+    (b, null_b): (&PlHashSet<K>, bool),
+) -> Option<bool> {
+    let mut known = true;
+    for v in a {
+        if !b.contains(v) {
+            if !null_b {
+                return Some(false);
+            }
+            known = false;
+        }
+    }
+    if null_a {
+        // An unknown element is certainly not in an empty list.
+        if b.is_empty() && !null_b {
+            return Some(false);
+        }
+        known = false;
+    }
+    known.then_some(true)
+}
+
+/// The [`three_valued_subset`] counterpart of [`PlHashSet::is_disjoint`].
+fn three_valued_disjoint<K: Hash + Eq>(
+    (a, null_a): (&PlHashSet<K>, bool),
+    (b, null_b): (&PlHashSet<K>, bool),
+) -> Option<bool> {
+    if a.iter().any(|v| b.contains(v)) {
+        return Some(false);
+    }
+    let empty_a = a.is_empty() && !null_a;
+    let empty_b = b.is_empty() && !null_b;
+    let known = !(null_a && !empty_b) && !(null_b && !empty_a);
+    known.then_some(true)
+}
+
+/// Whether the elements of `span_short` of `short` and `span_long` of `long` make up the same
+/// set, where `set_short` and `set_long` are empty scratch sets.
+fn probe_equal<'v, V: SetValues>(
+    short: &'v V,
+    span_short: Range<usize>,
+    long: &'v V,
+    span_long: Range<usize>,
+    set_short: &mut PlHashSet<V::Key<'v>>,
+    set_long: &mut PlHashSet<V::Key<'v>>,
+) -> bool {
+    // SAFETY: the offsets of a list array are in bounds of its values.
+    set_short.extend(span_short.map(|i| unsafe { short.key_unchecked(i) }));
+    for i in span_long {
+        let key = unsafe { long.key_unchecked(i) };
+        if !set_short.contains(&key) {
+            return false;
+        }
+        set_long.insert(key);
+    }
+    // Every element of the long list is in the short one, so the sets are equal if they are
+    // of the same size.
+    set_short.len() == set_long.len()
+}
+
+pub(super) struct BooleanSetOperation<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    validity_a: Option<&'a Bitmap>,
+    validity_b: Option<&'a Bitmap>,
+    set_op: SetOperation,
+    tri_state: bool,
+    null_logic: NullLogic,
+}
+
+impl PairKernel for BooleanSetOperation<'_> {
+    type Output = (MutableBitmap, Option<MutableBitmap>);
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        // Checked by `boolean_set_operation_impl`, which builds the kernel.
+        debug_assert!(self.set_op.is_boolean());
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let three_valued = self.null_logic == NullLogic::ThreeValued;
+        let mut out = MutableBitmap::with_capacity(n_rows);
+        let mut defined =
+            (self.tri_state || three_valued).then(|| MutableBitmap::with_capacity(n_rows));
+        let mut set_a = PlHashSet::new();
+        let mut set_b = PlHashSet::new();
+        // Under three-valued logic null elements are tracked separately from the sets.
+        let is_unknown = |validity: Option<&Bitmap>, i: usize| {
+            three_valued && matches!(validity, Some(v) if !v.get_bit(i))
+        };
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            let (empty_a, empty_b) = (span_a.is_empty(), span_b.is_empty());
+            set_a.clear();
+            set_b.clear();
+            if self.set_op == SetOperation::IsEqual && !three_valued {
+                // Build the set of the shorter list and probe it with the longer one, which
+                // stops at the first element that is missing.
+                let value = if span_a.len() <= span_b.len() {
+                    probe_equal(a, span_a, b, span_b, &mut set_a, &mut set_b)
+                } else {
+                    probe_equal(b, span_b, a, span_a, &mut set_b, &mut set_a)
+                };
+                out.push(value);
+                if let Some(defined) = defined.as_mut() {
+                    defined.push(!(empty_a && empty_b));
+                }
+                continue;
+            }
+            let (mut null_a, mut null_b) = (false, false);
+            for i in span_a {
+                if is_unknown(self.validity_a, i) {
+                    null_a = true;
+                } else {
+                    // SAFETY: the offsets of a list array are in bounds of its values.
+                    set_a.insert(unsafe { a.key_unchecked(i) });
+                }
+            }
+            for i in span_b {
+                if is_unknown(self.validity_b, i) {
+                    null_b = true;
+                } else {
+                    set_b.insert(unsafe { b.key_unchecked(i) });
+                }
+            }
+
+            let (a, b) = ((&set_a, null_a), (&set_b, null_b));
+            let (value, vacuous) = match self.set_op {
+                SetOperation::IsDisjoint => (three_valued_disjoint(a, b), empty_a || empty_b),
+                SetOperation::IsSubset => (three_valued_subset(a, b), empty_a),
+                SetOperation::IsSuperset => (three_valued_subset(b, a), empty_b),
+                _ => {
+                    let value = match (three_valued_subset(a, b), three_valued_subset(b, a)) {
+                        (Some(false), _) | (_, Some(false)) => Some(false),
+                        (Some(true), Some(true)) => Some(true),
+                        _ => None,
+                    };
+                    (value, empty_a && empty_b)
+                },
+            };
+            out.push(value.unwrap_or(false));
+            if let Some(defined) = defined.as_mut() {
+                defined.push(value.is_some() && !(self.tri_state && vacuous));
+            }
+        }
+        Ok((out, defined))
+    }
+}
+
+/// Evaluate the boolean [`SetOperation`] `set_op` per row, e.g. whether the lists of `a` are a
+/// subset of those of `b`. Elements are compared as sets, so duplicates don't matter. Under
+/// [`NullLogic::SetMember`] a null element is a regular member that only equals other nulls,
+/// e.g. `[null]` is a subset of `[1, null]` but not of `[1, 2]`, see [`NullLogic`] for the
+/// alternative.
+///
+/// Null rows in either operand result in null. If `tri_state` is set, rows where the result
+/// only holds vacuously because of an empty operand are null as well:
+/// * [`SetOperation::IsDisjoint`] if either list is empty,
+/// * [`SetOperation::IsSubset`] if the list of `a` is empty,
+/// * [`SetOperation::IsSuperset`] if the list of `b` is empty,
+/// * [`SetOperation::IsEqual`] if both lists are empty.
+pub fn boolean_list_set_operation(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    tri_state: bool,
+    null_logic: NullLogic,
+) -> PolarsResult<BooleanChunked> {
+    boolean_set_operation_impl(
+        a,
+        b,
+        set_op,
+        tri_state,
+        null_logic,
+        |values_a, values_b, kernel| with_set_values(values_a, values_b, kernel),
+    )
+}
+
+/// Evaluate a boolean [`SetOperation`] per pair of aligned chunks, where `call` runs the kernel
+/// on the inner values of both chunks.
+pub(super) fn boolean_set_operation_impl<C>(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    tri_state: bool,
+    null_logic: NullLogic,
+    call: C,
+) -> PolarsResult<BooleanChunked>
+where
+    C: Fn(
+        &dyn Array,
+        &dyn Array,
+        BooleanSetOperation<'_>,
+    ) -> PolarsResult<(MutableBitmap, Option<MutableBitmap>)>,
+{
+    polars_ensure!(
+        set_op.is_boolean(),
+        InvalidOperation: "'{}' is not a boolean list 'set' operation, use `list_set_operation`", set_op
+    );
+    let (a, b) = prepare_operands(a, b)?;
+    if a.is_empty() || b.is_empty() {
+        return Ok(BooleanChunked::full_null(a.name(), 0));
+    }
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let chunks = a
+        .downcast_iter()
+        .zip(b.downcast_iter())
+        .map(|(arr_a, arr_b)| {
+            let kernel = BooleanSetOperation {
+                offsets_a: arr_a.offsets().as_slice(),
+                offsets_b: arr_b.offsets().as_slice(),
+                validity_a: arr_a.values().validity(),
+                validity_b: arr_b.values().validity(),
+                set_op,
+                tri_state,
+                null_logic,
+            };
+            let (values, defined) = call(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+            let defined: Option<Bitmap> = defined.and_then(|d| d.into());
+            let validity =
+                combine_validities_and(combine_validities(arr_a, arr_b).as_ref(), defined.as_ref());
+            Ok(BooleanArray::new(
+                ArrowDataType::Boolean,
+                values.into(),
+                validity,
+            ))
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    Ok(BooleanChunked::from_chunk_iter(a.name(), chunks))
+}
@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+
+use arrow::array::{Array, BinaryViewArray, ListArray, MutableBinaryViewArray, Utf8ViewArray};
+use polars_core::prelude::*;
+
+use super::downcast_values;
+use super::pairwise::row_spans;
+
+/// Replace every string element of `out` by the variant of its key that occurs most often in
+/// the row it comes from, across both operands. Ties go to the variant seen first.
+///
+/// `a` and `b` hold the values the output was gathered from, e.g. trimmed.
+pub(super) fn most_frequent_casing<F>(
+    out: ListArray<i64>,
+    a: &BinaryViewArray,
+    b: &BinaryViewArray,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    key: &F,
+) -> PolarsResult<ListArray<i64>>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+{
+    let values = downcast_values::<Utf8ViewArray>(out.values().as_ref())?;
+    let mut resolved = MutableBinaryViewArray::<str>::with_capacity(values.len());
+    // Key -> variant -> count, in order of first occurrence.
+    let mut variants: PlHashMap<Cow<[u8]>, PlIndexMap<&[u8], usize>> = PlHashMap::default();
+
+    let out_spans = out
+        .offsets()
+        .as_slice()
+        .windows(2)
+        .map(|w| w[0] as usize..w[1] as usize);
+    for ((span_a, span_b), out_span) in row_spans(offsets_a, offsets_b).zip(out_spans) {
+        variants.clear();
+        let row_a = span_a.filter_map(|i| a.get(i));
+        let row_b = span_b.filter_map(|i| b.get(i));
+        for v in row_a.chain(row_b) {
+            *variants.entry(key(v)).or_default().entry(v).or_insert(0) += 1;
+        }
+        for v in out_span.map(|i| values.get(i)) {
+            let resolved_v = v.map(|v| {
+                let row_variants = &variants[&key(v.as_bytes())];
+                // `max_by_key` keeps the last maximum, so the variants are visited backwards.
+                let (&variant, _) = row_variants.iter().rev().max_by_key(|(_, &n)| n).unwrap();
+                // SAFETY: the variants are the bytes of string elements.
+                unsafe { std::str::from_utf8_unchecked(variant) }
+            });
+            resolved.push(resolved_v);
+        }
+    }
+    Ok(with_values(&out, resolved.freeze().boxed()))
+}
+
+/// Lowercase every string element of `out`.
+pub(super) fn lowercase_values(out: ListArray<i64>) -> PolarsResult<ListArray<i64>> {
+    let values = downcast_values::<Utf8ViewArray>(out.values().as_ref())?;
+    let mut lowercase = MutableBinaryViewArray::<str>::with_capacity(values.len());
+    for v in values.iter() {
+        lowercase.push(v.map(str::to_lowercase));
+    }
+    Ok(with_values(&out, lowercase.freeze().boxed()))
+}
+
+/// `out` with its values replaced by `values` of the same length and dtype.
+fn with_values(out: &ListArray<i64>, values: Box<dyn Array>) -> ListArray<i64> {
+    ListArray::new(
+        out.data_type().clone(),
+        out.offsets().clone(),
+        values,
+        out.validity().cloned(),
+    )
+}
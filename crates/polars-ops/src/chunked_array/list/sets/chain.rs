@@ -0,0 +1,123 @@
+use arrow::compute::concatenate::concatenate;
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+use super::{combine_many_validities, ensure_list_output, prepare_many_operands, SetOperation};
+
+struct ChainedSetOperation<'a> {
+    offsets: [&'a [i64]; 3],
+    n_rows: usize,
+    op1: SetOperation,
+    op2: SetOperation,
+}
+
+/// Keep the elements of `lhs` and `rhs` that `set_op` selects, in order of first occurrence,
+/// those of `lhs` before those of `rhs`, pushing their indices to `out`.
+fn select<K: std::hash::Hash + Eq>(
+    lhs: &PlIndexMap<K, usize>,
+    rhs: &PlIndexMap<K, usize>,
+    set_op: SetOperation,
+    mut out: impl FnMut(&K, usize),
+) {
+    let keep_lhs = |in_rhs: bool| match set_op {
+        SetOperation::Intersection => in_rhs,
+        SetOperation::Union => true,
+        SetOperation::Difference | SetOperation::SymmetricDifference => !in_rhs,
+        op => unreachable!("boolean operation {op} has no list output"),
+    };
+    for (key, &i) in lhs {
+        if keep_lhs(rhs.contains_key(key)) {
+            out(key, i);
+        }
+    }
+    let keep_rhs_only = matches!(
+        set_op,
+        SetOperation::Union | SetOperation::SymmetricDifference
+    );
+    if keep_rhs_only {
+        for (key, &i) in rhs {
+            if !lhs.contains_key(key) {
+                out(key, i);
+            }
+        }
+    }
+}
+
+impl ManyKernel for ChainedSetOperation<'_> {
+    type Output = ListIdxBuilder;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let mut out = ListIdxBuilder::new(self.n_rows);
+        // Element -> index of its first occurrence, where the elements of `b` are addressed
+        // after those of `a` and the elements of `c` after those of `b`.
+        let mut sets: [PlIndexMap<_, usize>; 3] = Default::default();
+        let mut set_ab = PlIndexMap::default();
+
+        for row in 0..self.n_rows {
+            let mut base = 0;
+            for ((set, values), offsets) in sets.iter_mut().zip(values).zip(self.offsets) {
+                set.clear();
+                for i in span(offsets, row) {
+                    // SAFETY: the offsets of a list array are in bounds of its values.
+                    let key = unsafe { values.key_unchecked(i) };
+                    set.entry(key).or_insert(base + i);
+                }
+                base += values.len();
+            }
+            let [a, b, c] = &sets;
+            // The result of `op1` is kept as a set, rather than materialized as a list.
+            set_ab.clear();
+            select(a, b, self.op1, |key, i| {
+                set_ab.insert(*key, i);
+            });
+            select(&set_ab, c, self.op2, |_, i| out.push(i));
+            out.finish_row();
+        }
+        Ok(out)
+    }
+}
+
+/// Compute `op2(op1(a, b), c)` per row in a single pass, e.g. `(a ∩ b) \ c`, without
+/// materializing the intermediate list column.
+///
+/// The output is the same as of two calls to [`list_set_operation`](super::list_set_operation)
+/// with default options: elements in order of their first occurrence, those of `a` before those
+/// of `b` before those of `c`. Columns of length 1 are broadcast and a null row in any of the
+/// columns results in a null row.
+pub fn list_set_operation_chain(
+    a: &ListChunked,
+    b: &ListChunked,
+    op1: SetOperation,
+    c: &ListChunked,
+    op2: SetOperation,
+) -> PolarsResult<ListChunked> {
+    ensure_list_output(op1)?;
+    ensure_list_output(op2)?;
+    let (columns, len) = prepare_many_operands(&[a, b, c])?;
+    if len == 0 {
+        return Ok(columns[0].clone());
+    }
+
+    let arrays = columns
+        .iter()
+        .map(|ca| ca.downcast_iter().next().unwrap())
+        .collect::<Vec<_>>();
+    let kernel = ChainedSetOperation {
+        offsets: [0, 1, 2].map(|i| arrays[i].offsets().as_slice()),
+        n_rows: len,
+        op1,
+        op2,
+    };
+    let inner_values = arrays
+        .iter()
+        .map(|arr| arr.values().as_ref())
+        .collect::<Vec<_>>();
+    let builder = with_many_set_values(&inner_values, kernel)?;
+
+    let values = concatenate(&inner_values)?;
+    let validity = combine_many_validities(&arrays, len);
+    // SAFETY: `values` are the concatenated values of all columns.
+    let arr = unsafe { builder.finish(values.as_ref(), validity) };
+    // SAFETY: the output values are gathered from the columns, so the dtype is unchanged.
+    Ok(unsafe { columns[0].with_chunks(vec![arr.boxed()]) })
+}
@@ -0,0 +1,66 @@
+use arrow::array::{ListArray, Utf8ViewArray};
+use icu_collator::Collator;
+use polars_core::prelude::*;
+
+use super::pairwise::{span, ListIdxBuilder};
+use super::{downcast_values, encode_output, list_set_operation, SetOpOptions, SetOperation};
+
+/// Sort the elements of every row of `arr`, of which the values are strings, with `collator`.
+fn collate_rows(
+    arr: &ListArray<i64>,
+    collator: &Collator,
+    nulls_last: bool,
+) -> PolarsResult<ListArray<i64>> {
+    let values = downcast_values::<Utf8ViewArray>(arr.values().as_ref())?;
+    let offsets = arr.offsets().as_slice();
+    let n_rows = offsets.len() - 1;
+    let mut out = ListIdxBuilder::new(n_rows);
+    let mut row = vec![];
+    for i in 0..n_rows {
+        row.clear();
+        row.extend(span(offsets, i));
+        row.sort_by(|&l, &r| match (values.get(l), values.get(r)) {
+            (Some(l), Some(r)) => collator.compare(l, r),
+            (l, r) if nulls_last => l.is_none().cmp(&r.is_none()),
+            (l, r) => l.is_some().cmp(&r.is_some()),
+        });
+        row.iter().for_each(|&i| out.push(i));
+        out.finish_row();
+    }
+    // SAFETY: we gather from the values of `arr` itself.
+    Ok(unsafe { out.finish(values, arr.validity().cloned()) })
+}
+
+/// Apply `set_op` between the lists of `a` and `b` like
+/// [`list_set_operation`](super::list_set_operation) and sort the elements of every output
+/// list with the locale-aware `collator` rather than by their bytes, e.g. so that `"éclair"`
+/// sorts next to `"eclat"` rather than after `"zebra"`.
+///
+/// Nulls are sorted first unless [`SetOpOptions::nulls_last`] is set. Only supported for the
+/// `String` inner dtype.
+pub fn list_set_operation_collated(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    collator: &Collator,
+) -> PolarsResult<ListChunked> {
+    polars_ensure!(
+        a.inner_dtype() == &DataType::String,
+        InvalidOperation: "collated list 'set' operations are not supported for inner dtype {}", a.inner_dtype()
+    );
+    // The output is encoded after it is sorted, as the collator compares strings.
+    let plain = SetOpOptions {
+        dictionary_encode: false,
+        categorical_threshold: None,
+        ..options
+    };
+    let out = list_set_operation(a, b, set_op, plain)?;
+    let chunks = out
+        .downcast_iter()
+        .map(|arr| Ok(collate_rows(arr, collator, options.nulls_last)?.boxed()))
+        .collect::<PolarsResult<_>>()?;
+    // SAFETY: the rows are only reordered, so the dtype is unchanged.
+    let out = unsafe { out.with_chunks(chunks) };
+    encode_output(out, &options)
+}
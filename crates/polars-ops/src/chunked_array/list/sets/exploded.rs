@@ -0,0 +1,40 @@
+use arrow::array::Array;
+use polars_core::prelude::*;
+
+use super::pairwise::span;
+use super::{list_set_operation, SetOpOptions, SetOperation};
+
+/// Apply `set_op` between the lists of `a` and `b` like
+/// [`list_set_operation`](super::list_set_operation) and return the result exploded, with a
+/// row per element of the output lists, e.g. for a SQL-style join on the elements.
+///
+/// Returns a [`DataFrame`] of the columns `list_index`, the `IdxSize` index of the row of the
+/// element, and `value`, the element itself with the inner dtype of `a`. Rows are in order of
+/// their list index. Empty and null output lists have no rows, unlike in an `explode`, which
+/// gives a null row for them.
+pub fn list_set_operation_exploded(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<DataFrame> {
+    let out = list_set_operation(a, b, set_op, options)?.rechunk();
+    let mut list_index: Vec<IdxSize> = vec![];
+    let mut value_idx: Vec<IdxSize> = vec![];
+    if let Some(arr) = out.downcast_iter().next() {
+        let offsets = arr.offsets().as_slice();
+        for row in (0..arr.len()).filter(|&row| arr.is_valid(row)) {
+            let span = span(offsets, row);
+            list_index.resize(list_index.len() + span.len(), row as IdxSize);
+            value_idx.extend(span.map(|i| i as IdxSize));
+        }
+    }
+    let value = out
+        .get_inner()
+        .take(&IdxCa::from_vec("", value_idx))?
+        .with_name("value");
+    DataFrame::new(vec![
+        IdxCa::from_vec("list_index", list_index).into_series(),
+        value,
+    ])
+}
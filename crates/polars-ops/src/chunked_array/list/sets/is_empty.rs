@@ -0,0 +1,88 @@
+use arrow::array::BooleanArray;
+use arrow::bitmap::MutableBitmap;
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+
+use super::pairwise::{row_spans, with_set_values, PairKernel, SetValues};
+use super::{combine_validities, ensure_list_output, prepare_operands, SetOperation};
+
+struct IsEmpty<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    set_op: SetOperation,
+}
+
+impl PairKernel for IsEmpty<'_> {
+    type Output = MutableBitmap;
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = MutableBitmap::with_capacity(n_rows);
+        let mut set_a = PlHashSet::new();
+        let mut set_b = PlHashSet::new();
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            let mut keys_a = span_a.map(|i| unsafe { a.key_unchecked(i) });
+            let keys_b = span_b.map(|i| unsafe { b.key_unchecked(i) });
+
+            let is_empty = match self.set_op {
+                SetOperation::Union => keys_a.len() == 0 && keys_b.len() == 0,
+                SetOperation::Intersection => {
+                    set_b.clear();
+                    set_b.extend(keys_b);
+                    !keys_a.any(|k| set_b.contains(&k))
+                },
+                SetOperation::Difference => {
+                    set_b.clear();
+                    set_b.extend(keys_b);
+                    keys_a.all(|k| set_b.contains(&k))
+                },
+                SetOperation::SymmetricDifference => {
+                    set_a.clear();
+                    set_b.clear();
+                    set_a.extend(keys_a);
+                    set_b.extend(keys_b);
+                    set_a.len() == set_b.len() && set_a.iter().all(|k| set_b.contains(k))
+                },
+                op => unreachable!("boolean operation {op} has no list output"),
+            };
+            out.push(is_empty);
+        }
+        Ok(out)
+    }
+}
+
+/// Determine per row whether the output of `set_op` on `a` and `b` would be an empty list,
+/// without materializing the output.
+///
+/// Null rows in either operand result in null.
+pub fn list_set_operation_is_empty(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+) -> PolarsResult<BooleanChunked> {
+    ensure_list_output(set_op)?;
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let chunks = a
+        .downcast_iter()
+        .zip(b.downcast_iter())
+        .map(|(arr_a, arr_b)| {
+            let kernel = IsEmpty {
+                offsets_a: arr_a.offsets().as_slice(),
+                offsets_b: arr_b.offsets().as_slice(),
+                set_op,
+            };
+            let values = with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+            let validity = combine_validities(arr_a, arr_b);
+            Ok(BooleanArray::new(
+                ArrowDataType::Boolean,
+                values.into(),
+                validity,
+            ))
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    Ok(BooleanChunked::from_chunk_iter(a.name(), chunks))
+}
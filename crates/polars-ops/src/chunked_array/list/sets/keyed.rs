@@ -0,0 +1,247 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "dtype-struct")]
+use arrow::array::StructArray;
+use arrow::array::{Array, BinaryViewArray, ListArray, Utf8ViewArray};
+use arrow::compute::concatenate::concatenate;
+#[cfg(feature = "dtype-struct")]
+use arrow::compute::utils::combine_validities_and;
+use polars_core::prelude::*;
+
+use super::boolean::{boolean_set_operation_impl, NullLogic};
+#[cfg(feature = "dtype-struct")]
+use super::pairwise::with_set_values;
+use super::pairwise::{IdxSetOperation, PairKernel, SetValues};
+use super::{
+    combine_validities, downcast_values, ensure_list_output, prepare_operands, SetOperation,
+};
+
+/// Binary values that are compared by a derived key instead of by their bytes.
+pub(super) struct KeyedBinary<'a> {
+    keys: Vec<Option<Cow<'a, [u8]>>>,
+}
+
+impl SetValues for KeyedBinary<'_> {
+    type Key<'a>
+        = Option<&'a [u8]>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.keys.get_unchecked(idx).as_deref()
+    }
+}
+
+pub(super) fn keyed_binary<'a, F>(values: &'a BinaryViewArray, key: &F) -> KeyedBinary<'a>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+{
+    let keys = values.iter().map(|v| v.map(key)).collect();
+    KeyedBinary { keys }
+}
+
+/// Run `kernel` on the string or binary values of `a` and `b`, keyed by `key`.
+fn with_keyed_values<F, K>(
+    values_a: &dyn Array,
+    values_b: &dyn Array,
+    key: &F,
+    kernel: K,
+) -> PolarsResult<K::Output>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+    K: PairKernel,
+{
+    match (values_a.data_type(), values_b.data_type()) {
+        (ArrowDataType::Utf8View, ArrowDataType::Utf8View) => {
+            let a = downcast_values::<Utf8ViewArray>(values_a)?.to_binview();
+            let b = downcast_values::<Utf8ViewArray>(values_b)?.to_binview();
+            kernel.call(&keyed_binary(&a, key), &keyed_binary(&b, key))
+        },
+        (ArrowDataType::BinaryView, ArrowDataType::BinaryView) => {
+            let a = downcast_values::<BinaryViewArray>(values_a)?;
+            let b = downcast_values::<BinaryViewArray>(values_b)?;
+            kernel.call(&keyed_binary(a, key), &keyed_binary(b, key))
+        },
+        (dt_a, dt_b) => polars_bail!(
+            InvalidOperation: "keyed list 'set' operations require string or binary elements, got {:?} and {:?}",
+            dt_a, dt_b
+        ),
+    }
+}
+
+fn keyed_array_set_operation<F>(
+    a: &ListArray<i64>,
+    b: &ListArray<i64>,
+    set_op: SetOperation,
+    key: &F,
+) -> PolarsResult<ListArray<i64>>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+{
+    let values_a = a.values().as_ref();
+    let values_b = b.values().as_ref();
+    let kernel = IdxSetOperation {
+        offsets_a: a.offsets().as_slice(),
+        offsets_b: b.offsets().as_slice(),
+        set_op,
+        preserve_left_duplicates: false,
+    };
+    let builder = with_keyed_values(values_a, values_b, key, kernel)?;
+
+    let values = concatenate(&[values_a, values_b])?;
+    let validity = combine_validities(a, b);
+    // SAFETY: `values` are the values of `a` followed by the values of `b`.
+    Ok(unsafe { builder.finish(values.as_ref(), validity) })
+}
+
+/// Apply `set_op` on list columns with `String` or `Binary` elements, where two elements are
+/// considered equal if `key` derives the same bytes from them.
+///
+/// This allows arbitrary canonicalization (unicode normalization, case folding, ...) of the
+/// elements. The output contains the first seen original of every distinct key.
+///
+/// # Example
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use polars_core::prelude::*;
+/// # use polars_ops::prelude::*;
+/// use unicode_normalization::UnicodeNormalization;
+///
+/// // "é" as a single code point and as an "e" followed by a combining accent.
+/// let a = Series::new("a", [Series::new("", ["caf\u{e9}", "bar"])]);
+/// let b = Series::new("b", [Series::new("", ["cafe\u{301}"])]);
+///
+/// fn nfc(v: &[u8]) -> Cow<[u8]> {
+///     let s = std::str::from_utf8(v).unwrap();
+///     Cow::Owned(s.nfc().collect::<String>().into_bytes())
+/// }
+/// let out = list_set_operation_with_key(a.list()?, b.list()?, SetOperation::Intersection, nfc)?;
+/// let row = out.get_as_series(0).unwrap();
+/// assert_eq!(row.str()?.get(0), Some("caf\u{e9}"));
+/// assert_eq!(row.len(), 1);
+/// # Ok::<(), PolarsError>(())
+/// ```
+pub fn list_set_operation_with_key<F>(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    key: F,
+) -> PolarsResult<ListChunked>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+{
+    ensure_list_output(set_op)?;
+    let (a, b) = prepare_operands(a, b)?;
+    // we use the unsafe variant because we want to keep the nested logical types type.
+    unsafe {
+        arity::try_binary_unchecked_same_type(
+            &a,
+            &b,
+            |a, b| keyed_array_set_operation(a, b, set_op, &key).map(|arr| arr.boxed()),
+            false,
+            false,
+        )
+    }
+}
+
+/// The boolean counterpart of [`list_set_operation_with_key`], see
+/// [`boolean_list_set_operation`](super::boolean_list_set_operation). E.g. with a
+/// case-folding `key`, `["FOO"]` is a subset of `["foo", "bar"]`.
+pub fn boolean_list_set_operation_with_key<F>(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    tri_state: bool,
+    null_logic: NullLogic,
+    key: F,
+) -> PolarsResult<BooleanChunked>
+where
+    F: Fn(&[u8]) -> Cow<'_, [u8]>,
+{
+    boolean_set_operation_impl(
+        a,
+        b,
+        set_op,
+        tri_state,
+        null_logic,
+        |values_a, values_b, kernel| with_keyed_values(values_a, values_b, &key, kernel),
+    )
+}
+
+/// Project the field `name` of the struct elements in `values` as the keys to compare them by.
+/// Null structs get a null key.
+#[cfg(feature = "dtype-struct")]
+fn struct_field_keys(values: &dyn Array, name: &str) -> PolarsResult<Box<dyn Array>> {
+    let values = downcast_values::<StructArray>(values)?;
+    let idx = values
+        .fields()
+        .iter()
+        .position(|field| field.name == name)
+        .ok_or_else(|| polars_err!(StructFieldNotFound: "{}", name))?;
+    let keys = &values.values()[idx];
+    Ok(keys.with_validity(combine_validities_and(keys.validity(), values.validity())))
+}
+
+#[cfg(feature = "dtype-struct")]
+fn struct_field_array_set_operation(
+    a: &ListArray<i64>,
+    b: &ListArray<i64>,
+    set_op: SetOperation,
+    field: &str,
+) -> PolarsResult<ListArray<i64>> {
+    let values_a = a.values().as_ref();
+    let values_b = b.values().as_ref();
+    let kernel = IdxSetOperation {
+        offsets_a: a.offsets().as_slice(),
+        offsets_b: b.offsets().as_slice(),
+        set_op,
+        preserve_left_duplicates: false,
+    };
+    let keys_a = struct_field_keys(values_a, field)?;
+    let keys_b = struct_field_keys(values_b, field)?;
+    let builder = with_set_values(keys_a.as_ref(), keys_b.as_ref(), kernel)?;
+
+    let values = concatenate(&[values_a, values_b])?;
+    let validity = combine_validities(a, b);
+    // SAFETY: `values` are the values of `a` followed by the values of `b`.
+    Ok(unsafe { builder.finish(values.as_ref(), validity) })
+}
+
+/// Apply `set_op` on list columns with struct elements, where two structs are considered equal
+/// if their `field` is, e.g. to deduplicate objects by their id.
+///
+/// The output contains the whole struct of the first seen element of every distinct key. Null
+/// structs are keyed as null, so they are equal to structs whose `field` is null.
+#[cfg(feature = "dtype-struct")]
+pub fn list_set_operation_by_struct_field(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    field: &str,
+) -> PolarsResult<ListChunked> {
+    ensure_list_output(set_op)?;
+    for ca in [a, b] {
+        polars_ensure!(
+            matches!(ca.inner_dtype(), DataType::Struct(_)),
+            InvalidOperation: "list 'set' operations by a struct field require struct elements, got {}",
+            ca.inner_dtype()
+        );
+    }
+    let (a, b) = prepare_operands(a, b)?;
+    // we use the unsafe variant because we want to keep the nested logical types type.
+    unsafe {
+        arity::try_binary_unchecked_same_type(
+            &a,
+            &b,
+            |a, b| struct_field_array_set_operation(a, b, set_op, field).map(|arr| arr.boxed()),
+            false,
+            false,
+        )
+    }
+}
@@ -0,0 +1,104 @@
+use arrow::array::{Array, BooleanArray, ListArray};
+use arrow::bitmap::MutableBitmap;
+use arrow::offset::OffsetsBuffer;
+use polars_core::prelude::*;
+
+use super::{apply_limit, list_set_operation, split_limit, SetOpOptions, SetOperation};
+
+/// Select the rows of `ca` where `mask` is set, unless `ca` is broadcast.
+fn filter_operand(ca: &ListChunked, mask: &BooleanChunked) -> PolarsResult<ListChunked> {
+    if ca.len() == 1 && mask.len() != 1 {
+        Ok(ca.clone())
+    } else {
+        ca.filter(mask)
+    }
+}
+
+/// [`list_set_operation`] on only the rows where `mask` is `true`. The other rows, including
+/// those where `mask` is null, are null in the output and aren't computed at all.
+///
+/// The mask must have the length of the output, so that of the longest operand.
+pub fn list_set_operation_masked(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    mask: &BooleanChunked,
+) -> PolarsResult<ListChunked> {
+    let len = std::cmp::max(a.len(), b.len());
+    polars_ensure!(
+        mask.len() == len,
+        ShapeMismatch: "list set op mask length mismatch: mask={} expected={}",
+        mask.len(), len
+    );
+    let (options, limit) = split_limit(options);
+    if mask.sum().unwrap_or(0) == 0 {
+        let out = ListChunked::full_null_with_dtype(a.name(), len, a.inner_dtype());
+        return Ok(apply_limit(out, limit));
+    }
+
+    let selected = list_set_operation(
+        &filter_operand(a, mask)?,
+        &filter_operand(b, mask)?,
+        set_op,
+        options,
+    )?
+    .rechunk();
+    let arr = selected.downcast_iter().next().unwrap();
+
+    // Spread the computed rows over the selected positions, the others are empty and null.
+    let selected_offsets = arr.offsets().as_slice();
+    let mut offsets = Vec::with_capacity(len + 1);
+    let mut validity = MutableBitmap::with_capacity(len);
+    offsets.push(selected_offsets[0]);
+    let mut row = 0;
+    for keep in mask.into_iter() {
+        if keep == Some(true) {
+            offsets.push(selected_offsets[row + 1]);
+            validity.push(arr.is_valid(row));
+            row += 1;
+        } else {
+            offsets.push(*offsets.last().unwrap());
+            validity.push(false);
+        }
+    }
+    // SAFETY: the offsets are those of `arr` with repeated values for the skipped rows.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let out = ListArray::new(
+        arr.data_type().clone(),
+        offsets,
+        arr.values().clone(),
+        validity.into(),
+    );
+    // SAFETY: only the rows change, so the dtype of `selected` is unchanged.
+    let out = unsafe { selected.with_chunks(vec![out.boxed()]) };
+    Ok(apply_limit(out, limit))
+}
+
+/// [`list_set_operation`] on only the rows at `indices`, e.g. the rows that changed since an
+/// earlier call. The other rows are null in the output and aren't computed at all, use
+/// [`ChunkZip::zip_with`] on the indices as a mask to keep the earlier results there.
+///
+/// Null and repeated indices are ignored, an index out of bounds of the output is an error.
+pub fn list_set_operation_at(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    indices: &IdxCa,
+) -> PolarsResult<ListChunked> {
+    let len = std::cmp::max(a.len(), b.len());
+    let mut mask = MutableBitmap::from_len_zeroed(len);
+    for idx in indices.into_iter().flatten() {
+        polars_ensure!(
+            (idx as usize) < len,
+            OutOfBounds: "list set op index {} is out of bounds for length {}", idx, len
+        );
+        mask.set(idx as usize, true);
+    }
+    let mask = BooleanChunked::from_chunk_iter(
+        a.name(),
+        [BooleanArray::from_data_default(mask.into(), None)],
+    );
+    list_set_operation_masked(a, b, set_op, options, &mask)
+}
@@ -0,0 +1,102 @@
+use arrow::array::{BooleanArray, ListArray};
+use arrow::bitmap::MutableBitmap;
+use arrow::offset::OffsetsBuffer;
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ManyKernel, SetValues};
+use super::prepare_operands;
+
+struct Membership<'a> {
+    offsets: &'a [i64],
+}
+
+impl ManyKernel for Membership<'_> {
+    /// The membership bits of every row, a bit per element of the vocabulary.
+    type Output = MutableBitmap;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let (values, vocab) = (values[0], values[1]);
+        // Element of the vocabulary -> its position.
+        let mut index = PlHashMap::with_capacity(vocab.len());
+        for pos in 0..vocab.len() {
+            // SAFETY: `pos` is in bounds of the vocabulary.
+            let key = unsafe { vocab.key_unchecked(pos) };
+            polars_ensure!(
+                index.insert(key, pos).is_none(),
+                ComputeError: "the vocabulary of a membership vector must not hold duplicate elements"
+            );
+        }
+
+        let n_rows = self.offsets.len() - 1;
+        let mut out = MutableBitmap::from_len_zeroed(n_rows * vocab.len());
+        for row in 0..n_rows {
+            for i in span(self.offsets, row) {
+                // SAFETY: the offsets of a list array are in bounds of its values.
+                let key = unsafe { values.key_unchecked(i) };
+                if let Some(&pos) = index.get(&key) {
+                    out.set(row * vocab.len() + pos, true);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Encode the set of every row of `a` as a `List(Boolean)` of fixed length, with a flag per
+/// element of `vocab` whether the row holds it, e.g. to turn sets of tags into features.
+///
+/// `vocab` is cast to the inner dtype of `a` and indexed once. Its elements must be distinct.
+/// Elements of `a` that are not in `vocab` are ignored, and a null in `vocab` flags the rows
+/// with a null element. Null rows stay null.
+pub fn list_to_membership_vector(a: &ListChunked, vocab: &Series) -> PolarsResult<ListChunked> {
+    if a.is_empty() {
+        return Ok(ListChunked::full_null_with_dtype(
+            a.name(),
+            0,
+            &DataType::Boolean,
+        ));
+    }
+    let vocab = vocab.cast(a.inner_dtype())?.rechunk();
+    let width = vocab.len();
+    // The vocabulary as a single row, so that it is brought to a common representation with
+    // `a` like the operands of a set operation, e.g. categoricals to a shared rev-map.
+    let values = vocab.chunks()[0].clone();
+    // SAFETY: the offsets span all values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(vec![0, width as i64].into()) };
+    let arr = ListArray::<i64>::new(
+        ListArray::<i64>::default_datatype(values.data_type().clone()),
+        offsets,
+        values,
+        None,
+    );
+    let mut vocab_row = ListChunked::with_chunk(vocab.name(), arr);
+    vocab_row.set_inner_dtype(vocab.dtype().clone());
+    let (a, vocab_row) = prepare_operands(a, &vocab_row)?;
+    let vocab_values = vocab_row.downcast_iter().next().unwrap().values().as_ref();
+
+    let chunks = a
+        .downcast_iter()
+        .map(|arr| {
+            let kernel = Membership {
+                offsets: arr.offsets().as_slice(),
+            };
+            let bits = with_many_set_values(&[arr.values().as_ref(), vocab_values], kernel)?;
+            let values = BooleanArray::from_data_default(bits.into(), None);
+            let offsets = (0..=arr.len())
+                .map(|row| (row * width) as i64)
+                .collect::<Vec<_>>();
+            // SAFETY: the offsets start at 0 and grow monotonically to the length of `values`.
+            let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+            let arr = ListArray::<i64>::new(
+                ListArray::<i64>::default_datatype(ArrowDataType::Boolean),
+                offsets,
+                values.boxed(),
+                arr.validity().cloned(),
+            );
+            Ok(arr.boxed())
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    let dtype = DataType::List(Box::new(DataType::Boolean));
+    // SAFETY: the chunks are lists of booleans.
+    Ok(unsafe { ListChunked::from_chunks_and_dtype(a.name(), chunks, dtype) })
+}
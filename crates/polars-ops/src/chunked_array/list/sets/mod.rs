@@ -0,0 +1,2090 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use arrow::array::{
+    Array, BinaryArray, BinaryViewArray, DictionaryArray, ListArray, MutableArray,
+    MutableBinaryViewArray, MutablePlBinary, MutablePrimitiveArray, PrimitiveArray, Utf8ViewArray,
+};
+use arrow::bitmap::Bitmap;
+use arrow::compute::cast::{cast, CastOptions};
+use arrow::compute::concatenate::concatenate;
+use arrow::compute::take::take_unchecked;
+use arrow::compute::utils::combine_validities_and;
+use arrow::match_integer_type;
+use arrow::offset::OffsetsBuffer;
+use arrow::types::NativeType;
+use indexmap::IndexSet;
+use num_traits::ToPrimitive;
+use polars_core::prelude::*;
+use polars_core::with_match_physical_numeric_type;
+use polars_utils::total_ord::{ToTotalOrd, TotalEq, TotalHash, TotalOrd, TotalOrdWrap};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use self::bitset::small_domain_set_operation;
+use self::casing::{lowercase_values, most_frequent_casing};
+use self::keyed::keyed_binary;
+use self::pairwise::{row_spans, with_set_values, IdxSetOperation, PairKernel};
+use self::pool::{PooledScratch, Scratch};
+use self::sequence::sequence_set_operation;
+use self::spill::{extend_or_spill, sort_merge_row, SPILL_THRESHOLD};
+use crate::frame::join::{DataFrameJoinOps, JoinArgs, JoinType};
+
+mod agg;
+mod at_least_k;
+mod bitset;
+mod boolean;
+mod casing;
+mod chain;
+#[cfg(feature = "list_sets_collation")]
+mod collation;
+mod exploded;
+mod is_empty;
+mod keyed;
+mod masked;
+mod membership;
+mod online;
+mod overlap;
+mod pairwise;
+mod pool;
+#[cfg(feature = "dtype-u8")]
+mod provenance;
+mod reduce;
+#[cfg(feature = "list_sets_roaring")]
+mod roaring;
+mod rolling;
+mod runs;
+mod sentinel;
+mod sequence;
+mod sorted;
+mod spill;
+mod stats;
+#[cfg(test)]
+mod test;
+#[cfg(feature = "dtype-struct")]
+mod value_counts;
+mod venn;
+
+pub use agg::*;
+pub use at_least_k::*;
+pub use boolean::*;
+pub use chain::*;
+#[cfg(feature = "list_sets_collation")]
+pub use collation::*;
+pub use exploded::*;
+pub use is_empty::*;
+pub use keyed::*;
+pub use masked::*;
+pub use membership::*;
+pub use online::*;
+pub use overlap::*;
+#[cfg(feature = "dtype-u8")]
+pub use provenance::*;
+pub use reduce::*;
+#[cfg(feature = "list_sets_roaring")]
+pub use roaring::*;
+pub use rolling::*;
+pub use runs::*;
+pub use sentinel::*;
+pub use sorted::*;
+pub use stats::*;
+#[cfg(feature = "dtype-struct")]
+pub use value_counts::*;
+pub use venn::*;
+
+trait MaterializeValues<K> {
+    // extends the iterator to the values and returns the current offset
+    fn extend_buf<I: Iterator<Item = K>>(&mut self, values: I) -> usize;
+}
+
+impl<T> MaterializeValues<Option<T>> for MutablePrimitiveArray<T>
+where
+    T: NativeType,
+{
+    fn extend_buf<I: Iterator<Item = Option<T>>>(&mut self, values: I) -> usize {
+        self.extend(values);
+        self.len()
+    }
+}
+
+impl<T> MaterializeValues<TotalOrdWrap<Option<T>>> for MutablePrimitiveArray<T>
+where
+    T: NativeType,
+{
+    fn extend_buf<I: Iterator<Item = TotalOrdWrap<Option<T>>>>(&mut self, values: I) -> usize {
+        self.extend(values.map(|x| x.0));
+        self.len()
+    }
+}
+
+impl<'a> MaterializeValues<Option<&'a [u8]>> for MutablePlBinary {
+    fn extend_buf<I: Iterator<Item = Option<&'a [u8]>>>(&mut self, values: I) -> usize {
+        self.extend(values);
+        self.len()
+    }
+}
+
+/// Apply `set_op` between `a` and `b` and push the output to `out`.
+///
+/// If `lhs_filled`, `set` already holds the elements of `a` and `a` is ignored, e.g. a broadcast
+/// `a` for the symmetric difference, as it is the only operation that doesn't consume `set`.
+/// If `rhs_filled`, `set2` already holds the elements of `b` in the same way.
+#[allow(clippy::too_many_arguments)]
+fn set_operation<K, I, J, R, S>(
+    set: &mut IndexSet<K, S>,
+    set2: &mut IndexSet<K, S>,
+    a: I,
+    b: J,
+    out: &mut R,
+    set_op: SetOperation,
+    rhs_filled: bool,
+    lhs_filled: bool,
+    preserve_left_duplicates: bool,
+) -> usize
+where
+    K: Eq + Hash + Copy,
+    I: IntoIterator<Item = K>,
+    J: IntoIterator<Item = K>,
+    R: MaterializeValues<K>,
+    S: BuildHasher,
+{
+    let preserve_left_duplicates = preserve_left_duplicates
+        && matches!(
+            set_op,
+            SetOperation::Intersection | SetOperation::Difference
+        );
+    let mut a = a.into_iter();
+    let b = b.into_iter();
+    if !lhs_filled && !preserve_left_duplicates {
+        set.clear();
+        set.extend(a.by_ref());
+    }
+
+    match set_op {
+        SetOperation::Intersection | SetOperation::Difference if preserve_left_duplicates => {
+            if !rhs_filled {
+                set2.clear();
+                set2.extend(b);
+            }
+            let keep_contained = set_op == SetOperation::Intersection;
+            out.extend_buf(a.filter(|v| set2.contains(v) == keep_contained))
+        },
+        SetOperation::Intersection => {
+            if !rhs_filled {
+                set2.clear();
+                set2.extend(b);
+            }
+            out.extend_buf(set.intersection(set2).copied())
+        },
+        SetOperation::Union => {
+            // If filled `set2` holds the deduplicated elements of `b`, which can be much fewer
+            // than the raw elements that would otherwise be hashed for every row.
+            if rhs_filled {
+                set.extend(set2.iter().copied());
+            } else {
+                set.extend(b);
+            }
+            out.extend_buf(set.drain(..))
+        },
+        SetOperation::Difference => {
+            if !rhs_filled {
+                set2.clear();
+                set2.extend(b);
+            }
+            // Retain rather than remove the elements of `b`, so the order of `a` is preserved.
+            set.retain(|v| !set2.contains(v));
+            out.extend_buf(set.drain(..))
+        },
+        SetOperation::SymmetricDifference => {
+            if !rhs_filled {
+                set2.clear();
+                set2.extend(b);
+            }
+            // The elements of `set` come before those of `set2`, both in insertion order.
+            out.extend_buf(set.symmetric_difference(set2).copied())
+        },
+        op => {
+            unreachable!("boolean operation {op} should be handled by `boolean_list_set_operation`")
+        },
+    }
+}
+
+fn copied_wrapper_opt<T: Copy + ToTotalOrd>(
+    v: Option<&T>,
+) -> <Option<T> as ToTotalOrd>::TotalOrdItem {
+    v.copied().to_total_ord()
+}
+
+/// An operation on the sets of a pair of lists, exported through `polars_ops::prelude` for
+/// crates that build on these operations.
+///
+/// # Example
+///
+/// ```
+/// use polars_ops::prelude::SetOperation;
+///
+/// let describe = |op: SetOperation| match op.is_boolean() {
+///     true => format!("{op} gives a boolean"),
+///     false => format!("{op} gives a list"),
+/// };
+/// assert_eq!(describe(SetOperation::Union), "union gives a list");
+/// assert_eq!(describe(SetOperation::IsSubset), "is_subset gives a boolean");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SetOperation {
+    Intersection,
+    Union,
+    Difference,
+    SymmetricDifference,
+    IsDisjoint,
+    IsSubset,
+    IsSuperset,
+    IsEqual,
+}
+
+impl SetOperation {
+    /// Whether the operation answers a yes/no question per row, see
+    /// [`boolean_list_set_operation`], rather than producing a list.
+    pub fn is_boolean(&self) -> bool {
+        matches!(
+            self,
+            SetOperation::IsDisjoint
+                | SetOperation::IsSubset
+                | SetOperation::IsSuperset
+                | SetOperation::IsEqual
+        )
+    }
+
+    /// Whether the operation on two empty lists gives a valid result under all options. The
+    /// boolean operations only hold vacuously on empty lists, which is null if `tri_state` is
+    /// set in [`boolean_list_set_operation`].
+    pub fn empty_result_is_valid(&self) -> bool {
+        !self.is_boolean()
+    }
+
+    /// Whether swapping the operands gives the same result. Lists are compared as sets, the
+    /// order of the output elements of e.g. [`SetOperation::Union`] does depend on the order
+    /// of the operands.
+    pub fn commutative(&self) -> bool {
+        matches!(
+            self,
+            SetOperation::Intersection
+                | SetOperation::Union
+                | SetOperation::SymmetricDifference
+                | SetOperation::IsDisjoint
+                | SetOperation::IsEqual
+        )
+    }
+}
+
+/// Options that tweak how the elements of the lists are compared and stored in
+/// [`list_set_operation`]. The default matches plain set semantics, where elements keep their
+/// order of insertion.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetOpOptions {
+    /// Trim leading and trailing whitespace of `String`/`Binary` elements before they are
+    /// compared. The trimmed form is written to the output. Has no effect on other dtypes.
+    pub trim: bool,
+    /// Dictionary-encode the output values over all rows, so that an element that recurs in
+    /// many rows is stored once. The output of a `String` operation is returned as
+    /// `List(Categorical)`. Only supported for `String` and `Categorical` inner dtypes.
+    pub dictionary_encode: bool,
+    /// Sort the elements of every output list of a numeric operation, nulls first unless
+    /// `nulls_last` is set. Has no effect on other dtypes.
+    pub sort_numeric: bool,
+    /// Put the nulls of sorted output lists last rather than first.
+    pub nulls_last: bool,
+    /// Apply the operation right-to-left, e.g. compute `b \ a` for [`SetOperation::Difference`].
+    /// The output keeps the name of `a` and either operand may still be broadcast.
+    pub swap_operands: bool,
+    /// Keep every element of `a`, including duplicates, whose membership in `b` matches for
+    /// [`SetOperation::Intersection`] and [`SetOperation::Difference`], e.g. `[1, 1, 2] \ [2]`
+    /// is `[1, 1]` rather than `[1]`. Has no effect on other operations.
+    pub preserve_left_duplicates: bool,
+    /// Cast the elements of `b` to the time unit of `a` if both are `Datetime` lists of
+    /// different time units, which is an error otherwise.
+    pub cast_time_unit: bool,
+    /// How [`SetOperation::Union`] deduplicates the elements of numeric lists. Has no effect
+    /// on other operations and dtypes.
+    pub union_strategy: UnionStrategy,
+    /// Treat null rows as empty lists rather than propagating them, e.g. the union of a null
+    /// row and `[1, 2]` is `[1, 2]` rather than null.
+    pub null_row_as_empty: bool,
+    /// Compare float elements approximately: elements are hashed by the nearest multiple of
+    /// `approx_eps`, so elements in the same bin of width `approx_eps` are equal. This is not
+    /// transitive, elements closer than `approx_eps` on either side of a bin boundary stay
+    /// distinct. Elements keep the value of their first occurrence. Has no effect on other
+    /// dtypes.
+    pub approx_eps: Option<f64>,
+    /// Hash `String`/`Binary` elements with SipHash under random keys rather than the default
+    /// hasher. This keeps the sets of a row linear in its length for untrusted input crafted
+    /// to collide, at the cost of slower hashing of every element, e.g. a union of short
+    /// strings takes about a third longer. Has no effect on other dtypes.
+    pub dos_resistant_hashing: bool,
+    /// Treat the lists as ordered sequences rather than sets, diffed along their longest
+    /// common subsequence: [`SetOperation::Intersection`] is that subsequence,
+    /// [`SetOperation::Difference`] the elements deleted from `a`, [`SetOperation::Union`] the
+    /// shortest sequence that holds both and [`SetOperation::SymmetricDifference`] the
+    /// deleted and inserted elements, all in diff order and with duplicates. E.g. the
+    /// intersection of `[1, 2, 3, 4]` and `[2, 4, 3]` is `[2, 4]`. The diff takes time and
+    /// memory quadratic in the length of the rows, and a pair of rows whose table would hold
+    /// more than 2^27 cells, e.g. two rows of about 11,600 elements, raises a `ComputeError`.
+    /// Only supported for numeric, `String` and `Binary` inner dtypes, and the options that
+    /// concern the hashing of elements have no effect.
+    pub sequence_mode: bool,
+    /// Compute only the first `limit` rows and return a column of at most `limit` rows, e.g.
+    /// to sanity-check the output on a few rows of a large column. The operands are still
+    /// checked to be of compatible lengths.
+    pub limit: Option<usize>,
+    /// Compare numeric elements by their absolute value, so `-x` and `x` are the same
+    /// element. Elements keep the sign of their first occurrence. Has no effect on other
+    /// dtypes.
+    pub abs_normalize: bool,
+    /// Compare `String` elements without their accents: elements are decomposed (NFD) and
+    /// stripped of combining marks, so `"café"` and `"cafe"` are the same element. Elements
+    /// keep the original of their first occurrence. Has no effect on other dtypes.
+    pub strip_accents: bool,
+    /// Compare `String` elements case-insensitively, after lowercasing them, and emit the
+    /// casing that the given [`CasingResolution`] picks for every element. `None` compares
+    /// them case-sensitively. Has no effect on other dtypes.
+    pub casing_resolution: Option<CasingResolution>,
+    /// Return the output of a `String` operation as `List(Categorical)` if it holds fewer
+    /// than this many distinct elements over all rows, which saves memory downstream when
+    /// few distinct strings recur in many rows. `None` never converts it. Has no effect on
+    /// other dtypes, with [`SetOpOptions::dictionary_encode`], which always converts it, or
+    /// without the `dtype-categorical` feature.
+    pub categorical_threshold: Option<usize>,
+    /// Compute a row of integers with bitsets over the range of its values rather than with
+    /// hash sets, if that range spans at most this many bits per element of the row. Bitsets
+    /// are faster for dense rows, but a sparse row would clear and scan mostly unset bits. `0`
+    /// always uses hash sets. Has no effect on other dtypes.
+    pub bitset_bits_per_element: usize,
+}
+
+impl Default for SetOpOptions {
+    fn default() -> Self {
+        Self {
+            trim: false,
+            dictionary_encode: false,
+            sort_numeric: false,
+            nulls_last: false,
+            swap_operands: false,
+            preserve_left_duplicates: false,
+            cast_time_unit: false,
+            union_strategy: UnionStrategy::default(),
+            null_row_as_empty: false,
+            approx_eps: None,
+            dos_resistant_hashing: false,
+            sequence_mode: false,
+            limit: None,
+            abs_normalize: false,
+            strip_accents: false,
+            casing_resolution: None,
+            categorical_threshold: None,
+            bitset_bits_per_element: 32,
+        }
+    }
+}
+
+/// How the elements of a numeric [`SetOperation::Union`] are deduplicated, see
+/// [`SetOpOptions::union_strategy`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnionStrategy {
+    /// Insert the elements in a hash set. Elements keep their order of first appearance.
+    #[default]
+    Hash,
+    /// Copy the elements of both lists in a buffer, then sort and deduplicate it. This can be
+    /// faster for short lists and gives sorted output, nulls first unless
+    /// [`SetOpOptions::nulls_last`] is set.
+    SortDedup,
+}
+
+/// The casing of the `String` elements that are compared case-insensitively, see
+/// [`SetOpOptions::casing_resolution`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CasingResolution {
+    /// The casing of the first occurrence of the element.
+    First,
+    /// The casing of the element that occurs most often in the row, in either operand. Ties
+    /// go to the casing seen first.
+    MostFrequent,
+    /// The element in lowercase.
+    Lowercase,
+}
+
+impl Hash for SetOpOptions {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trim.hash(state);
+        self.dictionary_encode.hash(state);
+        self.sort_numeric.hash(state);
+        self.nulls_last.hash(state);
+        self.swap_operands.hash(state);
+        self.preserve_left_duplicates.hash(state);
+        self.cast_time_unit.hash(state);
+        self.union_strategy.hash(state);
+        self.null_row_as_empty.hash(state);
+        self.approx_eps.map(f64::to_bits).hash(state);
+        self.dos_resistant_hashing.hash(state);
+        self.sequence_mode.hash(state);
+        self.limit.hash(state);
+        self.abs_normalize.hash(state);
+        self.strip_accents.hash(state);
+        self.casing_resolution.hash(state);
+        self.categorical_threshold.hash(state);
+        self.bitset_bits_per_element.hash(state);
+    }
+}
+
+impl SetOpOptions {
+    /// The default options, but with numeric output lists sorted and their nulls last. The
+    /// default keeps the faster insertion order, see [`list_set_operation`].
+    pub fn sorted_nulls_last() -> Self {
+        Self::default()
+            .with_sort_numeric(true)
+            .with_nulls_last(true)
+    }
+
+    /// Whether to trim whitespace of `String`/`Binary` elements. Default `false`.
+    pub fn with_trim(mut self, enabled: bool) -> Self {
+        self.trim = enabled;
+        self
+    }
+
+    /// Whether to dictionary-encode the output values. Default `false`.
+    pub fn with_dictionary_encode(mut self, enabled: bool) -> Self {
+        self.dictionary_encode = enabled;
+        self
+    }
+
+    /// Whether to sort the elements of numeric output lists. Default `false`.
+    pub fn with_sort_numeric(mut self, enabled: bool) -> Self {
+        self.sort_numeric = enabled;
+        self
+    }
+
+    /// Whether to put the nulls of sorted output lists last. Default `false`.
+    pub fn with_nulls_last(mut self, enabled: bool) -> Self {
+        self.nulls_last = enabled;
+        self
+    }
+
+    /// Whether to apply the operation right-to-left. Default `false`.
+    pub fn with_swap_operands(mut self, enabled: bool) -> Self {
+        self.swap_operands = enabled;
+        self
+    }
+
+    /// Whether to keep the duplicates of `a` for intersections and differences. Default `false`.
+    pub fn with_preserve_left_duplicates(mut self, enabled: bool) -> Self {
+        self.preserve_left_duplicates = enabled;
+        self
+    }
+
+    /// Whether to cast `Datetime` elements of `b` to the time unit of `a`. Default `false`.
+    pub fn with_cast_time_unit(mut self, enabled: bool) -> Self {
+        self.cast_time_unit = enabled;
+        self
+    }
+
+    /// How to deduplicate the elements of numeric unions. Default [`UnionStrategy::Hash`].
+    pub fn with_union_strategy(mut self, strategy: UnionStrategy) -> Self {
+        self.union_strategy = strategy;
+        self
+    }
+
+    /// Whether to treat null rows as empty lists. Default `false`.
+    pub fn with_null_row_as_empty(mut self, enabled: bool) -> Self {
+        self.null_row_as_empty = enabled;
+        self
+    }
+
+    /// The width of the bins in which float elements compare equal. Default `None`, which
+    /// compares them exactly.
+    pub fn with_approx_eps(mut self, eps: Option<f64>) -> Self {
+        self.approx_eps = eps;
+        self
+    }
+
+    /// Whether to hash `String`/`Binary` elements with SipHash. Default `false`.
+    pub fn with_dos_resistant_hashing(mut self, enabled: bool) -> Self {
+        self.dos_resistant_hashing = enabled;
+        self
+    }
+
+    /// Whether to treat the lists as ordered sequences. Default `false`.
+    pub fn with_sequence_mode(mut self, enabled: bool) -> Self {
+        self.sequence_mode = enabled;
+        self
+    }
+
+    /// The number of rows to compute. Default `None`, which computes all rows.
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Whether to compare numeric elements by their absolute value. Default `false`.
+    pub fn with_abs_normalize(mut self, enabled: bool) -> Self {
+        self.abs_normalize = enabled;
+        self
+    }
+
+    /// Whether to compare `String` elements without their accents. Default `false`.
+    pub fn with_strip_accents(mut self, enabled: bool) -> Self {
+        self.strip_accents = enabled;
+        self
+    }
+
+    /// How to resolve the casing of `String` elements that are compared case-insensitively.
+    /// Default `None`, which compares them case-sensitively.
+    pub fn with_casing_resolution(mut self, resolution: Option<CasingResolution>) -> Self {
+        self.casing_resolution = resolution;
+        self
+    }
+
+    /// The number of distinct `String` elements below which to return `List(Categorical)`.
+    /// Default `None`, which never converts the output.
+    pub fn with_categorical_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.categorical_threshold = threshold;
+        self
+    }
+
+    /// The number of bits per element of a row of integers up to which to use bitsets.
+    /// Default `32`.
+    pub fn with_bitset_bits_per_element(mut self, bits: usize) -> Self {
+        self.bitset_bits_per_element = bits;
+        self
+    }
+}
+
+impl Display for SetOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SetOperation::Intersection => "intersection",
+            SetOperation::Union => "union",
+            SetOperation::Difference => "difference",
+            SetOperation::SymmetricDifference => "symmetric_difference",
+            SetOperation::IsDisjoint => "is_disjoint",
+            SetOperation::IsSubset => "is_subset",
+            SetOperation::IsSuperset => "is_superset",
+            SetOperation::IsEqual => "is_equal",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn primitive<T>(
+    a: &PrimitiveArray<T>,
+    b: &PrimitiveArray<T>,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    set_op: SetOperation,
+    validity: Option<Bitmap>,
+    sort: bool,
+    nulls_last: bool,
+    preserve_left_duplicates: bool,
+    bitset_bits_per_element: usize,
+    pooled: bool,
+) -> PolarsResult<ListArray<i64>>
+where
+    T: NativeType + TotalHash + TotalEq + TotalOrd + Copy + ToTotalOrd + ToPrimitive,
+    <Option<T> as ToTotalOrd>::TotalOrdItem: Hash + Eq + Copy + 'static,
+{
+    // Boolean operations are evaluated by `boolean_list_set_operation` without a value builder.
+    debug_assert!(!set_op.is_boolean());
+    // Corrupt offsets, e.g. of an array imported over FFI, would underflow the lengths of the
+    // rows below.
+    check_offsets(offsets_a, a.len())?;
+    check_offsets(offsets_b, b.len())?;
+    let broadcast_lhs = offsets_a.len() == 2;
+    let broadcast_rhs = offsets_b.len() == 2;
+
+    let mut scratch = PooledScratch::<<Option<T> as ToTotalOrd>::TotalOrdItem>::new(pooled);
+    let Scratch { set, set2, bitsets } = &mut *scratch;
+
+    let mut values_out = MutablePrimitiveArray::with_capacity(std::cmp::max(
+        *offsets_a.last().unwrap(),
+        *offsets_b.last().unwrap(),
+    ) as usize);
+    let mut offsets = new_offsets(offsets_a, offsets_b);
+
+    let offsets_slice = if offsets_a.len() > offsets_b.len() {
+        offsets_a
+    } else {
+        offsets_b
+    };
+    let first_a = offsets_a[0];
+    let second_a = offsets_a[1];
+    let first_b = offsets_b[0];
+    let second_b = offsets_b[1];
+    if broadcast_rhs {
+        set2.extend(
+            b.into_iter()
+                .skip(first_b as usize)
+                .take(second_b as usize - first_b as usize)
+                .map(copied_wrapper_opt),
+        );
+    } else if broadcast_lhs && set_op == SetOperation::SymmetricDifference {
+        set.extend(
+            a.into_iter()
+                .skip(first_a as usize)
+                .take(second_a as usize - first_a as usize)
+                .map(copied_wrapper_opt),
+        );
+    }
+    for i in 1..offsets_slice.len() {
+        // If we go OOB we take the first element as we are then broadcasting.
+        let start_a = *offsets_a.get(i - 1).unwrap_or(&first_a) as usize;
+        let end_a = *offsets_a.get(i).unwrap_or(&second_a) as usize;
+
+        let start_b = *offsets_b.get(i - 1).unwrap_or(&first_b) as usize;
+        let end_b = *offsets_b.get(i).unwrap_or(&second_b) as usize;
+
+        let span_a = if broadcast_lhs {
+            first_a as usize..second_a as usize
+        } else {
+            start_a..end_a
+        };
+        let span_b = if broadcast_rhs {
+            first_b as usize..second_b as usize
+        } else {
+            start_b..end_b
+        };
+        if !preserve_left_duplicates {
+            if let Some(offset) = small_domain_set_operation(
+                a,
+                span_a,
+                b,
+                span_b,
+                set_op,
+                &mut values_out,
+                bitsets,
+                bitset_bits_per_element,
+            ) {
+                offsets.push(offset as i64);
+                continue;
+            }
+        }
+
+        // The branches are the same every loop.
+        // We rely on branch prediction here.
+        let offset = if broadcast_rhs {
+            // going via skip iterator instead of slice doesn't heap alloc nor trigger a bitcount
+            let a_iter = a
+                .into_iter()
+                .skip(start_a)
+                .take(end_a - start_a)
+                .map(copied_wrapper_opt);
+            let b_iter = b
+                .into_iter()
+                .skip(first_b as usize)
+                .take(second_b as usize - first_b as usize)
+                .map(copied_wrapper_opt);
+            set_operation(
+                set,
+                set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                true,
+                false,
+                preserve_left_duplicates,
+            )
+        } else if broadcast_lhs {
+            let a_iter = a
+                .into_iter()
+                .skip(first_a as usize)
+                .take(second_a as usize - first_a as usize)
+                .map(copied_wrapper_opt);
+
+            let b_iter = b
+                .into_iter()
+                .skip(start_b)
+                .take(end_b - start_b)
+                .map(copied_wrapper_opt);
+
+            set_operation(
+                set,
+                set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                false,
+                set_op == SetOperation::SymmetricDifference,
+                preserve_left_duplicates,
+            )
+        } else {
+            // going via skip iterator instead of slice doesn't heap alloc nor trigger a bitcount
+            let a_iter = a
+                .into_iter()
+                .skip(start_a)
+                .take(end_a - start_a)
+                .map(copied_wrapper_opt);
+
+            let b_iter = b
+                .into_iter()
+                .skip(start_b)
+                .take(end_b - start_b)
+                .map(copied_wrapper_opt);
+            set_operation(
+                set,
+                set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                false,
+                false,
+                preserve_left_duplicates,
+            )
+        };
+
+        offsets.push(offset as i64);
+    }
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let dtype = ListArray::<i64>::default_datatype(values_out.data_type().clone());
+
+    let mut values: PrimitiveArray<T> = values_out.into();
+    if sort {
+        values = sort_sublists(&values, offsets.as_slice(), nulls_last);
+    }
+    Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+}
+
+/// The union of every row of `a` and `b` by [`UnionStrategy::SortDedup`].
+fn sort_dedup_union<T>(
+    a: &PrimitiveArray<T>,
+    b: &PrimitiveArray<T>,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    validity: Option<Bitmap>,
+    nulls_last: bool,
+) -> ListArray<i64>
+where
+    T: NativeType + TotalEq + TotalOrd,
+{
+    let mut values_out = MutablePrimitiveArray::<T>::with_capacity(std::cmp::max(
+        *offsets_a.last().unwrap(),
+        *offsets_b.last().unwrap(),
+    ) as usize);
+    let mut offsets = new_offsets(offsets_a, offsets_b);
+    let mut buf = Vec::new();
+
+    for (span_a, span_b) in row_spans(offsets_a, offsets_b) {
+        buf.clear();
+        let mut has_null = false;
+        for (arr, span) in [(a, span_a), (b, span_b)] {
+            for i in span {
+                // SAFETY: the offsets of a list array are in bounds of its values.
+                match unsafe { arr.get_unchecked(i) } {
+                    Some(v) => buf.push(v),
+                    None => has_null = true,
+                }
+            }
+        }
+        buf.sort_unstable_by(|l, r| l.tot_cmp(r));
+        buf.dedup_by(|l, r| l.tot_eq(r));
+        if has_null && !nulls_last {
+            values_out.push_null();
+        }
+        values_out.extend_from_slice(&buf);
+        if has_null && nulls_last {
+            values_out.push_null();
+        }
+        offsets.push(values_out.len() as i64);
+    }
+    // SAFETY: the offsets are monotonically increasing and in bounds of the values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let values: PrimitiveArray<T> = values_out.into();
+    let values = values.to(a.data_type().clone());
+    let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+    ListArray::new(dtype, offsets, values.boxed(), validity)
+}
+
+/// Sort the elements of every sublist in their total order, nulls first unless `nulls_last`.
+fn sort_sublists<T>(
+    values: &PrimitiveArray<T>,
+    offsets: &[i64],
+    nulls_last: bool,
+) -> PrimitiveArray<T>
+where
+    T: NativeType + TotalOrd,
+{
+    let spans = offsets.windows(2).map(|w| w[0] as usize..w[1] as usize);
+    if values.null_count() == 0 {
+        let mut buf = values.values().to_vec();
+        for span in spans {
+            buf[span].sort_unstable_by(|l, r| l.tot_cmp(r));
+        }
+        PrimitiveArray::new(values.data_type().clone(), buf.into(), None)
+    } else {
+        let mut buf = values.iter().map(|v| v.copied()).collect::<Vec<_>>();
+        for span in spans {
+            if nulls_last {
+                buf[span].sort_unstable_by(|l, r| match (l, r) {
+                    (Some(l), Some(r)) => l.tot_cmp(r),
+                    (l, r) => r.is_some().cmp(&l.is_some()),
+                });
+            } else {
+                buf[span].sort_unstable_by(|l, r| l.tot_cmp(r));
+            }
+        }
+        PrimitiveArray::from(buf).to(values.data_type().clone())
+    }
+}
+
+fn trim_utf8(v: Option<&[u8]>) -> Option<&[u8]> {
+    // SAFETY: only called on the bytes of utf8 values.
+    v.map(|v| {
+        unsafe { std::str::from_utf8_unchecked(v) }
+            .trim()
+            .as_bytes()
+    })
+}
+
+/// The bytes of utf8 values, with leading and trailing whitespace trimmed.
+fn trim_utf8_values(values: &BinaryViewArray) -> BinaryViewArray {
+    MutableBinaryViewArray::<[u8]>::from_iterator(values.iter().map(trim_utf8)).freeze()
+}
+
+fn trim_binary(v: Option<&[u8]>) -> Option<&[u8]> {
+    v.map(|v| v.trim_ascii())
+}
+
+/// The bytes by which a utf8 value is compared under the options that canonicalize strings,
+/// see [`SetOpOptions::strip_accents`] and [`SetOpOptions::casing_resolution`].
+fn string_key<'a>(v: &'a [u8], options: &SetOpOptions) -> Cow<'a, [u8]> {
+    let v = if options.trim {
+        trim_utf8(Some(v)).unwrap()
+    } else {
+        v
+    };
+    let v = if options.strip_accents {
+        strip_accents(v)
+    } else {
+        Cow::Borrowed(v)
+    };
+    if options.casing_resolution.is_none()
+        || (v.is_ascii() && !v.iter().any(u8::is_ascii_uppercase))
+    {
+        return v;
+    }
+    // SAFETY: only called on the bytes of utf8 values.
+    let s = unsafe { std::str::from_utf8_unchecked(&v) };
+    Cow::Owned(s.to_lowercase().into_bytes())
+}
+
+/// The bytes of a utf8 value, decomposed and without combining marks, see
+/// [`SetOpOptions::strip_accents`].
+fn strip_accents(v: &[u8]) -> Cow<'_, [u8]> {
+    if v.is_ascii() {
+        return Cow::Borrowed(v);
+    }
+    // SAFETY: only called on the bytes of utf8 values.
+    let s = unsafe { std::str::from_utf8_unchecked(v) };
+    let stripped = s
+        .nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .collect::<String>();
+    Cow::Owned(stripped.into_bytes())
+}
+
+/// [`binary`] with the hasher of `dos_resistant_hashing`.
+#[allow(clippy::too_many_arguments)]
+fn binary_hashed<'a, A, N>(
+    a: &'a A,
+    b: &'a A,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    set_op: SetOperation,
+    validity: Option<Bitmap>,
+    as_utf8: bool,
+    normalize: N,
+    preserve_left_duplicates: bool,
+    dos_resistant_hashing: bool,
+) -> PolarsResult<ListArray<i64>>
+where
+    A: Array,
+    &'a A: IntoIterator<Item = Option<&'a [u8]>>,
+    N: Fn(Option<&'a [u8]>) -> Option<&'a [u8]> + Copy,
+{
+    if dos_resistant_hashing {
+        binary(
+            a,
+            b,
+            offsets_a,
+            offsets_b,
+            set_op,
+            validity,
+            as_utf8,
+            normalize,
+            preserve_left_duplicates,
+            std::collections::hash_map::RandomState::new(),
+        )
+    } else {
+        binary(
+            a,
+            b,
+            offsets_a,
+            offsets_b,
+            set_op,
+            validity,
+            as_utf8,
+            normalize,
+            preserve_left_duplicates,
+            ahash::RandomState::new(),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn binary<'a, A, N, S>(
+    a: &'a A,
+    b: &'a A,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    set_op: SetOperation,
+    validity: Option<Bitmap>,
+    as_utf8: bool,
+    normalize: N,
+    preserve_left_duplicates: bool,
+    hasher: S,
+) -> PolarsResult<ListArray<i64>>
+where
+    A: Array,
+    &'a A: IntoIterator<Item = Option<&'a [u8]>>,
+    N: Fn(Option<&'a [u8]>) -> Option<&'a [u8]> + Copy,
+    S: BuildHasher + Clone,
+{
+    // Boolean operations are evaluated by `boolean_list_set_operation` without a value builder.
+    debug_assert!(!set_op.is_boolean());
+    // Corrupt offsets, e.g. of an array imported over FFI, would underflow the lengths of the
+    // rows below.
+    check_offsets(offsets_a, a.len())?;
+    check_offsets(offsets_b, b.len())?;
+    let broadcast_lhs = offsets_a.len() == 2;
+    let broadcast_rhs = offsets_b.len() == 2;
+    let mut set: IndexSet<Option<&[u8]>, S> = IndexSet::with_hasher(hasher.clone());
+    let mut set2: IndexSet<Option<&[u8]>, S> = IndexSet::with_hasher(hasher);
+
+    let mut values_out = MutablePlBinary::with_capacity(std::cmp::max(
+        *offsets_a.last().unwrap(),
+        *offsets_b.last().unwrap(),
+    ) as usize);
+    let mut offsets = new_offsets(offsets_a, offsets_b);
+
+    let first_a = offsets_a[0];
+    let second_a = offsets_a[1];
+    let first_b = offsets_b[0];
+    let second_b = offsets_b[1];
+    // A broadcast operand may be sliced from a larger array, so its values can hold more than
+    // its single row.
+    let broadcast_a = || {
+        a.into_iter()
+            .skip(first_a as usize)
+            .take((second_a - first_a) as usize)
+            .map(normalize)
+    };
+    let broadcast_b = || {
+        b.into_iter()
+            .skip(first_b as usize)
+            .take((second_b - first_b) as usize)
+            .map(normalize)
+    };
+    if broadcast_rhs {
+        set2.extend(broadcast_b());
+    } else if broadcast_lhs && set_op == SetOperation::SymmetricDifference {
+        set.extend(broadcast_a());
+    }
+    let offsets_slice = if offsets_a.len() > offsets_b.len() {
+        offsets_a
+    } else {
+        offsets_b
+    };
+    for i in 1..offsets_slice.len() {
+        // If we go OOB we take the first element as we are then broadcasting.
+        let start_a = *offsets_a.get(i - 1).unwrap_or(&first_a) as usize;
+        let end_a = *offsets_a.get(i).unwrap_or(&second_a) as usize;
+
+        let start_b = *offsets_b.get(i - 1).unwrap_or(&first_b) as usize;
+        let end_b = *offsets_b.get(i).unwrap_or(&second_b) as usize;
+
+        let span_a = if broadcast_lhs {
+            first_a as usize..second_a as usize
+        } else {
+            start_a..end_a
+        };
+        let span_b = if broadcast_rhs {
+            first_b as usize..second_b as usize
+        } else {
+            start_b..end_b
+        };
+        // Only a row with more elements than the threshold can have as many distinct ones, so
+        // the other rows skip the check.
+        if span_a.len() + span_b.len() > SPILL_THRESHOLD {
+            let mut a_iter = a
+                .into_iter()
+                .skip(span_a.start)
+                .take(span_a.len())
+                .map(normalize);
+            let mut b_iter = b
+                .into_iter()
+                .skip(span_b.start)
+                .take(span_b.len())
+                .map(normalize);
+            // Hash the row as usual, unless a set already holds a broadcast operand.
+            let lhs_filled =
+                broadcast_lhs && !broadcast_rhs && set_op == SetOperation::SymmetricDifference;
+            let fill_lhs = !lhs_filled && !preserve_left_duplicates;
+            if !lhs_filled {
+                set.clear();
+            }
+            if !broadcast_rhs {
+                set2.clear();
+            }
+            let spilled = (fill_lhs && extend_or_spill(&mut set, &mut a_iter))
+                || (!broadcast_rhs && extend_or_spill(&mut set2, &mut b_iter));
+            let offset = if spilled {
+                // The elements hashed so far are in order of first occurrence, and are followed
+                // by those not hashed yet.
+                sort_merge_row(
+                    set.iter().copied().chain(a_iter),
+                    set2.iter().copied().chain(b_iter),
+                    set_op,
+                    preserve_left_duplicates,
+                    &mut values_out,
+                )
+            } else {
+                // The sets hold the elements of the row, except for `a` if its duplicates are
+                // preserved, which is then still in `a_iter`.
+                set_operation(
+                    &mut set,
+                    &mut set2,
+                    a_iter,
+                    b_iter,
+                    &mut values_out,
+                    set_op,
+                    true,
+                    true,
+                    preserve_left_duplicates,
+                )
+            };
+            offsets.push(offset as i64);
+            continue;
+        }
+
+        // The branches are the same every loop.
+        // We rely on branch prediction here.
+        let offset = if broadcast_rhs {
+            // going via skip iterator instead of slice doesn't heap alloc nor trigger a bitcount
+            let a_iter = a
+                .into_iter()
+                .skip(start_a)
+                .take(end_a - start_a)
+                .map(normalize);
+            let b_iter = broadcast_b();
+            set_operation(
+                &mut set,
+                &mut set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                true,
+                false,
+                preserve_left_duplicates,
+            )
+        } else if broadcast_lhs {
+            let a_iter = broadcast_a();
+            let b_iter = b
+                .into_iter()
+                .skip(start_b)
+                .take(end_b - start_b)
+                .map(normalize);
+            set_operation(
+                &mut set,
+                &mut set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                false,
+                set_op == SetOperation::SymmetricDifference,
+                preserve_left_duplicates,
+            )
+        } else {
+            // going via skip iterator instead of slice doesn't heap alloc nor trigger a bitcount
+            let a_iter = a
+                .into_iter()
+                .skip(start_a)
+                .take(end_a - start_a)
+                .map(normalize);
+            let b_iter = b
+                .into_iter()
+                .skip(start_b)
+                .take(end_b - start_b)
+                .map(normalize);
+            set_operation(
+                &mut set,
+                &mut set2,
+                a_iter,
+                b_iter,
+                &mut values_out,
+                set_op,
+                false,
+                false,
+                preserve_left_duplicates,
+            )
+        };
+        offsets.push(offset as i64);
+    }
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let values = values_out.freeze();
+
+    if as_utf8 {
+        let values = unsafe { values.to_utf8view_unchecked() };
+        let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+        Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+    } else {
+        let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+        Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+    }
+}
+
+/// The start of the output offsets of two list arrays, with room for the offsets of all output
+/// rows. There are as many as there are rows in the longer operand, as the other one is either
+/// just as long or broadcast.
+pub(super) fn new_offsets(offsets_a: &[i64], offsets_b: &[i64]) -> Vec<i64> {
+    let n_rows = std::cmp::max(offsets_a.len(), offsets_b.len()) - 1;
+    let mut offsets = Vec::with_capacity(n_rows + 1);
+    offsets.push(0);
+    offsets
+}
+
+/// Combine the validities of `a` and `b`, broadcasting a unit-length operand.
+///
+/// If neither operand has a validity mask, no mask is allocated.
+pub(super) fn combine_validities(a: &ListArray<i64>, b: &ListArray<i64>) -> Option<Bitmap> {
+    combine_many_validities(&[a, b], std::cmp::max(a.len(), b.len()))
+}
+
+/// Combine the validities of `arrays`, broadcasting unit-length arrays to `len`.
+pub(super) fn combine_many_validities(arrays: &[&ListArray<i64>], len: usize) -> Option<Bitmap> {
+    let mut validity = None;
+    for arr in arrays {
+        if arr.len() == len {
+            validity = combine_validities_and(validity.as_ref(), arr.validity());
+        } else if !arr.is_valid(0) {
+            // A broadcast null masks out every row.
+            return Some(Bitmap::new_zeroed(len));
+        }
+    }
+    validity
+}
+
+pub(super) fn downcast_values<A: Array + 'static>(values: &dyn Array) -> PolarsResult<&A> {
+    values.as_any().downcast_ref::<A>().ok_or_else(|| {
+        polars_err!(
+            ComputeError: "unexpected inner array type {:?} in list 'set' operation",
+            values.data_type()
+        )
+    })
+}
+
+/// The number of the bin of width `eps` of every float element, i.e. the element divided by
+/// `eps` and rounded to the nearest integer.
+fn float_bins(values: &dyn Array, eps: f64) -> PolarsResult<PrimitiveArray<f64>> {
+    let bins = match values.data_type() {
+        ArrowDataType::Float32 => {
+            let arr = downcast_values::<PrimitiveArray<f32>>(values)?;
+            arr.values()
+                .iter()
+                .map(|&v| (v as f64 / eps).round())
+                .collect()
+        },
+        _ => {
+            let arr = downcast_values::<PrimitiveArray<f64>>(values)?;
+            arr.values().iter().map(|&v| (v / eps).round()).collect()
+        },
+    };
+    Ok(PrimitiveArray::from_vec(bins).with_validity(values.validity().cloned()))
+}
+
+/// The absolute value of every numeric element: floats as `f64` and integers as `u64`, which
+/// holds the magnitude of any of them.
+fn abs_keys(values: &dyn Array) -> PolarsResult<Box<dyn Array>> {
+    let physical = DataType::from(values.data_type());
+    with_match_physical_numeric_type!(physical, |$T| {
+        let arr = downcast_values::<PrimitiveArray<$T>>(values)?;
+        let validity = arr.validity().cloned();
+        Ok(if physical.is_float() {
+            let keys = arr.values().iter().map(|v| v.to_f64().unwrap().abs());
+            PrimitiveArray::from_vec(keys.collect()).with_validity(validity).boxed()
+        } else {
+            let keys = arr
+                .values()
+                .iter()
+                .map(|v| v.to_i64().map_or_else(|| v.to_u64().unwrap(), i64::unsigned_abs));
+            PrimitiveArray::<u64>::from_vec(keys.collect()).with_validity(validity).boxed()
+        })
+    })
+}
+
+/// Downcast the inner values of both operands to `A`.
+fn downcast_pair<'a, A: Array + 'static>(
+    values_a: &'a dyn Array,
+    values_b: &'a dyn Array,
+) -> PolarsResult<(&'a A, &'a A)> {
+    Ok((downcast_values(values_a)?, downcast_values(values_b)?))
+}
+
+/// The inner values of a pair of aligned list arrays, downcast to the arrays their kernel works
+/// on.
+// Only one lives at a time, per pair of chunks.
+#[allow(clippy::large_enum_variant)]
+enum ValuesPair<'a> {
+    /// Strings, viewed as bytes.
+    Utf8(BinaryViewArray, BinaryViewArray),
+    Binary(&'a BinaryViewArray, &'a BinaryViewArray),
+    /// Numeric values of the given physical type, see [`downcast_pair`].
+    Numeric(DataType),
+    /// The 128-bit integers of decimals.
+    Decimal(&'a PrimitiveArray<i128>, &'a PrimitiveArray<i128>),
+    /// Any other type, which the kernels handle as [`AnyValue`]s.
+    Other,
+}
+
+/// Resolve which kernel handles the inner values of a pair of aligned list arrays, once per
+/// pair of chunks.
+fn values_pair<'a>(
+    values_a: &'a dyn Array,
+    values_b: &'a dyn Array,
+) -> PolarsResult<ValuesPair<'a>> {
+    // Only the element types have to match, the child field names and nullability flags of the
+    // list types are not part of them.
+    polars_ensure!(
+        values_a.data_type() == values_b.data_type(),
+        ComputeError: "list 'set' operation got mismatching inner types {:?} and {:?}",
+        values_a.data_type(), values_b.data_type()
+    );
+    Ok(match values_a.data_type() {
+        ArrowDataType::Utf8View => {
+            let (a, b) = downcast_pair::<Utf8ViewArray>(values_a, values_b)?;
+            ValuesPair::Utf8(a.to_binview(), b.to_binview())
+        },
+        ArrowDataType::BinaryView => {
+            let (a, b) = downcast_pair(values_a, values_b)?;
+            ValuesPair::Binary(a, b)
+        },
+        ArrowDataType::Decimal(_, _) => {
+            let (a, b) = downcast_pair(values_a, values_b)?;
+            ValuesPair::Decimal(a, b)
+        },
+        // Floats are hashed by their total order, so all NaNs compare equal. Infinities are
+        // ordinary values, distinct from each other and from NaN.
+        dtype if DataType::from(dtype).is_numeric() => ValuesPair::Numeric(dtype.into()),
+        _ => ValuesPair::Other,
+    })
+}
+
+/// Decode dictionary-encoded values, so that elements are compared by their dictionary values
+/// rather than by their keys. Other arrays are returned as is.
+fn decode_dictionary(values: &dyn Array) -> PolarsResult<Box<dyn Array>> {
+    let ArrowDataType::Dictionary(key_type, value_type, _) = values.data_type() else {
+        return Ok(values.to_boxed());
+    };
+    // The kernels work on the view types.
+    let to_type = match value_type.as_ref() {
+        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 => ArrowDataType::Utf8View,
+        ArrowDataType::Binary | ArrowDataType::LargeBinary => ArrowDataType::BinaryView,
+        dt => dt.clone(),
+    };
+    let (dict, idx) = match_integer_type!(key_type, |$T| {
+        let arr = downcast_values::<DictionaryArray<$T>>(values)?;
+        let idx = IdxArr::from_vec(arr.keys_values_iter().map(|k| k as IdxSize).collect())
+            .with_validity(arr.keys().validity().cloned());
+        (cast(arr.values().as_ref(), &to_type, CastOptions::default())?, idx)
+    });
+    // SAFETY: the keys of a dictionary array are in bounds of its values.
+    Ok(unsafe { take_unchecked(dict.as_ref(), &idx) })
+}
+
+/// Whether values of `dtype` can't be handled by the kernels as is: dictionaries and the
+/// offset-based `Binary`/`Utf8` layouts, which arrays imported over FFI may hold.
+fn is_foreign_values(dtype: &ArrowDataType) -> bool {
+    matches!(
+        dtype,
+        ArrowDataType::Dictionary(..)
+            | ArrowDataType::Binary
+            | ArrowDataType::LargeBinary
+            | ArrowDataType::Utf8
+            | ArrowDataType::LargeUtf8
+    )
+}
+
+/// Bring the values of [`is_foreign_values`] to the view types the kernels work on. Other
+/// arrays are returned as is.
+fn to_view_values(values: &dyn Array) -> PolarsResult<Box<dyn Array>> {
+    let to_type = match values.data_type() {
+        ArrowDataType::Dictionary(..) => return decode_dictionary(values),
+        ArrowDataType::Binary | ArrowDataType::LargeBinary => ArrowDataType::BinaryView,
+        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 => ArrowDataType::Utf8View,
+        _ => return Ok(values.to_boxed()),
+    };
+    cast(values, &to_type, CastOptions::default())
+}
+
+/// [`binary`] on lists of which both the values are `Binary` with i32 offsets. The output
+/// values are `BinaryView`, as if the values were promoted before.
+fn small_binary_set_operation(
+    a: &ListArray<i64>,
+    b: &ListArray<i64>,
+    set_op: SetOperation,
+    options: &SetOpOptions,
+) -> PolarsResult<ListArray<i64>> {
+    let (values_a, values_b) =
+        downcast_pair::<BinaryArray<i32>>(a.values().as_ref(), b.values().as_ref())?;
+    let (offsets_a, offsets_b) = (a.offsets().as_slice(), b.offsets().as_slice());
+    let validity = combine_validities(a, b);
+    let preserve_left_duplicates = options.preserve_left_duplicates
+        && matches!(
+            set_op,
+            SetOperation::Intersection | SetOperation::Difference
+        );
+    if options.trim {
+        binary_hashed(
+            values_a,
+            values_b,
+            offsets_a,
+            offsets_b,
+            set_op,
+            validity,
+            false,
+            trim_binary,
+            preserve_left_duplicates,
+            options.dos_resistant_hashing,
+        )
+    } else {
+        binary_hashed(
+            values_a,
+            values_b,
+            offsets_a,
+            offsets_b,
+            set_op,
+            validity,
+            false,
+            |v| v,
+            preserve_left_duplicates,
+            options.dos_resistant_hashing,
+        )
+    }
+}
+
+/// Turn the null rows of `arr` into valid empty lists. Null rows may still span elements, those
+/// are dropped.
+fn null_rows_as_empty(arr: &ListArray<i64>) -> ListArray<i64> {
+    let Some(validity) = arr.validity().filter(|v| v.unset_bits() > 0) else {
+        return arr.clone().with_validity(None);
+    };
+    let offsets = arr.offsets().as_slice();
+    let null_spans_elements =
+        (0..arr.len()).any(|row| !validity.get_bit(row) && offsets[row] != offsets[row + 1]);
+    if !null_spans_elements {
+        return arr.clone().with_validity(None);
+    }
+    let mut idx = Vec::with_capacity(arr.values().len());
+    let mut new_offsets = Vec::with_capacity(offsets.len());
+    new_offsets.push(0i64);
+    for row in 0..arr.len() {
+        if validity.get_bit(row) {
+            idx.extend((offsets[row] as IdxSize)..(offsets[row + 1] as IdxSize));
+        }
+        new_offsets.push(idx.len() as i64);
+    }
+    // SAFETY: the offsets of a list array are in bounds of its values.
+    let values = unsafe { take_unchecked(arr.values().as_ref(), &IdxArr::from_vec(idx)) };
+    // SAFETY: the offsets are monotonically increasing and in bounds of the values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(new_offsets.into()) };
+    ListArray::new(arr.data_type().clone(), offsets, values, None)
+}
+
+/// Apply `set_op` between the lists of two aligned arrays. If `pooled` is set the numeric
+/// kernel reuses the scratch buffers of earlier calls on this thread.
+fn array_set_operation(
+    a: &ListArray<i64>,
+    b: &ListArray<i64>,
+    set_op: SetOperation,
+    options: &SetOpOptions,
+    pooled: bool,
+) -> PolarsResult<ListArray<i64>> {
+    if options.null_row_as_empty && (a.validity().is_some() || b.validity().is_some()) {
+        let options = SetOpOptions {
+            null_row_as_empty: false,
+            ..*options
+        };
+        return array_set_operation(
+            &null_rows_as_empty(a),
+            &null_rows_as_empty(b),
+            set_op,
+            &options,
+            pooled,
+        );
+    }
+
+    if is_foreign_values(a.values().data_type()) || is_foreign_values(b.values().data_type()) {
+        if a.values().data_type() == &ArrowDataType::Binary
+            && b.values().data_type() == &ArrowDataType::Binary
+            && !options.sequence_mode
+        {
+            // The kernel iterates small binary directly, which spares the copy to views.
+            return small_binary_set_operation(a, b, set_op, options);
+        }
+        let convert = |arr: &ListArray<i64>| -> PolarsResult<ListArray<i64>> {
+            let values = to_view_values(arr.values().as_ref())?;
+            let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+            Ok(ListArray::new(
+                dtype,
+                arr.offsets().clone(),
+                values,
+                arr.validity().cloned(),
+            ))
+        };
+        return array_set_operation(&convert(a)?, &convert(b)?, set_op, options, pooled);
+    }
+    if options.sequence_mode {
+        return sequence_set_operation(a, b, set_op);
+    }
+
+    let offsets_a = a.offsets().as_slice();
+    let offsets_b = b.offsets().as_slice();
+
+    let values_a = a.values().as_ref();
+    let values_b = b.values().as_ref();
+    let dtype = values_b.data_type();
+    let validity = combine_validities(a, b);
+
+    let preserve_left_duplicates = options.preserve_left_duplicates
+        && matches!(
+            set_op,
+            SetOperation::Intersection | SetOperation::Difference
+        );
+
+    match values_pair(values_a, values_b)? {
+        ValuesPair::Utf8(a, b) if options.strip_accents || options.casing_resolution.is_some() => {
+            // The output holds the trimmed elements, so they are gathered from trimmed values.
+            let (a, b) = if options.trim {
+                (trim_utf8_values(&a), trim_utf8_values(&b))
+            } else {
+                (a, b)
+            };
+            let kernel = IdxSetOperation {
+                offsets_a,
+                offsets_b,
+                set_op,
+                preserve_left_duplicates,
+            };
+            let builder = kernel.call(
+                &keyed_binary(&a, &|v| string_key(v, options)),
+                &keyed_binary(&b, &|v| string_key(v, options)),
+            )?;
+            // SAFETY: both are views of utf8 values.
+            let (utf8_a, utf8_b) =
+                unsafe { (a.to_utf8view_unchecked(), b.to_utf8view_unchecked()) };
+            let values = concatenate(&[&utf8_a as &dyn Array, &utf8_b])?;
+            // SAFETY: `values` are the values of `a` followed by the values of `b`.
+            let out = unsafe { builder.finish(values.as_ref(), validity) };
+            match options.casing_resolution {
+                // The casings are counted from the trimmed values as well.
+                Some(CasingResolution::MostFrequent) => {
+                    most_frequent_casing(out, &a, &b, offsets_a, offsets_b, &|v| {
+                        string_key(v, options)
+                    })
+                },
+                Some(CasingResolution::Lowercase) => lowercase_values(out),
+                Some(CasingResolution::First) | None => Ok(out),
+            }
+        },
+        ValuesPair::Utf8(a, b) => {
+            if options.trim {
+                binary_hashed(
+                    &a,
+                    &b,
+                    offsets_a,
+                    offsets_b,
+                    set_op,
+                    validity,
+                    true,
+                    trim_utf8,
+                    preserve_left_duplicates,
+                    options.dos_resistant_hashing,
+                )
+            } else {
+                binary_hashed(
+                    &a,
+                    &b,
+                    offsets_a,
+                    offsets_b,
+                    set_op,
+                    validity,
+                    true,
+                    |v| v,
+                    preserve_left_duplicates,
+                    options.dos_resistant_hashing,
+                )
+            }
+        },
+        ValuesPair::Binary(a, b) => {
+            if options.trim {
+                binary_hashed(
+                    a,
+                    b,
+                    offsets_a,
+                    offsets_b,
+                    set_op,
+                    validity,
+                    false,
+                    trim_binary,
+                    preserve_left_duplicates,
+                    options.dos_resistant_hashing,
+                )
+            } else {
+                binary_hashed(
+                    a,
+                    b,
+                    offsets_a,
+                    offsets_b,
+                    set_op,
+                    validity,
+                    false,
+                    |v| v,
+                    preserve_left_duplicates,
+                    options.dos_resistant_hashing,
+                )
+            }
+        },
+        ValuesPair::Numeric(physical)
+            if options.abs_normalize || (physical.is_float() && options.approx_eps.is_some()) =>
+        {
+            // The elements are compared by key, while the output gathers the elements themselves.
+            let (mut keys_a, mut keys_b) = (values_a.to_boxed(), values_b.to_boxed());
+            if let (true, Some(eps)) = (physical.is_float(), options.approx_eps) {
+                polars_ensure!(
+                    eps.is_finite() && eps > 0.0,
+                    InvalidOperation: "`approx_eps` of a list 'set' operation must be positive and finite, got {}", eps
+                );
+                keys_a = float_bins(keys_a.as_ref(), eps)?.boxed();
+                keys_b = float_bins(keys_b.as_ref(), eps)?.boxed();
+            }
+            if options.abs_normalize {
+                keys_a = abs_keys(keys_a.as_ref())?;
+                keys_b = abs_keys(keys_b.as_ref())?;
+            }
+            let kernel = IdxSetOperation {
+                offsets_a,
+                offsets_b,
+                set_op,
+                preserve_left_duplicates,
+            };
+            let builder = with_set_values(keys_a.as_ref(), keys_b.as_ref(), kernel)?;
+            let values = concatenate(&[values_a, values_b])?;
+            // SAFETY: `values` are the values of `a` followed by the values of `b`, of which the
+            // keys are of the same length.
+            Ok(unsafe { builder.finish(values.as_ref(), validity) })
+        },
+        ValuesPair::Numeric(physical) => {
+            with_match_physical_numeric_type!(physical, |$T| {
+                let (a, b) = downcast_pair::<PrimitiveArray<$T>>(values_a, values_b)?;
+
+                if set_op == SetOperation::Union
+                    && options.union_strategy == UnionStrategy::SortDedup
+                {
+                    Ok(sort_dedup_union(
+                        a,
+                        b,
+                        offsets_a,
+                        offsets_b,
+                        validity,
+                        options.nulls_last,
+                    ))
+                } else {
+                    primitive(
+                        a,
+                        b,
+                        offsets_a,
+                        offsets_b,
+                        set_op,
+                        validity,
+                        options.sort_numeric,
+                        options.nulls_last,
+                        preserve_left_duplicates,
+                        options.bitset_bits_per_element,
+                        pooled,
+                    )
+                }
+            })
+        },
+        ValuesPair::Decimal(a, b) => {
+            let out = if set_op == SetOperation::Union
+                && options.union_strategy == UnionStrategy::SortDedup
+            {
+                sort_dedup_union(a, b, offsets_a, offsets_b, validity, options.nulls_last)
+            } else {
+                primitive(
+                    a,
+                    b,
+                    offsets_a,
+                    offsets_b,
+                    set_op,
+                    validity,
+                    options.sort_numeric,
+                    options.nulls_last,
+                    preserve_left_duplicates,
+                    options.bitset_bits_per_element,
+                    pooled,
+                )?
+            };
+            // Keep the precision and scale of decimal values.
+            let values = downcast_values::<PrimitiveArray<i128>>(out.values().as_ref())?;
+            let values = values.clone().to(dtype.clone()).boxed();
+            Ok(ListArray::new(
+                ListArray::<i64>::default_datatype(dtype.clone()),
+                out.offsets().clone(),
+                values,
+                out.validity().cloned(),
+            ))
+        },
+        // Any other type goes through the slower `AnyValue` path.
+        ValuesPair::Other => {
+            let kernel = IdxSetOperation {
+                offsets_a,
+                offsets_b,
+                set_op,
+                preserve_left_duplicates,
+            };
+            let builder = with_set_values(values_a, values_b, kernel)?;
+            let values = concatenate(&[values_a, values_b])?;
+            // SAFETY: `values` are the values of `a` followed by the values of `b`.
+            Ok(unsafe { builder.finish(values.as_ref(), validity) })
+        },
+    }
+}
+
+/// Check that `set_op` produces lists rather than booleans.
+fn ensure_list_output(set_op: SetOperation) -> PolarsResult<()> {
+    polars_ensure!(
+        !set_op.is_boolean(),
+        InvalidOperation: "'{}' is a boolean list 'set' operation, use `boolean_list_set_operation`", set_op
+    );
+    Ok(())
+}
+
+/// Check that the lengths of `a` and `b` are compatible and bring them in a state the array
+/// kernels can work with.
+fn prepare_operands(a: &ListChunked, b: &ListChunked) -> PolarsResult<(ListChunked, ListChunked)> {
+    polars_ensure!(
+        a.len() == b.len() || b.len() == 1 || a.len() == 1,
+        ShapeMismatch: "list set op length mismatch: lhs={} rhs={} (expected equal, or one of them to be 1)",
+        a.len(), b.len()
+    );
+    let mut a = a.clone();
+    let mut b = b.clone();
+    // A column may have no chunks at all, while the kernels expect at least one array.
+    for ca in [&mut a, &mut b] {
+        if ca.chunks().is_empty() {
+            *ca = ListChunked::full_null_with_dtype(ca.name(), 0, ca.inner_dtype());
+        }
+    }
+    // An empty operand can only be broadcast against, so there is no row to compute. A
+    // unit-length operand is emptied as well, as the kernels would broadcast it to one row.
+    if a.is_empty() || b.is_empty() {
+        a = a.clear();
+        b = b.clear();
+    }
+    if a.len() != b.len() {
+        a = a.rechunk();
+        b = b.rechunk();
+    }
+
+    // We will OOB in the kernel otherwise.
+    a.prune_empty_chunks();
+    b.prune_empty_chunks();
+
+    // Make categoricals compatible
+    #[cfg(feature = "dtype-categorical")]
+    if let (DataType::Categorical(_, _), DataType::Categorical(_, _)) =
+        (&a.inner_dtype(), &b.inner_dtype())
+    {
+        (a, b) = make_list_categoricals_compatible(a, b)?;
+    }
+
+    Ok((a, b))
+}
+
+/// The [`prepare_operands`] counterpart for any number of columns, which are rechunked.
+///
+/// Returns the columns and the length of the output. If it is 0 every column is emptied, as
+/// in [`prepare_operands`], and there is no row to compute.
+fn prepare_many_operands(columns: &[&ListChunked]) -> PolarsResult<(Vec<ListChunked>, usize)> {
+    // An empty column can only be broadcast against, so the output is empty.
+    let len = match columns.iter().any(|ca| ca.is_empty()) {
+        true => 0,
+        false => columns.iter().map(|ca| ca.len()).max().unwrap(),
+    };
+    for ca in columns {
+        polars_ensure!(
+            ca.len() == len || ca.len() == 1,
+            ShapeMismatch: "list set operation length mismatch: got a column of length {} where {} was expected",
+            ca.len(), len
+        );
+    }
+    if len == 0 {
+        return Ok((columns.iter().map(|ca| ca.clear()).collect(), 0));
+    }
+
+    #[allow(unused_mut)]
+    let mut columns = columns.iter().map(|ca| ca.rechunk()).collect::<Vec<_>>();
+
+    // Make categoricals compatible, the rev-map of the first column ends up holding all categories.
+    #[cfg(feature = "dtype-categorical")]
+    if columns
+        .iter()
+        .all(|ca| matches!(ca.inner_dtype(), DataType::Categorical(_, _)))
+    {
+        for i in 1..columns.len() {
+            let (first, other) =
+                make_list_categoricals_compatible(columns[0].clone(), columns[i].clone())?;
+            columns[0] = first;
+            columns[i] = other;
+        }
+    }
+    Ok((columns, len))
+}
+
+/// Bring `Datetime` lists of different time zones to a common dtype. The physical values are
+/// instants in UTC, so they can be compared as is and the output is labelled as UTC.
+///
+/// Returns the operands and the dtype of the output if it differs from the one of `a`.
+#[cfg(feature = "dtype-datetime")]
+fn normalize_datetimes(
+    a: ListChunked,
+    b: ListChunked,
+    cast_time_unit: bool,
+) -> PolarsResult<(ListChunked, ListChunked, Option<DataType>)> {
+    let (DataType::Datetime(tu_a, tz_a), DataType::Datetime(tu_b, tz_b)) =
+        (a.inner_dtype().clone(), b.inner_dtype().clone())
+    else {
+        return Ok((a, b, None));
+    };
+    let b = if tu_a == tu_b {
+        b
+    } else {
+        polars_ensure!(
+            cast_time_unit,
+            InvalidOperation: "list 'set' operation on datetimes of different time units {} and {}, \
+            set `cast_time_unit` to cast the rhs to the unit of the lhs",
+            tu_a, tu_b
+        );
+        let dtype = DataType::List(Box::new(DataType::Datetime(tu_a, tz_b.clone())));
+        b.cast(&dtype)?.list()?.clone()
+    };
+    let dtype = match (&tz_a, &tz_b) {
+        (Some(zone_a), Some(zone_b)) if zone_a != zone_b => Some(DataType::List(Box::new(
+            DataType::Datetime(tu_a, Some("UTC".to_string())),
+        ))),
+        (Some(_), None) | (None, Some(_)) => polars_bail!(
+            InvalidOperation: "list 'set' operation between time-zone-aware and naive datetimes"
+        ),
+        _ => None,
+    };
+    Ok((a, b, dtype))
+}
+
+/// Apply `set_op` between the lists of `a` and `b` row by row. Either operand may be of length
+/// 1, in which case it is broadcast. [`SetOpOptions::default`] gives the plain set semantics.
+///
+/// Unless an option sorts them or keeps duplicates, the elements of every output list are
+/// distinct and in order of their first occurrence, those of `a` before those of `b`. E.g. the
+/// symmetric difference of `[5, 3, 1, 3]` and `[4, 1, 0]` is `[5, 3, 4, 0]`.
+///
+/// `Datetime` lists of different time zones compare the instants and give a list of UTC
+/// datetimes.
+///
+/// The operation is elementwise, so the streaming engine applies it to every batch on its own.
+/// An operand of length 1, e.g. a literal, is broadcast against every batch.
+///
+/// # Example
+///
+/// ```
+/// # use polars_core::prelude::*;
+/// # use polars_ops::prelude::*;
+/// let a = Series::new("a", [Series::new("", [3i32, 1, 3, 2])]);
+/// let b = Series::new("b", [Series::new("", [2i32])]);
+///
+/// let options = SetOpOptions::default()
+///     .with_sort_numeric(true)
+///     .with_preserve_left_duplicates(true);
+/// let out = list_set_operation(a.list()?, b.list()?, SetOperation::Difference, options)?;
+/// let row = out.get_as_series(0).unwrap();
+/// assert_eq!(Vec::from(row.i32()?), &[Some(1), Some(3), Some(3)]);
+/// # Ok::<(), PolarsError>(())
+/// ```
+pub fn list_set_operation(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<ListChunked> {
+    list_set_operation_impl(a, b, set_op, options, false)
+}
+
+/// [`list_set_operation`] that reuses the hash sets and other scratch buffers of numeric
+/// operations between calls on the same thread, rather than allocating them for every call.
+///
+/// This pays off when calling it many times on small inputs, e.g. once per group. The buffers
+/// are cleared between uses and never shared between threads. Buffers that grew large are
+/// not kept. The offsets and values buffers aren't pooled, as they become the buffers of the
+/// output array and can't be reused by the next call.
+pub fn list_set_operation_pooled(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<ListChunked> {
+    list_set_operation_impl(a, b, set_op, options, true)
+}
+
+/// [`list_set_operation`] whose output is of length `len`, e.g. the height of the frame the
+/// operands come from. If both operands are of length 1 the single output row is repeated
+/// `len` times. An operand whose length is neither 1 nor `len` is an error.
+pub fn list_set_operation_to_len(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    len: usize,
+) -> PolarsResult<ListChunked> {
+    for (side, ca) in [("lhs", a), ("rhs", b)] {
+        polars_ensure!(
+            ca.len() == len || ca.len() == 1,
+            ShapeMismatch: "list set op {} of length {} can't be broadcast to the expected length {}",
+            side, ca.len(), len
+        );
+    }
+    let (options, limit) = split_limit(options);
+    let out = list_set_operation(a, b, set_op, options)?;
+    let out = if out.len() == len {
+        out
+    } else {
+        out.new_from_index(0, len)
+    };
+    Ok(apply_limit(out, limit))
+}
+
+/// Split [`SetOpOptions::limit`] off `options`, for the wrappers whose output rows don't map
+/// one to one onto the rows of the operation they call. Those compute every row and slice
+/// their output with [`apply_limit`].
+pub(super) fn split_limit(options: SetOpOptions) -> (SetOpOptions, Option<usize>) {
+    let limit = options.limit;
+    (
+        SetOpOptions {
+            limit: None,
+            ..options
+        },
+        limit,
+    )
+}
+
+/// Keep the first `limit` rows of `out`, see [`split_limit`].
+pub(super) fn apply_limit(out: ListChunked, limit: Option<usize>) -> ListChunked {
+    match limit {
+        Some(limit) => out.slice(0, limit),
+        None => out,
+    }
+}
+
+/// Append the output of [`list_set_operation`] on `a` and `b` to `out`, e.g. to collect the
+/// results of successive batches of a stream in a single column.
+///
+/// `out` must be a list column of the dtype of the output, its name is kept.
+pub fn list_set_operation_extend(
+    out: &mut Series,
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<()> {
+    let batch = list_set_operation(a, b, set_op, options)?;
+    polars_ensure!(
+        out.dtype() == batch.dtype(),
+        SchemaMismatch: "cannot extend a column of dtype {} with the output of a list 'set' operation of dtype {}",
+        out.dtype(), batch.dtype()
+    );
+    out.append(&batch.into_series())?;
+    Ok(())
+}
+
+/// Check that `offsets` are monotonically increasing and in bounds of `values_len` values.
+fn check_offsets(offsets: &[i64], values_len: usize) -> PolarsResult<()> {
+    polars_ensure!(
+        offsets.windows(2).all(|w| w[0] <= w[1]),
+        ComputeError: "list 'set' operation on a list array whose offsets are not monotonically increasing"
+    );
+    let last = *offsets.last().unwrap();
+    polars_ensure!(
+        last as usize <= values_len,
+        ComputeError: "list 'set' operation on a list array whose last offset {} exceeds the length {} of its values",
+        last, values_len
+    );
+    Ok(())
+}
+
+/// [`list_set_operation`] that first validates the offsets of every chunk of `a` and `b`, which
+/// is an error rather than reading out of bounds if they are corrupt, e.g. an array imported
+/// over FFI whose offsets are not monotonic or exceed its values.
+pub fn list_set_operation_checked(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<ListChunked> {
+    for arr in a.downcast_iter().chain(b.downcast_iter()) {
+        check_offsets(arr.offsets().as_slice(), arr.values().len())?;
+    }
+    list_set_operation(a, b, set_op, options)
+}
+
+fn list_set_operation_impl(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    pooled: bool,
+) -> PolarsResult<ListChunked> {
+    ensure_list_output(set_op)?;
+    let (mut a, mut b) = prepare_operands(a, b)?;
+    if a.is_empty() || b.is_empty() {
+        // There is no row to compute, as an empty operand can only be broadcast against.
+        return encode_output(a.clear(), &options);
+    }
+    if let Some(limit) = options.limit {
+        // Slicing is zero-copy, so the rows past the limit are never visited. A row is
+        // computed even for a limit of 0, as the kernels expect at least one, and sliced off
+        // the output below.
+        for ca in [&mut a, &mut b] {
+            if ca.len() != 1 {
+                *ca = ca.slice(0, std::cmp::max(limit, 1));
+                ca.prune_empty_chunks();
+            }
+        }
+    }
+    #[cfg(feature = "dtype-datetime")]
+    let (a, b, out_dtype) = normalize_datetimes(a, b, options.cast_time_unit)?;
+
+    // we use the unsafe variant because we want to keep the nested logical types type.
+    // Every pair of aligned chunks gives its own output chunk, so the output keeps the chunk
+    // boundaries of operands that are already aligned. Only a broadcast operand makes us
+    // rechunk both.
+    let out = unsafe {
+        arity::try_binary_unchecked_same_type(
+            &a,
+            &b,
+            |a, b| {
+                let (a, b) = if options.swap_operands {
+                    (b, a)
+                } else {
+                    (a, b)
+                };
+                array_set_operation(a, b, set_op, &options, pooled).map(|arr| arr.boxed())
+            },
+            false,
+            false,
+        )
+    }?;
+    #[cfg(feature = "dtype-datetime")]
+    let out = match out_dtype {
+        // SAFETY: only the time zone changes, the physical values are the same.
+        Some(dtype) => unsafe {
+            ListChunked::from_chunks_and_dtype(out.name(), out.chunks().clone(), dtype)
+        },
+        None => out,
+    };
+    let out = match options.limit {
+        Some(0) => out.clear(),
+        _ => out,
+    };
+    encode_output(out, &options)
+}
+
+/// Apply [`SetOpOptions::dictionary_encode`] and [`SetOpOptions::categorical_threshold`] to
+/// the output.
+fn encode_output(out: ListChunked, options: &SetOpOptions) -> PolarsResult<ListChunked> {
+    if options.dictionary_encode {
+        return dictionary_encode(out);
+    }
+    match options.categorical_threshold {
+        #[cfg(feature = "dtype-categorical")]
+        Some(threshold)
+            if out.inner_dtype() == &DataType::String
+                && out.get_inner().n_unique()? < threshold =>
+        {
+            dictionary_encode(out)
+        },
+        _ => Ok(out),
+    }
+}
+
+fn dictionary_encode(out: ListChunked) -> PolarsResult<ListChunked> {
+    match out.inner_dtype() {
+        #[cfg(feature = "dtype-categorical")]
+        DataType::String => {
+            let dtype = DataType::List(Box::new(DataType::Categorical(None, Default::default())));
+            Ok(out.cast(&dtype)?.list()?.clone())
+        },
+        #[cfg(feature = "dtype-categorical")]
+        DataType::Categorical(_, _) => Ok(out),
+        dt => polars_bail!(
+            InvalidOperation: "dictionary-encoded output of list 'set' operations is not supported for inner dtype {}", dt
+        ),
+    }
+}
+
+/// Inner join `a` and `b` on the `on` columns and apply `set_op` with `options` between the
+/// list columns named `column` of the matched rows.
+///
+/// Returns the joined [`DataFrame`] with the columns of `a` and the result added as
+/// `"{column}_{set_op}"`. `column` can't be one of the `on` columns, as those are coalesced by
+/// the join.
+pub fn list_set_operation_by(
+    a: &DataFrame,
+    b: &DataFrame,
+    on: &[&str],
+    column: &str,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<DataFrame> {
+    const RHS_NAME: &str = "__POLARS_LIST_SET_RHS";
+    polars_ensure!(
+        !on.contains(&column),
+        InvalidOperation: "list 'set' operation cannot be applied to join key '{}'", column
+    );
+    // Only the list column of `b` is joined, under a name that doesn't clash with `a`.
+    let mut rhs = b.select(on.iter().copied())?;
+    rhs.with_column(b.column(column)?.clone().with_name(RHS_NAME))?;
+    let mut joined = a.join(&rhs, on, on, JoinArgs::new(JoinType::Inner))?;
+
+    let rhs = joined.drop_in_place(RHS_NAME)?;
+    let out = list_set_operation(joined.column(column)?.list()?, rhs.list()?, set_op, options)?
+        .with_name(&format!("{column}_{set_op}"));
+    joined.with_column(out)?;
+    Ok(joined)
+}
@@ -0,0 +1,90 @@
+use std::hash::Hash;
+
+use polars_core::prelude::*;
+
+/// A set that is fed elements incrementally, e.g. to maintain the running union of the
+/// elements seen per key in a stream, when the full lists are never available at once.
+///
+/// Elements keep the order of their first occurrence, like the output of
+/// [`list_set_operation`](super::list_set_operation) with default options.
+///
+/// # Example
+///
+/// ```
+/// use polars_ops::prelude::OnlineSet;
+///
+/// let mut set = OnlineSet::new();
+/// set.extend([3, 1, 3]);
+/// assert_eq!(set.len(), 2);
+///
+/// // A later batch only adds the elements that weren't seen yet.
+/// set.extend([2, 1, 4]);
+/// assert_eq!(set.len(), 4);
+/// assert!(set.contains(&2));
+/// assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 1, 2, 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct OnlineSet<K> {
+    set: PlIndexSet<K>,
+}
+
+impl<K> Default for OnlineSet<K> {
+    fn default() -> Self {
+        Self {
+            set: PlIndexSet::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq> OnlineSet<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `element`, returning whether it wasn't in the set yet.
+    pub fn insert(&mut self, element: K) -> bool {
+        self.set.insert(element)
+    }
+
+    pub fn contains(&self, element: &K) -> bool {
+        self.set.contains(element)
+    }
+
+    /// The number of distinct elements seen so far.
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// The elements seen so far, in order of their first occurrence.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &K> {
+        self.set.iter()
+    }
+
+    /// Forget all elements, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.set.clear()
+    }
+
+    /// Take the elements seen so far, in order of their first occurrence.
+    pub fn into_vec(self) -> Vec<K> {
+        self.set.into_iter().collect()
+    }
+}
+
+impl<K: Hash + Eq> Extend<K> for OnlineSet<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.set.extend(iter)
+    }
+}
+
+impl<K: Hash + Eq> FromIterator<K> for OnlineSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        Self {
+            set: iter.into_iter().collect(),
+        }
+    }
+}
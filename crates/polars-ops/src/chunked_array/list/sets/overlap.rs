@@ -0,0 +1,88 @@
+use arrow::bitmap::Bitmap;
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+
+/// The root of the tree of `row` in the disjoint-set forest `parent`, compressing the path.
+fn find(parent: &mut [usize], mut row: usize) -> usize {
+    while parent[row] != row {
+        parent[row] = parent[parent[row]];
+        row = parent[row];
+    }
+    row
+}
+
+struct MergeOverlapping<'a> {
+    offsets: &'a [i64],
+    validity: Option<&'a Bitmap>,
+}
+
+impl ManyKernel for MergeOverlapping<'_> {
+    type Output = ListIdxBuilder;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let values = values[0];
+        let n_rows = self.offsets.len() - 1;
+        let is_valid = |row: usize| !matches!(self.validity, Some(v) if !v.get_bit(row));
+        // SAFETY: the offsets of a list array are in bounds of its values.
+        let key = |i: usize| unsafe { values.key_unchecked(i) };
+
+        // Join the rows that share an element, the root of a tree being its first row.
+        let mut parent = (0..n_rows).collect::<Vec<_>>();
+        // Element -> index of its first occurrence and the row of it.
+        let mut first = PlHashMap::default();
+        for row in (0..n_rows).filter(|&row| is_valid(row)) {
+            for i in span(self.offsets, row) {
+                let (_, first_row) = *first.entry(key(i)).or_insert((i, row));
+                let (root, other) = (find(&mut parent, row), find(&mut parent, first_row));
+                parent[std::cmp::max(root, other)] = std::cmp::min(root, other);
+            }
+        }
+
+        // Collect the first occurrences of the elements per set of overlapping rows, which
+        // are numbered in order of their first row.
+        let mut set_of_root = PlHashMap::default();
+        let mut sets: Vec<Vec<usize>> = vec![];
+        for row in (0..n_rows).filter(|&row| is_valid(row)) {
+            let root = find(&mut parent, row);
+            let set = *set_of_root.entry(root).or_insert_with(|| {
+                sets.push(vec![]);
+                sets.len() - 1
+            });
+            for i in span(self.offsets, row) {
+                if first[&key(i)].0 == i {
+                    sets[set].push(i);
+                }
+            }
+        }
+
+        let mut out = ListIdxBuilder::new(sets.len());
+        for set in sets {
+            set.into_iter().for_each(|i| out.push(i));
+            out.finish_row();
+        }
+        Ok(out)
+    }
+}
+
+/// Merge the rows of `a` whose sets overlap, directly or through other rows, into a single
+/// set, e.g. to find the connected components of a graph of which the rows are adjacency
+/// lists.
+///
+/// The output has a row per set of overlapping rows, in order of the first row of every set,
+/// and the elements of a set are distinct and in order of their first appearance in `a`. An
+/// empty row overlaps with no other row and results in an empty set of its own. Null elements
+/// are regular members, so rows that both hold a null overlap, and null rows are ignored.
+pub fn merge_overlapping_sets(a: &ListChunked) -> PolarsResult<ListChunked> {
+    let a = a.rechunk();
+    let arr = a.downcast_iter().next().unwrap();
+    let kernel = MergeOverlapping {
+        offsets: arr.offsets().as_slice(),
+        validity: arr.validity(),
+    };
+    let builder = with_many_set_values(&[arr.values().as_ref()], kernel)?;
+    // SAFETY: we gather from the values of `arr` itself.
+    let out = unsafe { builder.finish(arr.values().as_ref(), None) };
+    // SAFETY: the output values are gathered from `a`, so the dtype is unchanged.
+    Ok(unsafe { a.with_chunks(vec![out.boxed()]) })
+}
@@ -0,0 +1,322 @@
+use std::hash::Hash;
+use std::ops::Range;
+
+use arrow::array::{Array, BinaryViewArray, ListArray, PrimitiveArray, StaticArray, Utf8ViewArray};
+use arrow::bitmap::Bitmap;
+#[cfg(feature = "dtype-struct")]
+use arrow::compute::cast::binary_to_binview;
+use arrow::compute::take::take_unchecked;
+use arrow::offset::OffsetsBuffer;
+use arrow::types::NativeType;
+use polars_core::prelude::*;
+use polars_core::with_match_physical_numeric_type;
+use polars_utils::total_ord::ToTotalOrd;
+
+use super::{downcast_values, SetOperation};
+
+/// Gives access to the elements of the inner values of a list array as hashable keys.
+pub(super) trait SetValues {
+    type Key<'a>: Hash + Eq + Copy
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize;
+
+    /// # Safety
+    /// `idx` must be in bounds.
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_>;
+}
+
+impl<T> SetValues for PrimitiveArray<T>
+where
+    T: NativeType + ToTotalOrd,
+    <Option<T> as ToTotalOrd>::TotalOrdItem: Hash + Eq + Copy,
+{
+    type Key<'a> = <Option<T> as ToTotalOrd>::TotalOrdItem;
+
+    fn len(&self) -> usize {
+        Array::len(self)
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.get_unchecked(idx).to_total_ord()
+    }
+}
+
+impl SetValues for BinaryViewArray {
+    type Key<'a> = Option<&'a [u8]>;
+
+    fn len(&self) -> usize {
+        Array::len(self)
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.get_unchecked(idx)
+    }
+}
+
+/// The elements of a list array of a type without a dedicated path, e.g. booleans, structs or
+/// nested lists, compared by their [`AnyValue`]. This is a lot slower than the typed paths.
+pub(super) struct AnyValues(Vec<AnyValue<'static>>);
+
+impl SetValues for AnyValues {
+    type Key<'a> = &'a AnyValue<'static>;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.0.get_unchecked(idx)
+    }
+}
+
+/// A computation over the elements of two list arrays that is generic over the element type.
+pub(super) trait PairKernel {
+    type Output;
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output>;
+}
+
+/// Apply a [`SetOperation`] row by row, selecting the first occurrence of every output element.
+pub(super) struct IdxSetOperation<'a> {
+    pub(super) offsets_a: &'a [i64],
+    pub(super) offsets_b: &'a [i64],
+    pub(super) set_op: SetOperation,
+    /// Select every element of `a` of which the membership in `b` matches for an intersection
+    /// or difference, rather than only its first occurrence.
+    pub(super) preserve_left_duplicates: bool,
+}
+
+impl PairKernel for IdxSetOperation<'_> {
+    type Output = ListIdxBuilder;
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = ListIdxBuilder::new(n_rows);
+        let mut set_a = PlIndexMap::default();
+        let mut set_b = PlIndexMap::default();
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            set_a.clear();
+            set_b.clear();
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            for i in span_a.clone() {
+                set_a.entry(unsafe { a.key_unchecked(i) }).or_insert(i);
+            }
+            for i in span_b {
+                set_b
+                    .entry(unsafe { b.key_unchecked(i) })
+                    .or_insert(a.len() + i);
+            }
+
+            if self.preserve_left_duplicates
+                && matches!(
+                    self.set_op,
+                    SetOperation::Intersection | SetOperation::Difference
+                )
+            {
+                let keep_contained = self.set_op == SetOperation::Intersection;
+                for i in span_a {
+                    // SAFETY: the offsets of a list array are in bounds of its values.
+                    if set_b.contains_key(&unsafe { a.key_unchecked(i) }) == keep_contained {
+                        out.push(i);
+                    }
+                }
+                out.finish_row();
+                continue;
+            }
+
+            let a_only = || set_a.iter().filter(|(k, _)| !set_b.contains_key(*k));
+            let b_only = || set_b.iter().filter(|(k, _)| !set_a.contains_key(*k));
+            match self.set_op {
+                SetOperation::Intersection => set_a
+                    .iter()
+                    .filter(|(k, _)| set_b.contains_key(*k))
+                    .for_each(|(_, &i)| out.push(i)),
+                SetOperation::Union => set_a
+                    .values()
+                    .chain(b_only().map(|(_, i)| i))
+                    .for_each(|&i| out.push(i)),
+                SetOperation::Difference => a_only().for_each(|(_, &i)| out.push(i)),
+                SetOperation::SymmetricDifference => {
+                    a_only().chain(b_only()).for_each(|(_, &i)| out.push(i))
+                },
+                op => unreachable!("boolean operation {op} has no list output"),
+            }
+            out.finish_row();
+        }
+        Ok(out)
+    }
+}
+
+/// A computation over the elements of any number of list arrays that is generic over the
+/// element type.
+pub(super) trait ManyKernel {
+    type Output;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output>;
+}
+
+struct Pair<K>(K);
+
+impl<K: PairKernel> ManyKernel for Pair<K> {
+    type Output = K::Output;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        self.0.call(values[0], values[1])
+    }
+}
+
+/// Resolve the physical type of the inner values of `a` and `b` and run `kernel` on them.
+pub(super) fn with_set_values<K: PairKernel>(
+    a: &dyn Array,
+    b: &dyn Array,
+    kernel: K,
+) -> PolarsResult<K::Output> {
+    with_many_set_values(&[a, b], Pair(kernel))
+}
+
+fn downcast_all<'a, A: Array + 'static>(values: &[&'a dyn Array]) -> PolarsResult<Vec<&'a A>> {
+    values.iter().map(|v| downcast_values::<A>(*v)).collect()
+}
+
+/// Resolve the physical type of the inner values of all list arrays and run `kernel` on them.
+pub(super) fn with_many_set_values<K: ManyKernel>(
+    values: &[&dyn Array],
+    kernel: K,
+) -> PolarsResult<K::Output> {
+    let dtype = values[0].data_type();
+    if let Some(other) = values.iter().find(|v| v.data_type() != dtype) {
+        polars_bail!(
+            ComputeError: "list 'set' operation got mismatching inner types {:?} and {:?}",
+            dtype, other.data_type()
+        );
+    }
+    match dtype {
+        ArrowDataType::Utf8View => {
+            let values = downcast_all::<Utf8ViewArray>(values)?
+                .into_iter()
+                .map(|v| v.to_binview())
+                .collect::<Vec<_>>();
+            kernel.call(&values.iter().collect::<Vec<_>>())
+        },
+        ArrowDataType::BinaryView => kernel.call(&downcast_all::<BinaryViewArray>(values)?),
+        dtype if DataType::from(dtype).is_numeric() => {
+            let physical: DataType = dtype.into();
+            with_match_physical_numeric_type!(physical, |$T| {
+                kernel.call(&downcast_all::<PrimitiveArray<$T>>(values)?)
+            })
+        },
+        #[cfg(feature = "dtype-struct")]
+        ArrowDataType::Struct(_) => {
+            let keys = values
+                .iter()
+                .map(|v| struct_row_keys(*v))
+                .collect::<PolarsResult<Vec<_>>>()?;
+            kernel.call(&keys.iter().collect::<Vec<_>>())
+        },
+        _ => {
+            // Owned values, as borrowed structs don't compare equal to each other.
+            let values = values
+                .iter()
+                .map(|v| {
+                    let s = Series::from_arrow("", v.to_boxed())?;
+                    let values = s.iter().map(AnyValue::into_static);
+                    Ok(AnyValues(values.collect::<PolarsResult<_>>()?))
+                })
+                .collect::<PolarsResult<Vec<_>>>()?;
+            kernel.call(&values.iter().collect::<Vec<_>>())
+        },
+    }
+}
+
+/// Encode every struct element of `values` as the bytes of all its fields, so that structs are
+/// compared field for field without materializing them as [`AnyValue`]s. Nested nulls are part
+/// of the encoding. The validity of a null struct is pushed down to its fields, so it is
+/// encoded like a struct of which all fields are null.
+#[cfg(feature = "dtype-struct")]
+fn struct_row_keys(values: &dyn Array) -> PolarsResult<BinaryViewArray> {
+    let s = Series::from_arrow("", values.to_boxed())?;
+    let rows = s.struct_()?.rows_encode()?;
+    let keys = binary_to_binview(rows.downcast_iter().next().unwrap());
+    Ok(keys)
+}
+
+/// The range of elements of row `i`, where a list array of a single row is broadcast.
+#[inline]
+pub(super) fn span(offsets: &[i64], i: usize) -> Range<usize> {
+    let i = if offsets.len() == 2 { 0 } else { i };
+    offsets[i] as usize..offsets[i + 1] as usize
+}
+
+/// Iterate over the element ranges of `a` and `b` that make up every output row.
+pub(super) fn row_spans<'a>(
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + 'a {
+    let n_rows = std::cmp::max(offsets_a.len(), offsets_b.len()) - 1;
+    (0..n_rows).map(move |i| (span(offsets_a, i), span(offsets_b, i)))
+}
+
+/// Builds a list array by selecting elements from the concatenated values of the operands,
+/// e.g. the elements of `b` are addressed with an offset of `a.values().len()`.
+pub(super) struct ListIdxBuilder {
+    idx: Vec<IdxSize>,
+    offsets: Vec<i64>,
+}
+
+impl ListIdxBuilder {
+    pub(super) fn new(n_rows: usize) -> Self {
+        let mut offsets = Vec::with_capacity(n_rows + 1);
+        offsets.push(0);
+        Self {
+            idx: vec![],
+            offsets,
+        }
+    }
+
+    #[inline]
+    pub(super) fn push(&mut self, idx: usize) {
+        self.idx.push(idx as IdxSize)
+    }
+
+    #[inline]
+    pub(super) fn finish_row(&mut self) {
+        self.offsets.push(self.idx.len() as i64)
+    }
+
+    /// # Safety
+    /// `values` must be the concatenated values of the operands.
+    pub(super) unsafe fn finish(
+        self,
+        values: &dyn Array,
+        validity: Option<Bitmap>,
+    ) -> ListArray<i64> {
+        self.finish_with(values, validity, |values| values)
+    }
+
+    /// Like [`ListIdxBuilder::finish`], but `f` can transform the gathered values before they
+    /// are wrapped in a list array.
+    ///
+    /// # Safety
+    /// `values` must be the concatenated values of the operands.
+    pub(super) unsafe fn finish_with<F>(
+        self,
+        values: &dyn Array,
+        validity: Option<Bitmap>,
+        f: F,
+    ) -> ListArray<i64>
+    where
+        F: FnOnce(Box<dyn Array>) -> Box<dyn Array>,
+    {
+        let idx = IdxArr::from_vec(self.idx);
+        let values = f(take_unchecked(values, &idx));
+        let offsets = OffsetsBuffer::new_unchecked(self.offsets.into());
+        let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+        ListArray::new(dtype, offsets, values, validity)
+    }
+}
@@ -0,0 +1,89 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+
+use polars_core::prelude::*;
+
+use super::bitset::DomainBitsets;
+
+/// Sets larger than this are dropped rather than returned to the pool, so that a single call
+/// on long lists doesn't pin its memory to the thread.
+const MAX_POOLED_CAPACITY: usize = 1 << 12;
+
+thread_local! {
+    /// The idle scratch buffers of this thread, by key type.
+    static POOL: RefCell<PlHashMap<TypeId, Box<dyn Any>>> = RefCell::new(PlHashMap::default());
+}
+
+/// The buffers of the numeric kernel that don't end up in its output.
+pub(super) struct Scratch<K> {
+    pub(super) set: PlIndexSet<K>,
+    pub(super) set2: PlIndexSet<K>,
+    pub(super) bitsets: DomainBitsets,
+}
+
+impl<K> Default for Scratch<K> {
+    fn default() -> Self {
+        Self {
+            set: Default::default(),
+            set2: Default::default(),
+            bitsets: Default::default(),
+        }
+    }
+}
+
+/// [`Scratch`] that is taken from the thread-local pool if `pooled` is set and returned to it,
+/// cleared, when dropped.
+///
+/// A buffer is owned by a single call while in use, so nested or concurrent calls on the same
+/// thread each get their own.
+pub(super) struct PooledScratch<K: 'static> {
+    scratch: Scratch<K>,
+    pooled: bool,
+}
+
+impl<K: 'static> PooledScratch<K> {
+    pub(super) fn new(pooled: bool) -> Self {
+        let scratch = if pooled {
+            POOL.with(|pool| pool.borrow_mut().remove(&TypeId::of::<K>()))
+                .and_then(|scratch| scratch.downcast::<Scratch<K>>().ok())
+                .map(|scratch| *scratch)
+                .unwrap_or_default()
+        } else {
+            Scratch::default()
+        };
+        Self { scratch, pooled }
+    }
+}
+
+impl<K: 'static> std::ops::Deref for PooledScratch<K> {
+    type Target = Scratch<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scratch
+    }
+}
+
+impl<K: 'static> std::ops::DerefMut for PooledScratch<K> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.scratch
+    }
+}
+
+impl<K: 'static> Drop for PooledScratch<K> {
+    fn drop(&mut self) {
+        let Scratch { set, set2, bitsets } = &mut self.scratch;
+        if !self.pooled || std::cmp::max(set.capacity(), set2.capacity()) > MAX_POOLED_CAPACITY {
+            return;
+        }
+        // The kernel expects empty sets, except for the ones it fills for a broadcast operand.
+        set.clear();
+        set2.clear();
+        bitsets.reset(0);
+        let scratch = std::mem::take(&mut self.scratch);
+        // The pool may already be gone if the thread is shutting down.
+        let _ = POOL.try_with(|pool| {
+            pool.borrow_mut()
+                .insert(TypeId::of::<K>(), Box::new(scratch) as Box<dyn Any>)
+        });
+    }
+}
@@ -0,0 +1,99 @@
+use arrow::array::{ListArray, PrimitiveArray};
+use arrow::compute::concatenate::concatenate;
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+
+use super::pairwise::{row_spans, with_set_values, ListIdxBuilder, PairKernel, SetValues};
+use super::{combine_validities, prepare_operands};
+
+struct UnionProvenance<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+}
+
+impl PairKernel for UnionProvenance<'_> {
+    type Output = (ListIdxBuilder, Vec<u8>);
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = ListIdxBuilder::new(n_rows);
+        let mut tags = Vec::new();
+        // Element -> (index of its first occurrence, provenance tag).
+        let mut union = PlIndexMap::default();
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            union.clear();
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            for i in span_a {
+                union.entry(unsafe { a.key_unchecked(i) }).or_insert((i, 1));
+            }
+            for i in span_b {
+                union
+                    .entry(unsafe { b.key_unchecked(i) })
+                    .or_insert((a.len() + i, 0))
+                    .1 |= 2;
+            }
+            for &(i, tag) in union.values() {
+                out.push(i);
+                tags.push(tag);
+            }
+            out.finish_row();
+        }
+        Ok((out, tags))
+    }
+}
+
+/// The union of the lists of `a` and `b` together with the provenance of every element of it:
+/// `1` if it is only found in `a`, `2` if it is only found in `b` and `3` if it is found in
+/// both.
+///
+/// Returns `(a ∪ b, provenance)`, where the provenance is a `List(UInt8)` column with the same
+/// shape as the union. The union is the one of [`SetOperation::Union`], so elements keep their
+/// order of first appearance, those of `a` first. Null rows in either operand result in null
+/// rows in both outputs.
+///
+/// [`SetOperation::Union`]: super::SetOperation::Union
+pub fn list_union_with_provenance(
+    a: &ListChunked,
+    b: &ListChunked,
+) -> PolarsResult<(ListChunked, ListChunked)> {
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let mut union_chunks = Vec::with_capacity(a.chunks().len());
+    let mut provenance_chunks = Vec::with_capacity(a.chunks().len());
+    for (arr_a, arr_b) in a.downcast_iter().zip(b.downcast_iter()) {
+        let kernel = UnionProvenance {
+            offsets_a: arr_a.offsets().as_slice(),
+            offsets_b: arr_b.offsets().as_slice(),
+        };
+        let (builder, tags) =
+            with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+
+        let values = concatenate(&[arr_a.values().as_ref(), arr_b.values().as_ref()])?;
+        let validity = combine_validities(arr_a, arr_b);
+        // SAFETY: `values` are the values of `a` followed by the values of `b`.
+        let union = unsafe { builder.finish(values.as_ref(), validity.clone()) };
+        let tags = PrimitiveArray::from_vec(tags);
+        let provenance = ListArray::<i64>::new(
+            ListArray::<i64>::default_datatype(tags.data_type().clone()),
+            union.offsets().clone(),
+            tags.boxed(),
+            validity,
+        );
+        union_chunks.push(union.boxed());
+        provenance_chunks.push(provenance.boxed());
+    }
+
+    // SAFETY: the union values are gathered from `a` and `b`, so the dtype is unchanged.
+    let union = unsafe { a.with_chunks(union_chunks) };
+    // SAFETY: the provenance chunks are lists of `u8`.
+    let provenance = unsafe {
+        ListChunked::from_chunks_and_dtype(
+            "provenance",
+            provenance_chunks,
+            DataType::List(Box::new(DataType::UInt8)),
+        )
+    };
+    Ok((union, provenance))
+}
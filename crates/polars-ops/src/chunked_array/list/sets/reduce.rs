@@ -0,0 +1,230 @@
+use arrow::array::{ListArray, PrimitiveArray};
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use arrow::compute::utils::combine_validities_and;
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::pairwise::{row_spans, with_set_values, PairKernel, SetValues};
+use super::{combine_validities, prepare_operands};
+
+/// A distinct element of a row of [`list_reduce_pairwise`] and how often it occurs in either
+/// operand.
+#[derive(Copy, Clone, Debug)]
+pub struct PairwiseElement<'a> {
+    /// The inner values of `a` and `b`.
+    values: [&'a Series; 2],
+    /// The operand and index in its inner values of the first occurrence of the element.
+    first: (usize, usize),
+    pub count_a: IdxSize,
+    pub count_b: IdxSize,
+}
+
+impl PairwiseElement<'_> {
+    /// The element, as its first occurrence in either operand. This is only materialized on
+    /// request, as most reductions only need the counts.
+    pub fn value(&self) -> AnyValue<'_> {
+        let (operand, idx) = self.first;
+        // SAFETY: the element was found at this index.
+        unsafe { self.values[operand].get_unchecked(idx) }
+    }
+}
+
+struct ReducePairwise<'a, I, F, G> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    values: [&'a Series; 2],
+    init: &'a I,
+    fold: &'a F,
+    finish: &'a G,
+}
+
+impl<N: Default, S, I, F, G> PairKernel for ReducePairwise<'_, I, F, G>
+where
+    I: Fn() -> S,
+    F: Fn(&mut S, PairwiseElement<'_>),
+    G: Fn(S) -> Option<N>,
+{
+    type Output = (Vec<N>, Option<MutableBitmap>);
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = Vec::with_capacity(n_rows);
+        // Only allocated once `finish` gives the first null.
+        let mut validity: Option<MutableBitmap> = None;
+        // Element -> (index of its first occurrence, count in a, count in b), where the
+        // elements of `b` are addressed after those of `a`.
+        let mut counts = PlIndexMap::default();
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            counts.clear();
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            for i in span_a {
+                let key = unsafe { a.key_unchecked(i) };
+                counts.entry(key).or_insert((i, 0, 0)).1 += 1;
+            }
+            for i in span_b {
+                let key = unsafe { b.key_unchecked(i) };
+                counts.entry(key).or_insert((a.len() + i, 0, 0)).2 += 1;
+            }
+            let mut state = (self.init)();
+            for &(i, count_a, count_b) in counts.values() {
+                let first = match i.checked_sub(a.len()) {
+                    Some(i) => (1, i),
+                    None => (0, i),
+                };
+                let element = PairwiseElement {
+                    values: self.values,
+                    first,
+                    count_a,
+                    count_b,
+                };
+                (self.fold)(&mut state, element);
+            }
+            match (self.finish)(state) {
+                Some(v) => {
+                    out.push(v);
+                    if let Some(validity) = &mut validity {
+                        validity.push(true);
+                    }
+                },
+                None => {
+                    let validity = validity.get_or_insert_with(|| {
+                        let mut validity = MutableBitmap::with_capacity(n_rows);
+                        validity.extend_constant(out.len(), true);
+                        validity
+                    });
+                    out.push(N::default());
+                    validity.push(false);
+                },
+            }
+        }
+        Ok((out, validity))
+    }
+}
+
+/// Reduce every pair of rows of `a` and `b` to a single value with custom semantics.
+///
+/// For every row a state is created with `init`, `fold` is called with every distinct element
+/// of the row of either operand in order of first occurrence, `a` before `b`, and `finish`
+/// turns the state into the output value, or null if it returns `None`. Elements are compared like in
+/// [`list_set_operation`](super::list_set_operation). Columns of length 1 are broadcast and
+/// null rows in either operand result in null.
+///
+/// # Example
+///
+/// Count the elements that two lists have in common.
+/// ```
+/// # use polars_core::prelude::*;
+/// # use polars_ops::prelude::*;
+/// let a = Series::new("a", [Series::new("", [1i32, 2, 3, 3])]);
+/// let b = Series::new("b", [Series::new("", [3i32, 1, 4])]);
+/// let out: IdxCa = list_reduce_pairwise(
+///     a.list()?,
+///     b.list()?,
+///     || 0,
+///     |n, el| *n += (el.count_a > 0 && el.count_b > 0) as IdxSize,
+///     Some,
+/// )?;
+/// assert_eq!(out.get(0), Some(2));
+/// # Ok::<(), PolarsError>(())
+/// ```
+pub fn list_reduce_pairwise<T, S, I, F, G>(
+    a: &ListChunked,
+    b: &ListChunked,
+    init: I,
+    fold: F,
+    finish: G,
+) -> PolarsResult<ChunkedArray<T>>
+where
+    T: PolarsNumericType,
+    I: Fn() -> S,
+    F: Fn(&mut S, PairwiseElement<'_>),
+    G: Fn(S) -> Option<T::Native>,
+{
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+    let inner_values = |arr: &ListArray<i64>, ca: &ListChunked| {
+        // SAFETY: the values of a list chunk are of its inner dtype.
+        unsafe {
+            Series::from_chunks_and_dtype_unchecked(
+                "",
+                vec![arr.values().clone()],
+                ca.inner_dtype(),
+            )
+        }
+    };
+
+    let chunks = a
+        .downcast_iter()
+        .zip(b.downcast_iter())
+        .map(|(arr_a, arr_b)| {
+            let values = [inner_values(arr_a, &a), inner_values(arr_b, &b)];
+            let kernel = ReducePairwise {
+                offsets_a: arr_a.offsets().as_slice(),
+                offsets_b: arr_b.offsets().as_slice(),
+                values: [&values[0], &values[1]],
+                init: &init,
+                fold: &fold,
+                finish: &finish,
+            };
+            let (out, defined) =
+                with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+            let defined: Option<Bitmap> = defined.map(|d| d.into());
+            let validity =
+                combine_validities_and(combine_validities(arr_a, arr_b).as_ref(), defined.as_ref());
+            Ok(PrimitiveArray::from_vec(out).with_validity(validity))
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    Ok(ChunkedArray::from_chunk_iter(a.name(), chunks))
+}
+
+/// The similarity of two empty lists, for which the similarity measures divide zero by zero.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EmptySimilarity {
+    /// Two empty lists are equal sets, so they are fully similar.
+    #[default]
+    One,
+    /// Two empty lists have no element in common, so they aren't similar at all.
+    Zero,
+    /// The similarity of two empty lists is undefined.
+    Null,
+}
+
+/// The Jaccard similarity of the lists of `a` and `b`: the number of distinct elements they
+/// have in common over the number of distinct elements in either. The similarity of two empty
+/// lists is given by `empty_similarity`. Null elements are regular members and null rows
+/// result in null.
+pub fn list_jaccard_similarity(
+    a: &ListChunked,
+    b: &ListChunked,
+    empty_similarity: EmptySimilarity,
+) -> PolarsResult<Float64Chunked> {
+    list_reduce_pairwise(
+        a,
+        b,
+        || (0, 0),
+        |(common, total): &mut (IdxSize, IdxSize), el| {
+            *common += (el.count_a > 0 && el.count_b > 0) as IdxSize;
+            *total += 1;
+        },
+        |(common, total)| match (total, empty_similarity) {
+            (0, EmptySimilarity::One) => Some(1.0),
+            (0, EmptySimilarity::Zero) => Some(0.0),
+            (0, EmptySimilarity::Null) => None,
+            _ => Some(common as f64 / total as f64),
+        },
+    )
+}
+
+/// The Jaccard distance `1.0 - similarity` of the lists of `a` and `b`, see
+/// [`list_jaccard_similarity`]. Two empty lists are at distance `0.0` if their similarity is
+/// [`EmptySimilarity::One`].
+pub fn list_jaccard_distance(
+    a: &ListChunked,
+    b: &ListChunked,
+    empty_similarity: EmptySimilarity,
+) -> PolarsResult<Float64Chunked> {
+    Ok(list_jaccard_similarity(a, b, empty_similarity)?.apply_values(|v| 1.0 - v))
+}
@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use arrow::array::{ListArray, MutableArray, MutablePrimitiveArray, PrimitiveArray};
+use arrow::datatypes::{PhysicalType, PrimitiveType};
+use arrow::offset::OffsetsBuffer;
+use arrow::types::NativeType;
+use polars_core::prelude::*;
+use roaring::RoaringBitmap;
+
+use super::pairwise::{row_spans, span};
+use super::{
+    combine_validities, downcast_values, ensure_list_output, prepare_operands, SetOperation,
+};
+
+/// A 32-bit integer, mapped to the `u32`s that roaring bitmaps hold in the same order.
+trait RoaringKey: NativeType {
+    fn to_key(self) -> u32;
+
+    fn from_key(key: u32) -> Self;
+}
+
+impl RoaringKey for u32 {
+    fn to_key(self) -> u32 {
+        self
+    }
+
+    fn from_key(key: u32) -> Self {
+        key
+    }
+}
+
+impl RoaringKey for i32 {
+    // Flipping the sign bit orders the negative numbers before the positive ones.
+    fn to_key(self) -> u32 {
+        self as u32 ^ (1 << 31)
+    }
+
+    fn from_key(key: u32) -> Self {
+        (key ^ (1 << 31)) as i32
+    }
+}
+
+/// The elements of `span` as a bitmap, and whether one of them is null. `keys` is scratch.
+fn row_bitmap<T: RoaringKey>(
+    values: &PrimitiveArray<T>,
+    span: Range<usize>,
+    keys: &mut Vec<u32>,
+) -> (RoaringBitmap, bool) {
+    let mut has_null = false;
+    keys.clear();
+    keys.extend(span.filter_map(|i| {
+        let v = values.get(i);
+        has_null |= v.is_none();
+        v.map(T::to_key)
+    }));
+    // Appending sorted keys fills the containers one at a time, which is several times faster
+    // than inserting them in any order.
+    keys.sort_unstable();
+    keys.dedup();
+    let bitmap = RoaringBitmap::from_sorted_iter(keys.iter().copied()).unwrap();
+    (bitmap, has_null)
+}
+
+fn roaring_primitive<T: RoaringKey>(
+    arr_a: &ListArray<i64>,
+    arr_b: &ListArray<i64>,
+    set_op: SetOperation,
+) -> PolarsResult<ListArray<i64>> {
+    let values_a = downcast_values::<PrimitiveArray<T>>(arr_a.values().as_ref())?;
+    let values_b = downcast_values::<PrimitiveArray<T>>(arr_b.values().as_ref())?;
+    let (offsets_a, offsets_b) = (arr_a.offsets().as_slice(), arr_b.offsets().as_slice());
+    // The bitmap of a broadcast operand is built once.
+    let mut keys = vec![];
+    let mut broadcast = |values, offsets: &[i64]| {
+        (offsets.len() == 2).then(|| row_bitmap(values, span(offsets, 0), &mut keys))
+    };
+    let (broadcast_a, broadcast_b) = (
+        broadcast(values_a, offsets_a),
+        broadcast(values_b, offsets_b),
+    );
+
+    let mut values = MutablePrimitiveArray::<T>::new();
+    let mut offsets = Vec::with_capacity(std::cmp::max(offsets_a.len(), offsets_b.len()));
+    offsets.push(0i64);
+    for (span_a, span_b) in row_spans(offsets_a, offsets_b) {
+        let (row_a, row_b);
+        let (a, null_a) = match &broadcast_a {
+            Some((a, null_a)) => (a, *null_a),
+            None => {
+                row_a = row_bitmap(values_a, span_a, &mut keys);
+                (&row_a.0, row_a.1)
+            },
+        };
+        let (b, null_b) = match &broadcast_b {
+            Some((b, null_b)) => (b, *null_b),
+            None => {
+                row_b = row_bitmap(values_b, span_b, &mut keys);
+                (&row_b.0, row_b.1)
+            },
+        };
+        let (out, null) = match set_op {
+            SetOperation::Union => (a | b, null_a || null_b),
+            SetOperation::Intersection => (a & b, null_a && null_b),
+            SetOperation::Difference => (a - b, null_a && !null_b),
+            SetOperation::SymmetricDifference => (a ^ b, null_a != null_b),
+            op => unreachable!("boolean operation {op} has no list output"),
+        };
+        if null {
+            values.push_null();
+        }
+        values.extend(out.iter().map(|key| Some(T::from_key(key))));
+        offsets.push(values.len() as i64);
+    }
+
+    let values: PrimitiveArray<T> = values.into();
+    let values = values.to(values_a.data_type().clone());
+    // SAFETY: the offsets are monotonically increasing and in bounds of the values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+    let validity = combine_validities(arr_a, arr_b);
+    Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+}
+
+/// Apply `set_op` between the lists of `a` and `b` with roaring bitmaps rather than hash sets.
+/// Either operand may be of length 1, in which case it is broadcast.
+///
+/// Building the bitmaps of every row from its unsorted elements dominates the cost, so this is
+/// only faster than [`list_set_operation`](super::list_set_operation) for rows of around
+/// 100,000 elements spread over a wide range, by about a fifth according to the
+/// `list_sets_roaring` bench. Rows of a dense range of integers are better served by the
+/// bitset path of [`list_set_operation`](super::list_set_operation).
+///
+/// Only inner dtypes of which the physical type is `Int32` or `UInt32` are supported. The
+/// output lists are sorted in ascending order, led by a null if the result holds one.
+pub fn list_set_operation_roaring(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+) -> PolarsResult<ListChunked> {
+    ensure_list_output(set_op)?;
+    let (a, b) = prepare_operands(a, b)?;
+
+    // SAFETY: the output values are of the inner dtype of `a`.
+    unsafe {
+        arity::try_binary_unchecked_same_type(
+            &a,
+            &b,
+            |arr_a, arr_b| {
+                let dtype = arr_a.values().data_type();
+                let out = match dtype.to_physical_type() {
+                    PhysicalType::Primitive(PrimitiveType::Int32) => {
+                        roaring_primitive::<i32>(arr_a, arr_b, set_op)
+                    },
+                    PhysicalType::Primitive(PrimitiveType::UInt32) => {
+                        roaring_primitive::<u32>(arr_a, arr_b, set_op)
+                    },
+                    _ => polars_bail!(
+                        InvalidOperation: "roaring list 'set' operations are not supported for inner dtype {:?}", dtype
+                    ),
+                }?;
+                Ok(out.boxed())
+            },
+            false,
+            false,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+use super::{ensure_list_output, SetOperation};
+
+struct Rolling<'a> {
+    offsets: &'a [i64],
+    validity: Option<&'a Bitmap>,
+    window: usize,
+    set_op: SetOperation,
+}
+
+impl Rolling<'_> {
+    #[inline]
+    fn is_valid(&self, row: usize) -> bool {
+        match self.validity {
+            Some(v) => v.get_bit(row),
+            None => true,
+        }
+    }
+}
+
+impl ManyKernel for Rolling<'_> {
+    type Output = (ListIdxBuilder, MutableBitmap);
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let values = values[0];
+        let n_rows = self.offsets.len() - 1;
+        let mut out = ListIdxBuilder::new(n_rows);
+        let mut validity = MutableBitmap::with_capacity(n_rows);
+        // Element -> index of its first occurrence in every valid row of the window that
+        // contains it, oldest row first. Rows enter and leave in order, so the indices of a
+        // leaving row are always at the front.
+        let mut occurrences: PlHashMap<_, VecDeque<usize>> = PlHashMap::default();
+        let mut n_valid = 0usize;
+        let mut seen = PlHashSet::default();
+        let intersect = self.set_op == SetOperation::Intersection;
+
+        // SAFETY (all `key_unchecked` calls): the offsets of a list array are in bounds of its
+        // values.
+        let mut update = |row: usize, occurrences: &mut PlHashMap<_, VecDeque<usize>>, add| {
+            seen.clear();
+            for i in span(self.offsets, row) {
+                let key = unsafe { values.key_unchecked(i) };
+                if !seen.insert(key) {
+                    continue;
+                }
+                if add {
+                    occurrences.entry(key).or_default().push_back(i);
+                } else if let Some(rows) = occurrences.get_mut(&key) {
+                    rows.pop_front();
+                    if rows.is_empty() {
+                        occurrences.remove(&key);
+                    }
+                }
+            }
+        };
+
+        let mut row_out = vec![];
+        for row in 0..n_rows {
+            if self.is_valid(row) {
+                update(row, &mut occurrences, true);
+                n_valid += 1;
+            }
+            if row >= self.window && self.is_valid(row - self.window) {
+                update(row - self.window, &mut occurrences, false);
+                n_valid -= 1;
+            }
+
+            // The union holds every element in the window and the intersection those in every
+            // valid row. Both are ordered by the first occurrence of their elements, as values
+            // are laid out in row order.
+            row_out.clear();
+            row_out.extend(
+                occurrences
+                    .values()
+                    .filter(|rows| !intersect || rows.len() == n_valid)
+                    .map(|rows| rows[0]),
+            );
+            row_out.sort_unstable();
+            row_out.iter().for_each(|&i| out.push(i));
+            validity.push(n_valid > 0);
+            out.finish_row();
+        }
+        Ok((out, validity))
+    }
+}
+
+/// Apply `set_op` per row over the lists of a sliding window of `window` rows that ends at the
+/// current row.
+///
+/// Only [`SetOperation::Union`] and [`SetOperation::Intersection`] are supported. The first
+/// `window - 1` rows use the partial window of the rows before them. Null rows are ignored and
+/// a row whose window holds only nulls is null. Union elements keep their order of first
+/// appearance in the window, intersection elements the order of the oldest valid row.
+pub fn list_set_rolling(
+    a: &ListChunked,
+    window: usize,
+    set_op: SetOperation,
+) -> PolarsResult<ListChunked> {
+    polars_ensure!(window >= 1, ComputeError: "`window` must be at least 1, got {}", window);
+    ensure_list_output(set_op)?;
+    polars_ensure!(
+        matches!(set_op, SetOperation::Union | SetOperation::Intersection),
+        InvalidOperation: "rolling list 'set' operation {} is not supported, use union or intersection",
+        set_op
+    );
+    let a = a.rechunk();
+    let chunks = a
+        .downcast_iter()
+        .map(|arr| {
+            let kernel = Rolling {
+                offsets: arr.offsets().as_slice(),
+                validity: arr.validity(),
+                window,
+                set_op,
+            };
+            let (builder, validity) = with_many_set_values(&[arr.values().as_ref()], kernel)?;
+            // SAFETY: we gather from the values of `arr` itself.
+            let arr = unsafe { builder.finish(arr.values().as_ref(), validity.into()) };
+            Ok(arr.boxed())
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    // SAFETY: the output values are gathered from `a`, so the dtype is unchanged.
+    Ok(unsafe { a.with_chunks(chunks) })
+}
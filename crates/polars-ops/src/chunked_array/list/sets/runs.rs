@@ -0,0 +1,107 @@
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use polars_core::prelude::*;
+
+use super::pairwise::{span, with_many_set_values, ManyKernel, SetValues};
+use super::{
+    apply_limit, list_set_operation, prepare_operands, split_limit, SetOpOptions, SetOperation,
+};
+
+/// Finds the rows that differ from the row before them.
+struct RunStarts<'a> {
+    offsets: &'a [i64],
+    validity: Option<&'a Bitmap>,
+}
+
+impl ManyKernel for RunStarts<'_> {
+    type Output = MutableBitmap;
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let values = values[0];
+        let n_rows = self.offsets.len() - 1;
+        let is_valid = |row: usize| !matches!(self.validity, Some(v) if !v.get_bit(row));
+        let mut out = MutableBitmap::with_capacity(n_rows);
+        for row in 0..n_rows {
+            let starts_run = row == 0
+                || match (is_valid(row - 1), is_valid(row)) {
+                    (false, false) => false,
+                    (true, true) => {
+                        let prev = span(self.offsets, row - 1);
+                        let cur = span(self.offsets, row);
+                        // SAFETY: the offsets of a list array are in bounds of its values.
+                        prev.len() != cur.len()
+                            || prev.zip(cur).any(|(i, j)| unsafe {
+                                values.key_unchecked(i) != values.key_unchecked(j)
+                            })
+                    },
+                    _ => true,
+                };
+            out.push(starts_run);
+        }
+        Ok(out)
+    }
+}
+
+/// Whether every row of `ca` starts a run of equal rows.
+fn run_starts(ca: &ListChunked) -> PolarsResult<Bitmap> {
+    let ca = ca.rechunk();
+    let arr = ca.downcast_iter().next().unwrap();
+    let kernel = RunStarts {
+        offsets: arr.offsets().as_slice(),
+        validity: arr.validity(),
+    };
+    Ok(with_many_set_values(&[arr.values().as_ref()], kernel)?.into())
+}
+
+/// [`list_set_operation`] that applies `set_op` once per run of consecutive rows where both
+/// `a` and `b` are equal to the row before, and repeats the output of the run for its rows.
+///
+/// This is faster on columns with long runs of identical lists, e.g. sorted or repeated data,
+/// and slower on others because of the extra comparisons. The output is the same.
+pub fn list_set_operation_runs(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+) -> PolarsResult<ListChunked> {
+    let (a, b) = prepare_operands(a, b)?;
+    let len = std::cmp::max(a.len(), b.len());
+    // A broadcast operand is the same for every row.
+    let starts = match (a.len() == len, b.len() == len) {
+        (true, true) => &run_starts(&a)? | &run_starts(&b)?,
+        (true, false) => run_starts(&a)?,
+        _ => run_starts(&b)?,
+    };
+    if starts.unset_bits() == 0 || len <= 1 {
+        return list_set_operation(&a, &b, set_op, options);
+    }
+
+    let first_rows = IdxCa::from_vec(
+        "",
+        starts
+            .iter()
+            .enumerate()
+            .filter_map(|(row, start)| start.then_some(row as IdxSize))
+            .collect(),
+    );
+    let run_of_row = IdxCa::from_vec(
+        "",
+        starts
+            .iter()
+            .scan(0 as IdxSize, |n_runs, start| {
+                *n_runs += start as IdxSize;
+                Some(*n_runs - 1)
+            })
+            .collect(),
+    );
+    let gather = |ca: &ListChunked| -> PolarsResult<ListChunked> {
+        if ca.len() == len {
+            ca.take(&first_rows)
+        } else {
+            Ok(ca.clone())
+        }
+    };
+    // The output has a row per run, the limit applies to the rows they are repeated for.
+    let (options, limit) = split_limit(options);
+    let out = list_set_operation(&gather(&a)?, &gather(&b)?, set_op, options)?;
+    Ok(apply_limit(out.take(&run_of_row)?, limit))
+}
@@ -0,0 +1,71 @@
+use arrow::array::{Array, ListArray};
+use arrow::offset::OffsetsBuffer;
+use polars_core::prelude::*;
+
+use super::{list_set_operation, SetOpOptions, SetOperation};
+
+/// Remove every element equal to `value` from the lists of `ca`.
+fn remove_value(ca: &ListChunked, value: &AnyValue) -> PolarsResult<ListChunked> {
+    let ca = ca.rechunk();
+    let Some(arr) = ca.downcast_iter().next() else {
+        return Ok(ca);
+    };
+    let inner = ca.get_inner();
+    let sentinel = Series::from_any_values_and_dtype("", std::slice::from_ref(value), inner.dtype(), true)
+        .map_err(|err| {
+            polars_err!(
+                SchemaMismatch: "sentinel {} does not match the inner dtype {} of the list 'set' operation: {}",
+                value, inner.dtype(), err
+            )
+        })?;
+    let keep = inner.not_equal_missing(&sentinel)?;
+
+    // Number of kept values before every position, to translate the offsets.
+    let mut kept_before = Vec::with_capacity(inner.len() + 1);
+    kept_before.push(0i64);
+    let mut n_kept = 0;
+    for keep in keep.into_no_null_iter() {
+        n_kept += keep as i64;
+        kept_before.push(n_kept);
+    }
+    let offsets = arr
+        .offsets()
+        .iter()
+        .map(|&o| kept_before[o as usize])
+        .collect::<Vec<_>>();
+
+    let values = inner.filter(&keep)?.rechunk().chunks()[0].clone();
+    // SAFETY: the translated offsets are monotonically increasing and in bounds of `values`.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+    let out = ListArray::new(
+        arr.data_type().clone(),
+        offsets,
+        values,
+        arr.validity().cloned(),
+    );
+    // SAFETY: the values are a subset of those of `ca`, so the dtype is unchanged.
+    Ok(unsafe { ca.with_chunks(vec![out.boxed()]) })
+}
+
+/// Apply `set_op` with `options` after removing every element equal to `ignore_value` from
+/// both operands.
+///
+/// This is meant for encodings that use a sentinel such as `-1` or `""` for missing elements,
+/// nulls are not affected. The sentinel must be of (or losslessly castable to) the inner dtype.
+pub fn list_set_operation_ignore_value(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+    options: SetOpOptions,
+    ignore_value: Option<&AnyValue>,
+) -> PolarsResult<ListChunked> {
+    match ignore_value {
+        Some(value) => list_set_operation(
+            &remove_value(a, value)?,
+            &remove_value(b, value)?,
+            set_op,
+            options,
+        ),
+        None => list_set_operation(a, b, set_op, options),
+    }
+}
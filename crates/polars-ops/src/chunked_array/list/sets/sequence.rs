@@ -0,0 +1,112 @@
+use arrow::array::ListArray;
+use arrow::compute::concatenate::concatenate;
+use polars_core::prelude::*;
+
+use super::pairwise::{row_spans, with_set_values, ListIdxBuilder, PairKernel, SetValues};
+use super::{combine_validities, SetOperation};
+
+/// The most cells the diff table of a single row may hold, i.e. 512 MiB of `u32` lengths.
+/// A pair of rows with `(n + 1) * (m + 1)` above this is rejected rather than allocated.
+pub(super) const MAX_SEQUENCE_TABLE_CELLS: usize = 1 << 27;
+
+/// Diffs the rows of `a` and `b` as sequences along their longest common subsequence.
+struct SequenceOperation<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    set_op: SetOperation,
+}
+
+impl PairKernel for SequenceOperation<'_> {
+    type Output = ListIdxBuilder;
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let (keep_common, keep_deleted, keep_inserted) = match self.set_op {
+            SetOperation::Intersection => (true, false, false),
+            SetOperation::Union => (true, true, true),
+            SetOperation::Difference => (false, true, false),
+            SetOperation::SymmetricDifference => (false, true, true),
+            op => unreachable!("boolean operation {op} has no list output"),
+        };
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = ListIdxBuilder::new(n_rows);
+        // The length of the longest common subsequence of every pair of suffixes of a row.
+        let mut lcs: Vec<u32> = vec![];
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            let key_a = |i: usize| unsafe { a.key_unchecked(span_a.start + i) };
+            let key_b = |j: usize| unsafe { b.key_unchecked(span_b.start + j) };
+            let (n, m) = (span_a.len(), span_b.len());
+            let width = m + 1;
+            let cells = (n + 1)
+                .checked_mul(width)
+                .filter(|&cells| cells <= MAX_SEQUENCE_TABLE_CELLS);
+            let Some(cells) = cells else {
+                polars_bail!(
+                    ComputeError: "list 'set' operation in sequence mode would diff rows of {} and {} elements, \
+                    which exceeds the limit of {} table cells per row",
+                    n, m, MAX_SEQUENCE_TABLE_CELLS
+                );
+            };
+            lcs.clear();
+            lcs.resize(cells, 0);
+            for i in (0..n).rev() {
+                for j in (0..m).rev() {
+                    lcs[i * width + j] = if key_a(i) == key_b(j) {
+                        lcs[(i + 1) * width + j + 1] + 1
+                    } else {
+                        std::cmp::max(lcs[(i + 1) * width + j], lcs[i * width + j + 1])
+                    };
+                }
+            }
+
+            // Walk the diff from the front, deleting from `a` before inserting from `b`.
+            let (mut i, mut j) = (0, 0);
+            while i < n || j < m {
+                if i < n && j < m && key_a(i) == key_b(j) {
+                    if keep_common {
+                        out.push(span_a.start + i);
+                    }
+                    i += 1;
+                    j += 1;
+                } else if j == m || (i < n && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+                    if keep_deleted {
+                        out.push(span_a.start + i);
+                    }
+                    i += 1;
+                } else {
+                    if keep_inserted {
+                        out.push(a.len() + span_b.start + j);
+                    }
+                    j += 1;
+                }
+            }
+            out.finish_row();
+        }
+        Ok(out)
+    }
+}
+
+/// Apply `set_op` between the rows of `a` and `b` as ordered sequences rather than sets, see
+/// [`SetOpOptions::sequence_mode`](super::SetOpOptions::sequence_mode).
+pub(super) fn sequence_set_operation(
+    a: &ListArray<i64>,
+    b: &ListArray<i64>,
+    set_op: SetOperation,
+) -> PolarsResult<ListArray<i64>> {
+    let (values_a, values_b) = (a.values().as_ref(), b.values().as_ref());
+    let dtype = DataType::from(values_a.data_type());
+    polars_ensure!(
+        dtype.is_numeric() || matches!(dtype, DataType::String | DataType::Binary),
+        InvalidOperation: "list 'set' operations in sequence mode are not supported for inner dtype {}", dtype
+    );
+    let kernel = SequenceOperation {
+        offsets_a: a.offsets().as_slice(),
+        offsets_b: b.offsets().as_slice(),
+        set_op,
+    };
+    let builder = with_set_values(values_a, values_b, kernel)?;
+    let values = concatenate(&[values_a, values_b])?;
+    // SAFETY: `values` are the values of `a` followed by the values of `b`.
+    Ok(unsafe { builder.finish(values.as_ref(), combine_validities(a, b)) })
+}
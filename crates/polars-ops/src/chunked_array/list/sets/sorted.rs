@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+
+use arrow::array::{
+    Array, BinaryViewArray, ListArray, MutableArray, MutablePrimitiveArray, PrimitiveArray,
+    StaticArray, Utf8ViewArray,
+};
+use arrow::compute::concatenate::concatenate;
+use arrow::offset::OffsetsBuffer;
+use arrow::types::NativeType;
+use polars_core::prelude::*;
+#[cfg(feature = "simd")]
+use polars_core::with_match_physical_integer_type;
+use polars_core::with_match_physical_numeric_type;
+use polars_utils::total_ord::{TotalOrd, TotalOrdWrap};
+
+use super::pairwise::{row_spans, ListIdxBuilder};
+use super::{
+    combine_validities, downcast_values, ensure_list_output, new_offsets, prepare_operands,
+    SetOperation,
+};
+
+/// Gives access to the elements of the inner values of a list array as totally ordered keys.
+trait SortedValues {
+    type Key<'a>: Ord + Copy
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize;
+
+    /// # Safety
+    /// `idx` must be in bounds.
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_>;
+}
+
+impl<T: NativeType + TotalOrd> SortedValues for PrimitiveArray<T> {
+    type Key<'a> = Option<TotalOrdWrap<T>>;
+
+    fn len(&self) -> usize {
+        Array::len(self)
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.get_unchecked(idx).map(TotalOrdWrap)
+    }
+}
+
+impl SortedValues for BinaryViewArray {
+    type Key<'a> = Option<&'a [u8]>;
+
+    fn len(&self) -> usize {
+        Array::len(self)
+    }
+
+    #[inline]
+    unsafe fn key_unchecked(&self, idx: usize) -> Self::Key<'_> {
+        self.get_unchecked(idx)
+    }
+}
+
+/// Collects the elements selected by [`merge`].
+trait MergeOutput<K> {
+    /// Push the element `key` at index `idx` of the values of `a` followed by those of `b`.
+    fn push(&mut self, key: K, idx: usize);
+
+    fn finish_row(&mut self);
+}
+
+impl<K> MergeOutput<K> for ListIdxBuilder {
+    #[inline]
+    fn push(&mut self, _key: K, idx: usize) {
+        ListIdxBuilder::push(self, idx)
+    }
+
+    #[inline]
+    fn finish_row(&mut self) {
+        ListIdxBuilder::finish_row(self)
+    }
+}
+
+/// Writes numeric elements directly rather than gathering them afterwards.
+struct PrimitiveListBuilder<T: NativeType> {
+    values: MutablePrimitiveArray<T>,
+    offsets: Vec<i64>,
+}
+
+impl<T: NativeType> MergeOutput<Option<TotalOrdWrap<T>>> for PrimitiveListBuilder<T> {
+    #[inline]
+    fn push(&mut self, key: Option<TotalOrdWrap<T>>, _idx: usize) {
+        self.values.push(key.map(|v| v.0))
+    }
+
+    #[inline]
+    fn finish_row(&mut self) {
+        self.offsets.push(self.values.len() as i64)
+    }
+}
+
+/// Merge the sorted rows of `a` and `b` with two pointers, pushing the elements to keep to
+/// `out`.
+fn merge<'v, V: SortedValues, O: MergeOutput<V::Key<'v>>>(
+    a: &'v V,
+    b: &'v V,
+    offsets_a: &[i64],
+    offsets_b: &[i64],
+    set_op: SetOperation,
+    out: &mut O,
+) {
+    let (keep_a_only, keep_both, keep_b_only) = match set_op {
+        SetOperation::Intersection => (false, true, false),
+        SetOperation::Union => (true, true, true),
+        SetOperation::Difference => (true, false, false),
+        SetOperation::SymmetricDifference => (true, false, true),
+        op => unreachable!("boolean operation {op} has no list output"),
+    };
+    // Elements of `b` are addressed after those of `a`.
+    let len_a = a.len();
+
+    // SAFETY: the offsets of a list array are in bounds of its values.
+    let key_a = |i| unsafe { a.key_unchecked(i) };
+    let key_b = |i| unsafe { b.key_unchecked(i) };
+    for (span_a, span_b) in row_spans(offsets_a, offsets_b) {
+        debug_assert!(
+            span_a.clone().skip(1).all(|i| key_a(i - 1) < key_a(i))
+                && span_b.clone().skip(1).all(|i| key_b(i - 1) < key_b(i)),
+            "input of `list_set_operation_sorted` is not sorted and duplicate-free"
+        );
+        let (mut i, mut j) = (span_a.start, span_b.start);
+        while i < span_a.end && j < span_b.end {
+            let (ka, kb) = (key_a(i), key_b(j));
+            match ka.cmp(&kb) {
+                Ordering::Less => {
+                    if keep_a_only {
+                        out.push(ka, i);
+                    }
+                    i += 1;
+                },
+                Ordering::Greater => {
+                    if keep_b_only {
+                        out.push(kb, len_a + j);
+                    }
+                    j += 1;
+                },
+                Ordering::Equal => {
+                    if keep_both {
+                        out.push(ka, i);
+                    }
+                    i += 1;
+                    j += 1;
+                },
+            }
+        }
+        if keep_a_only {
+            (i..span_a.end).for_each(|i| out.push(key_a(i), i));
+        }
+        if keep_b_only {
+            (j..span_b.end).for_each(|j| out.push(key_b(j), len_a + j));
+        }
+        out.finish_row();
+    }
+}
+
+/// [`merge`] the rows of two arrays of which the values are binary.
+fn merge_binary(
+    arr_a: &ListArray<i64>,
+    arr_b: &ListArray<i64>,
+    a: &BinaryViewArray,
+    b: &BinaryViewArray,
+    set_op: SetOperation,
+) -> PolarsResult<ListArray<i64>> {
+    let (offsets_a, offsets_b) = (arr_a.offsets().as_slice(), arr_b.offsets().as_slice());
+    let mut builder = ListIdxBuilder::new(std::cmp::max(arr_a.len(), arr_b.len()));
+    merge(a, b, offsets_a, offsets_b, set_op, &mut builder);
+    let values = concatenate(&[arr_a.values().as_ref(), arr_b.values().as_ref()])?;
+    // SAFETY: `values` are the values of `a` followed by the values of `b`.
+    Ok(unsafe { builder.finish(values.as_ref(), combine_validities(arr_a, arr_b)) })
+}
+
+/// [`merge`] the rows of two arrays of which the values are numeric.
+fn merge_primitive<T: NativeType + TotalOrd>(
+    arr_a: &ListArray<i64>,
+    arr_b: &ListArray<i64>,
+    set_op: SetOperation,
+) -> PolarsResult<ListArray<i64>> {
+    let a = downcast_values::<PrimitiveArray<T>>(arr_a.values().as_ref())?;
+    let b = downcast_values::<PrimitiveArray<T>>(arr_b.values().as_ref())?;
+    let (offsets_a, offsets_b) = (arr_a.offsets().as_slice(), arr_b.offsets().as_slice());
+    let mut builder = PrimitiveListBuilder {
+        values: MutablePrimitiveArray::with_capacity(std::cmp::max(a.len(), b.len())),
+        offsets: new_offsets(offsets_a, offsets_b),
+    };
+    merge(a, b, offsets_a, offsets_b, set_op, &mut builder);
+
+    let values: PrimitiveArray<T> = builder.values.into();
+    let values = values.to(a.data_type().clone());
+    // SAFETY: the offsets are monotonically increasing and in bounds of the values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(builder.offsets.into()) };
+    let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+    let validity = combine_validities(arr_a, arr_b);
+    Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+}
+
+/// Intersects sorted and duplicate-free slices of integers with SIMD.
+#[cfg(feature = "simd")]
+trait SimdIntersect: NativeType + TotalOrd + Ord {
+    /// Push the elements that are in both `a` and `b` to `out`, in ascending order. Slices
+    /// shorter than a block are merged with scalar code.
+    fn intersect(a: &[Self], b: &[Self], out: &mut MutablePrimitiveArray<Self>);
+}
+
+#[cfg(feature = "simd")]
+macro_rules! impl_simd_intersect {
+    ($($T:ty),*) => {$(
+        impl SimdIntersect for $T {
+            fn intersect(a: &[$T], b: &[$T], out: &mut MutablePrimitiveArray<$T>) {
+                use std::simd::prelude::*;
+                const LANES: usize = 8;
+
+                let (mut i, mut j) = (0, 0);
+                // The lanes of the current block of `a` that are found in a block of `b`.
+                let mut found = Mask::<<$T as std::simd::SimdElement>::Mask, LANES>::splat(false);
+                let mut emit = |found: Mask<_, LANES>, block: &[$T]| {
+                    let bits = found.to_bitmask();
+                    for (lane, &v) in block.iter().enumerate() {
+                        if bits >> lane & 1 == 1 {
+                            out.push_value(v);
+                        }
+                    }
+                };
+                // Compare a block of `a` with every element of a block of `b` and advance past
+                // the block with the smaller maximum, both if the maxima are equal.
+                while i + LANES <= a.len() && j + LANES <= b.len() {
+                    let block_a = Simd::<$T, LANES>::from_slice(&a[i..i + LANES]);
+                    for &v in &b[j..j + LANES] {
+                        found |= block_a.simd_eq(Simd::splat(v));
+                    }
+                    let (max_a, max_b) = (a[i + LANES - 1], b[j + LANES - 1]);
+                    if max_a <= max_b {
+                        emit(found, &a[i..i + LANES]);
+                        found = Mask::splat(false);
+                        i += LANES;
+                    }
+                    if max_b <= max_a {
+                        j += LANES;
+                    }
+                }
+                // Elements found in blocks of `b` that were passed are smaller than `b[j]`, so
+                // the scalar tail neither finds them again nor emits smaller elements.
+                if found.any() {
+                    emit(found, &a[i..i + LANES]);
+                }
+                while i < a.len() && j < b.len() {
+                    match a[i].cmp(&b[j]) {
+                        Ordering::Less => i += 1,
+                        Ordering::Greater => j += 1,
+                        Ordering::Equal => {
+                            out.push_value(a[i]);
+                            i += 1;
+                            j += 1;
+                        },
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "simd")]
+impl_simd_intersect!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+/// The intersection of the rows of two arrays of which the values are integers, where rows
+/// without nulls are intersected with SIMD and the others are [`merge`]d.
+#[cfg(feature = "simd")]
+fn intersect_primitive_simd<T: SimdIntersect>(
+    arr_a: &ListArray<i64>,
+    arr_b: &ListArray<i64>,
+) -> PolarsResult<ListArray<i64>> {
+    let a = downcast_values::<PrimitiveArray<T>>(arr_a.values().as_ref())?;
+    let b = downcast_values::<PrimitiveArray<T>>(arr_b.values().as_ref())?;
+    let (offsets_a, offsets_b) = (arr_a.offsets().as_slice(), arr_b.offsets().as_slice());
+    let mut builder = PrimitiveListBuilder {
+        values: MutablePrimitiveArray::with_capacity(std::cmp::min(a.len(), b.len())),
+        offsets: new_offsets(offsets_a, offsets_b),
+    };
+    let has_nulls = |arr: &PrimitiveArray<T>, span: &std::ops::Range<usize>| matches!(arr.validity(), Some(v) if v.null_count_range(span.start, span.len()) > 0);
+    for (span_a, span_b) in row_spans(offsets_a, offsets_b) {
+        if !has_nulls(a, &span_a) && !has_nulls(b, &span_b) {
+            let (a, b) = (&a.values()[span_a], &b.values()[span_b]);
+            debug_assert!(
+                a.windows(2).all(|w| w[0] < w[1]) && b.windows(2).all(|w| w[0] < w[1]),
+                "input of `list_set_operation_sorted` is not sorted and duplicate-free"
+            );
+            T::intersect(a, b, &mut builder.values);
+            builder.finish_row();
+        } else {
+            // Merge the row on its own, a single row is broadcast to itself.
+            let row_a = [span_a.start as i64, span_a.end as i64];
+            let row_b = [span_b.start as i64, span_b.end as i64];
+            merge(
+                a,
+                b,
+                &row_a,
+                &row_b,
+                SetOperation::Intersection,
+                &mut builder,
+            );
+        }
+    }
+
+    let values: PrimitiveArray<T> = builder.values.into();
+    let values = values.to(a.data_type().clone());
+    // SAFETY: the offsets are monotonically increasing and in bounds of the values.
+    let offsets = unsafe { OffsetsBuffer::new_unchecked(builder.offsets.into()) };
+    let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+    let validity = combine_validities(arr_a, arr_b);
+    Ok(ListArray::new(dtype, offsets, values.boxed(), validity))
+}
+
+/// Apply `set_op` between the lists of `a` and `b` by merging them rather than hashing, which
+/// is faster and gives sorted output. Either operand may be of length 1, in which case it is
+/// broadcast.
+///
+/// Every list must be sorted in ascending order, nulls first, and free of duplicates, as
+/// e.g. after a sort and `unique`. Floats are ordered by their total order. Lists that don't
+/// meet this give wrong results, which is checked in debug builds only. Only numeric,
+/// `String` and `Binary` inner dtypes are supported.
+///
+/// With the `simd` feature the intersection of long lists of integers compares blocks of
+/// elements at once, which is several times faster than merging them one by one.
+pub fn list_set_operation_sorted(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+) -> PolarsResult<ListChunked> {
+    ensure_list_output(set_op)?;
+    let (a, b) = prepare_operands(a, b)?;
+
+    // SAFETY: the output values are gathered from `a` and `b`, so the dtype is unchanged.
+    unsafe {
+        arity::try_binary_unchecked_same_type(
+            &a,
+            &b,
+            |arr_a, arr_b| {
+                let (values_a, values_b) = (arr_a.values(), arr_b.values());
+                polars_ensure!(
+                    values_a.data_type() == values_b.data_type(),
+                    ComputeError: "list 'set' operation got mismatching inner types {:?} and {:?}",
+                    values_a.data_type(), values_b.data_type()
+                );
+                let out = match values_a.data_type() {
+                    ArrowDataType::Utf8View => {
+                        // Bytes of utf8 sort in the same order as their strings.
+                        let a = downcast_values::<Utf8ViewArray>(values_a.as_ref())?.to_binview();
+                        let b = downcast_values::<Utf8ViewArray>(values_b.as_ref())?.to_binview();
+                        merge_binary(arr_a, arr_b, &a, &b, set_op)
+                    },
+                    ArrowDataType::BinaryView => {
+                        let a = downcast_values::<BinaryViewArray>(values_a.as_ref())?;
+                        let b = downcast_values::<BinaryViewArray>(values_b.as_ref())?;
+                        merge_binary(arr_a, arr_b, a, b, set_op)
+                    },
+                    #[cfg(feature = "simd")]
+                    dtype
+                        if set_op == SetOperation::Intersection
+                            && DataType::from(dtype).is_integer() =>
+                    {
+                        let physical: DataType = dtype.into();
+                        with_match_physical_integer_type!(physical, |$T| {
+                            intersect_primitive_simd::<$T>(arr_a, arr_b)
+                        })
+                    },
+                    dtype if DataType::from(dtype).is_numeric() => {
+                        let physical: DataType = dtype.into();
+                        with_match_physical_numeric_type!(physical, |$T| {
+                            merge_primitive::<$T>(arr_a, arr_b, set_op)
+                        })
+                    },
+                    dtype => polars_bail!(
+                        InvalidOperation: "sorted list 'set' operations are not supported for inner dtype {}",
+                        DataType::from(dtype)
+                    ),
+                };
+                out.map(|arr| arr.boxed())
+            },
+            false,
+            false,
+        )
+    }
+}
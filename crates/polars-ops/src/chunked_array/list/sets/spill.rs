@@ -0,0 +1,105 @@
+use std::hash::{BuildHasher, Hash};
+
+use arrow::array::MutablePlBinary;
+use indexmap::IndexSet;
+
+use super::SetOperation;
+
+/// Rows of binary lists whose hash sets would hold more distinct elements than this are
+/// finished by [`sort_merge_row`] rather than with hash sets, which avoids the overhead of
+/// large hash tables on pathologically wide rows: the keys are held in flat vectors, which are
+/// more compact than hash sets and don't rehash as they grow. The memory is still linear in the
+/// number of elements of the row.
+pub(super) const SPILL_THRESHOLD: usize = 1 << 16;
+
+/// Extend `set` with `keys` until it holds more than [`SPILL_THRESHOLD`] elements.
+///
+/// Returns whether it did, in which case the keys not inserted yet are left in `keys`.
+pub(super) fn extend_or_spill<K: Hash + Eq, S: BuildHasher>(
+    set: &mut IndexSet<K, S>,
+    keys: &mut impl Iterator<Item = K>,
+) -> bool {
+    for key in keys {
+        set.insert(key);
+        if set.len() > SPILL_THRESHOLD {
+            return true;
+        }
+    }
+    false
+}
+
+/// Sort `keys`, tagged with their position, and keep the first occurrence of every key.
+fn sorted_first_occurrences<'a>(
+    keys: impl Iterator<Item = Option<&'a [u8]>>,
+) -> Vec<(Option<&'a [u8]>, usize)> {
+    let mut keys = keys.enumerate().map(|(i, k)| (k, i)).collect::<Vec<_>>();
+    // A stable sort keeps the positions of equal keys in ascending order.
+    keys.sort_by(|l, r| l.0.cmp(&r.0));
+    keys.dedup_by(|later, first| later.0 == first.0);
+    keys
+}
+
+fn contains(sorted: &[(Option<&[u8]>, usize)], key: Option<&[u8]>) -> bool {
+    sorted.binary_search_by(|probe| probe.0.cmp(&key)).is_ok()
+}
+
+/// Apply `set_op` on a single row by sorting rather than hashing its elements, and push the
+/// output to `out`. The output is the same as the one of the hash set path, in the same order.
+/// Only the first occurrence of an element of `a` or `b` counts for that order, so the
+/// elements hashed before spilling can be passed first, followed by the rest of the row.
+///
+/// Returns the new length of `out`.
+pub(super) fn sort_merge_row<'a>(
+    a: impl Iterator<Item = Option<&'a [u8]>>,
+    b: impl Iterator<Item = Option<&'a [u8]>>,
+    set_op: SetOperation,
+    preserve_left_duplicates: bool,
+    out: &mut MutablePlBinary,
+) -> usize {
+    let a = a.collect::<Vec<_>>();
+    let sorted_a = sorted_first_occurrences(a.iter().copied());
+    let sorted_b = sorted_first_occurrences(b);
+
+    if preserve_left_duplicates {
+        let keep_contained = set_op == SetOperation::Intersection;
+        for &v in &a {
+            if contains(&sorted_b, v) == keep_contained {
+                out.push(v);
+            }
+        }
+        return out.len();
+    }
+
+    let (keep_a_only, keep_both, keep_b_only) = match set_op {
+        SetOperation::Intersection => (false, true, false),
+        SetOperation::Union => (true, true, true),
+        SetOperation::Difference => (true, false, false),
+        SetOperation::SymmetricDifference => (true, false, true),
+        op => unreachable!("boolean operation {op} has no list output"),
+    };
+    // The elements of `a` come first, in order of first occurrence, then those only in `b`.
+    let mut selected = sorted_a
+        .iter()
+        .filter(|(k, _)| {
+            if contains(&sorted_b, *k) {
+                keep_both
+            } else {
+                keep_a_only
+            }
+        })
+        .map(|&(k, i)| (i, k))
+        .collect::<Vec<_>>();
+    if keep_b_only {
+        selected.extend(
+            sorted_b
+                .iter()
+                .filter(|(k, _)| !contains(&sorted_a, *k))
+                .map(|&(k, i)| (a.len() + i, k)),
+        );
+    }
+    selected.sort_unstable_by_key(|(i, _)| *i);
+    for (_, v) in selected {
+        out.push(v);
+    }
+    out.len()
+}
@@ -0,0 +1,169 @@
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+
+use super::pairwise::{row_spans, with_set_values, PairKernel, SetValues};
+use super::{combine_validities, ensure_list_output, prepare_operands, SetOperation};
+
+#[cfg(feature = "dtype-struct")]
+const FIELDS: [&str; 6] = [
+    "len_a",
+    "len_b",
+    "distinct_a",
+    "distinct_b",
+    "intersection",
+    "union",
+];
+
+struct SetStats<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+}
+
+impl PairKernel for SetStats<'_> {
+    /// One column per field of [`FIELDS`].
+    type Output = [Vec<IdxSize>; 6];
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out: [Vec<IdxSize>; 6] = Default::default();
+        out.iter_mut().for_each(|v| v.reserve(n_rows));
+        let mut set_a = PlHashSet::new();
+        let mut set_b = PlHashSet::new();
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            set_a.clear();
+            set_b.clear();
+            let (len_a, len_b) = (span_a.len(), span_b.len());
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            set_a.extend(span_a.map(|i| unsafe { a.key_unchecked(i) }));
+            let mut intersection = 0;
+            for i in span_b {
+                let key = unsafe { b.key_unchecked(i) };
+                // Every distinct element of `b` is counted once.
+                if set_b.insert(key) && set_a.contains(&key) {
+                    intersection += 1;
+                }
+            }
+            let union = set_a.len() + set_b.len() - intersection;
+            let row = [len_a, len_b, set_a.len(), set_b.len(), intersection, union];
+            for (column, value) in out.iter_mut().zip(row) {
+                column.push(value as IdxSize);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Run [`SetStats`] over the aligned chunks of `a` and `b`, returning the chunks of every field
+/// of [`FIELDS`]. Null rows in either operand are null in every field.
+fn set_stats_chunks(a: &ListChunked, b: &ListChunked) -> PolarsResult<[Vec<IdxArr>; 6]> {
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let mut columns: [Vec<IdxArr>; 6] = Default::default();
+    for (arr_a, arr_b) in a.downcast_iter().zip(b.downcast_iter()) {
+        let kernel = SetStats {
+            offsets_a: arr_a.offsets().as_slice(),
+            offsets_b: arr_b.offsets().as_slice(),
+        };
+        let out = with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+        let validity = combine_validities(arr_a, arr_b);
+        for (chunks, values) in columns.iter_mut().zip(out) {
+            chunks.push(IdxArr::from_vec(values).with_validity(validity.clone()));
+        }
+    }
+    Ok(columns)
+}
+
+/// Combine the counts of two fields of [`FIELDS`] per row with `f`, keeping the validity of
+/// `lhs`, which is the same for every field.
+fn zip_counts(
+    lhs: &[IdxArr],
+    rhs: &[IdxArr],
+    f: impl Fn(IdxSize, IdxSize) -> IdxSize,
+) -> Vec<IdxArr> {
+    lhs.iter()
+        .zip(rhs)
+        .map(|(lhs, rhs)| {
+            let values = lhs.values().iter().zip(rhs.values().iter());
+            IdxArr::from_vec(values.map(|(&l, &r)| f(l, r)).collect())
+                .with_validity(lhs.validity().cloned())
+        })
+        .collect()
+}
+
+/// Compute the statistics of the sets of every pair of rows of `a` and `b` in a single pass.
+///
+/// Returns a `Struct{len_a, len_b, distinct_a, distinct_b, intersection, union}` column: the
+/// number of elements of either list, the number of distinct elements of either list and the
+/// sizes of their intersection and union. Null elements are counted like any other value.
+/// Null rows in either operand have all fields null, which makes the struct row null.
+#[cfg(feature = "dtype-struct")]
+pub fn list_set_stats(a: &ListChunked, b: &ListChunked) -> PolarsResult<StructChunked> {
+    let columns = set_stats_chunks(a, b)?;
+    let fields = FIELDS
+        .iter()
+        .zip(columns)
+        .map(|(name, chunks)| IdxCa::from_chunk_iter(name, chunks).into_series())
+        .collect::<Vec<_>>();
+    StructChunked::new(a.name(), &fields)
+}
+
+/// Count the elements of every region of the Venn diagram of the sets of every pair of rows of
+/// `a` and `b` in a single pass, without materializing any list.
+///
+/// Returns a [`DataFrame`] of the flat `IdxSize` columns `a`, `b`, `intersection`, `a_only` and
+/// `b_only`: the number of distinct elements of either list, of their intersection and of
+/// either difference. Null elements are counted like any other value and null rows in either
+/// operand are null in every column.
+pub fn list_set_region_counts(a: &ListChunked, b: &ListChunked) -> PolarsResult<DataFrame> {
+    let [_, _, distinct_a, distinct_b, intersection, _] = set_stats_chunks(a, b)?;
+    // The intersection is part of either set, so the difference can't underflow.
+    let only = |distinct: &[IdxArr]| zip_counts(distinct, &intersection, |d, i| d - i);
+    let (a_only, b_only) = (only(&distinct_a), only(&distinct_b));
+    let columns = [
+        ("a", distinct_a),
+        ("b", distinct_b),
+        ("intersection", intersection),
+        ("a_only", a_only),
+        ("b_only", b_only),
+    ];
+    DataFrame::new(
+        columns
+            .into_iter()
+            .map(|(name, chunks)| IdxCa::from_chunk_iter(name, chunks).into_series())
+            .collect(),
+    )
+}
+
+/// Count per pair of rows of `a` and `b` how many distinct elements of `a` `set_op` removes
+/// and how many it adds, without materializing the result, e.g. to summarize the changes of
+/// a column of sets.
+///
+/// Returns the `IdxSize` columns `removed`, the elements of `a` that are not in the result,
+/// and `added`, the elements of the result that are not in `a`. [`SetOperation::Union`] never
+/// removes and [`SetOperation::Intersection`] and [`SetOperation::Difference`] never add
+/// elements. Null elements are counted like any other value and null rows in either operand
+/// are null in both columns.
+pub fn list_set_change_counts(
+    a: &ListChunked,
+    b: &ListChunked,
+    set_op: SetOperation,
+) -> PolarsResult<(IdxCa, IdxCa)> {
+    ensure_list_output(set_op)?;
+    let [_, _, distinct_a, distinct_b, intersection, _] = set_stats_chunks(a, b)?;
+    // The intersection is part of either set, so the differences can't underflow.
+    let only = |distinct: &[IdxArr]| zip_counts(distinct, &intersection, |d, i| d - i);
+    let none = || zip_counts(&intersection, &intersection, |_, _| 0);
+    let (removed, added) = match set_op {
+        SetOperation::Intersection => (only(&distinct_a), none()),
+        SetOperation::Union => (none(), only(&distinct_b)),
+        SetOperation::Difference => (intersection.clone(), none()),
+        SetOperation::SymmetricDifference => (intersection.clone(), only(&distinct_b)),
+        op => unreachable!("boolean operation {op} has no list output"),
+    };
+    Ok((
+        IdxCa::from_chunk_iter("removed", removed),
+        IdxCa::from_chunk_iter("added", added),
+    ))
+}
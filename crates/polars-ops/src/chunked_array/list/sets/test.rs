@@ -0,0 +1,4339 @@
+use std::borrow::Cow;
+use std::hash::Hash;
+
+use proptest::prelude::*;
+
+use super::*;
+use crate::chunked_array::list::ListNameSpaceImpl;
+
+fn str_list(name: &str, rows: &[&[&str]]) -> ListChunked {
+    let rows = rows
+        .iter()
+        .map(|row| Series::new("", *row))
+        .collect::<Vec<_>>();
+    Series::new(name, rows).list().unwrap().clone()
+}
+
+fn str_row(ca: &ListChunked, idx: usize) -> Vec<Option<String>> {
+    let s = ca.get_as_series(idx).unwrap();
+    s.str()
+        .unwrap()
+        .into_iter()
+        .map(|v| v.map(|v| v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_set_operation_trim() -> PolarsResult<()> {
+    let a = str_list("a", &[&[" a", "a "]]);
+    let b = str_list("b", &[&["b"]]);
+
+    let options = SetOpOptions {
+        trim: true,
+        ..Default::default()
+    };
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        str_row(&out, 0),
+        &[Some("a".to_string()), Some("b".to_string())]
+    );
+
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.get_as_series(0).unwrap().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_error_names_dtype() {
+    let arr_a = ListArray::<i64>::new(
+        ListArray::<i64>::default_datatype(ArrowDataType::Int64),
+        OffsetsBuffer::default(),
+        PrimitiveArray::<i64>::from_vec(vec![]).boxed(),
+        None,
+    );
+    let arr_b = ListArray::<i64>::new(
+        ListArray::<i64>::default_datatype(ArrowDataType::Int32),
+        OffsetsBuffer::default(),
+        PrimitiveArray::<i32>::from_vec(vec![]).boxed(),
+        None,
+    );
+    let err = array_set_operation(
+        &arr_a,
+        &arr_b,
+        SetOperation::Union,
+        &SetOpOptions::default(),
+        false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Int64"), "{err}");
+    assert!(err.to_string().contains("Int32"), "{err}");
+}
+
+#[test]
+fn test_set_operation_length_mismatch() {
+    let a = str_list("a", &[&["x"], &["y"], &["z"]]);
+    let b = str_list("b", &[&["x"], &["y"]]);
+    let err = list_set_operation(&a, &b, SetOperation::Union, Default::default()).unwrap_err();
+    assert!(err.to_string().contains("lhs=3 rhs=2"), "{err}");
+}
+
+#[test]
+fn test_set_operation_dictionary_values() -> PolarsResult<()> {
+    use arrow::array::Utf8Array;
+
+    let list = |values: Box<dyn Array>, offsets: Vec<i64>| {
+        ListArray::<i64>::new(
+            ListArray::<i64>::default_datatype(values.data_type().clone()),
+            offsets.try_into().unwrap(),
+            values,
+            None,
+        )
+    };
+    let dict = DictionaryArray::try_from_keys(
+        PrimitiveArray::from([Some(0u32), Some(1), Some(0), None, Some(2)]),
+        Utf8Array::<i64>::from_slice(["x", "y", "z"]).boxed(),
+    )?;
+    let a = list(dict.boxed(), vec![0, 3, 5]);
+    let plain_a = list(
+        Utf8ViewArray::from_slice([Some("x"), Some("y"), Some("x"), None, Some("z")]).boxed(),
+        vec![0, 3, 5],
+    );
+    let b = list(
+        Utf8ViewArray::from_slice_values(["y", "q", "z"]).boxed(),
+        vec![0, 2, 3],
+    );
+
+    let options = SetOpOptions::default();
+    for set_op in [SetOperation::Union, SetOperation::Difference] {
+        let out = array_set_operation(&a, &b, set_op, &options, false)?;
+        let expected = array_set_operation(&plain_a, &b, set_op, &options, false)?;
+        assert_eq!(out, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_offset_binary_values() -> PolarsResult<()> {
+    use arrow::array::{BinaryArray, Utf8Array};
+
+    let list = |values: Box<dyn Array>, offsets: Vec<i64>| {
+        ListArray::<i64>::new(
+            ListArray::<i64>::default_datatype(values.data_type().clone()),
+            offsets.try_into().unwrap(),
+            values,
+            None,
+        )
+    };
+    let a_values = [
+        Some(b"x".as_slice()),
+        Some(b"y"),
+        Some(b"x"),
+        None,
+        Some(b"z"),
+    ];
+    let b_values = [b"y".as_slice(), b"q", b"z"];
+    let plain_a = list(BinaryViewArray::from_slice(a_values).boxed(), vec![0, 3, 5]);
+    let plain_b = list(
+        BinaryViewArray::from_slice_values(b_values).boxed(),
+        vec![0, 2, 3],
+    );
+    // Binary values with i32 offsets, as imported over FFI, on either side.
+    let a = list(BinaryArray::<i32>::from(a_values).boxed(), vec![0, 3, 5]);
+    let b = list(
+        BinaryArray::<i64>::from_slice(b_values).boxed(),
+        vec![0, 2, 3],
+    );
+
+    // Binary values with i32 offsets on both sides skip the promotion.
+    let small_b = list(
+        BinaryArray::<i32>::from_slice(b_values).boxed(),
+        vec![0, 2, 3],
+    );
+
+    for options in [
+        SetOpOptions::default(),
+        SetOpOptions::default().with_preserve_left_duplicates(true),
+    ] {
+        for set_op in [
+            SetOperation::Union,
+            SetOperation::Difference,
+            SetOperation::Intersection,
+            SetOperation::SymmetricDifference,
+        ] {
+            let expected = array_set_operation(&plain_a, &plain_b, set_op, &options, false)?;
+            assert_eq!(
+                array_set_operation(&a, &b, set_op, &options, false)?,
+                expected
+            );
+            assert_eq!(
+                array_set_operation(&a, &plain_b, set_op, &options, false)?,
+                expected
+            );
+            assert_eq!(
+                array_set_operation(&a, &small_b, set_op, &options, false)?,
+                expected
+            );
+        }
+    }
+    let options = SetOpOptions::default();
+
+    // Utf8 values are brought to `Utf8View`.
+    let a = list(Utf8Array::<i32>::from_slice(["x", "y"]).boxed(), vec![0, 2]);
+    let b = list(Utf8ViewArray::from_slice_values(["y"]).boxed(), vec![0, 1]);
+    let out = array_set_operation(&a, &b, SetOperation::Union, &options, false)?;
+    assert_eq!(out.values().data_type(), &ArrowDataType::Utf8View);
+    assert_eq!(out.values().len(), 2);
+    Ok(())
+}
+
+#[cfg(feature = "dtype-categorical")]
+#[test]
+fn test_set_operation_dictionary_encode() -> PolarsResult<()> {
+    let rows = (0..1000)
+        .map(|i| {
+            if i % 2 == 0 {
+                &["some-long-element", "x"][..]
+            } else {
+                &["some-long-element"][..]
+            }
+        })
+        .collect::<Vec<_>>();
+    let a = str_list("a", &rows);
+    let b = str_list("b", &[&["another-long-element"]]);
+
+    let plain = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let options = SetOpOptions {
+        dictionary_encode: true,
+        ..Default::default()
+    };
+    let encoded = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert!(matches!(encoded.inner_dtype(), DataType::Categorical(_, _)));
+    assert!(
+        encoded.clone().into_series().estimated_size()
+            < plain.clone().into_series().estimated_size()
+    );
+
+    let decoded = encoded.cast(&DataType::List(Box::new(DataType::String)))?;
+    assert!(decoded.equals_missing(&plain.into_series()));
+
+    let ints = int_list("a", &[Some(&[1])]);
+    assert!(list_set_operation(&ints, &ints, SetOperation::Union, options).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_float_nan() -> PolarsResult<()> {
+    let list = |name, values: &[f64]| {
+        let s = Series::new(name, [Series::new("", values)]);
+        s.list().unwrap().clone()
+    };
+    let row = |ca: &ListChunked| -> Vec<f64> {
+        let s = ca.get_as_series(0).unwrap();
+        s.f64().unwrap().into_no_null_iter().collect()
+    };
+    let a = list("a", &[1.0, f64::NAN, f64::NAN, 2.0]);
+    let b = list("b", &[f64::NAN, 3.0]);
+
+    // Floats are compared by their total order, so all NaNs are equal.
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    let out = row(&out);
+    assert!(out.len() == 1 && out[0].is_nan());
+
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let out = row(&out);
+    assert_eq!(out.len(), 4);
+    assert!(out[1].is_nan());
+    assert_eq!([out[0], out[2], out[3]], [1.0, 2.0, 3.0]);
+
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    assert_eq!(row(&out), [1.0, 2.0]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_float_infinity() -> PolarsResult<()> {
+    let a = Series::new(
+        "a",
+        [Series::new(
+            "",
+            [f64::INFINITY, f64::NEG_INFINITY, f64::NAN],
+        )],
+    );
+    let b = Series::new(
+        "b",
+        [Series::new("", [f64::NEG_INFINITY, 1.0, f64::INFINITY])],
+    );
+    let c = Series::new("c", [Series::new("", [f64::NAN, -f64::NAN, 2.0])]);
+    for dtype in [DataType::Float64, DataType::Float32] {
+        let list = |s: &Series| -> PolarsResult<ListChunked> {
+            Ok(s.cast(&DataType::List(Box::new(dtype.clone())))?
+                .list()?
+                .clone())
+        };
+        let (a, b, c) = (list(&a)?, list(&b)?, list(&c)?);
+        let row = |set_op, a, b| -> PolarsResult<Vec<String>> {
+            let out = list_set_operation(a, b, set_op, Default::default())?
+                .get_as_series(0)
+                .unwrap();
+            let out = out.cast(&DataType::Float64)?;
+            Ok(out
+                .f64()?
+                .into_no_null_iter()
+                .map(|v| v.to_string())
+                .collect())
+        };
+
+        // Infinities are ordinary values, equal to themselves and distinct from each other and
+        // from NaN.
+        assert_eq!(
+            row(SetOperation::Union, &a, &b)?,
+            ["inf", "-inf", "NaN", "1"]
+        );
+        assert_eq!(row(SetOperation::Intersection, &a, &b)?, ["inf", "-inf"]);
+        assert_eq!(row(SetOperation::Difference, &a, &b)?, ["NaN"]);
+        assert_eq!(
+            row(SetOperation::SymmetricDifference, &a, &b)?,
+            ["NaN", "1"]
+        );
+        // All NaNs are equal, whatever their sign or payload.
+        assert_eq!(row(SetOperation::Intersection, &a, &c)?, ["NaN"]);
+        assert_eq!(row(SetOperation::Difference, &c, &a)?, ["2"]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_approx_eps() -> PolarsResult<()> {
+    let list = |row: &[Option<f64>]| Series::new("", [Series::new("", row)]);
+    let a = list(&[Some(1.0), Some(1.0000001), None, Some(2.0)]);
+    let b = list(&[Some(1.0004), Some(3.0), None]);
+    let row = |out: &ListChunked| -> Vec<Option<f64>> {
+        let s = out
+            .get_as_series(0)
+            .unwrap()
+            .cast(&DataType::Float64)
+            .unwrap();
+        Vec::from(s.f64().unwrap())
+    };
+
+    let out = list_set_operation(
+        a.list()?,
+        b.list()?,
+        SetOperation::Union,
+        Default::default(),
+    )?;
+    assert_eq!(
+        row(&out),
+        &[
+            Some(1.0),
+            Some(1.0000001),
+            None,
+            Some(2.0),
+            Some(1.0004),
+            Some(3.0)
+        ]
+    );
+    // Elements in the same bin are equal and keep the value of their first occurrence.
+    let options = SetOpOptions::default().with_approx_eps(Some(1e-3));
+    for dtype in [DataType::Float64, DataType::Float32] {
+        let dtype = DataType::List(Box::new(dtype));
+        let (a, b) = (a.cast(&dtype)?, b.cast(&dtype)?);
+        let (a, b) = (a.list()?, b.list()?);
+        let out = list_set_operation(a, b, SetOperation::Union, options)?;
+        assert_eq!(out.dtype(), &dtype);
+        assert_eq!(row(&out), &[Some(1.0), None, Some(2.0), Some(3.0)]);
+        let out = list_set_operation(a, b, SetOperation::Intersection, options)?;
+        assert_eq!(row(&out), &[Some(1.0), None]);
+        let out = list_set_operation(a, b, SetOperation::Difference, options)?;
+        assert_eq!(row(&out), &[Some(2.0)]);
+    }
+
+    // Other dtypes are compared exactly.
+    let a = int_list("a", &[Some(&[1, 2])]);
+    let b = int_list("b", &[Some(&[2, 3])]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1), Some(2), Some(3)]));
+
+    let a = list(&[Some(1.0)]);
+    for eps in [0.0, -1.0, f64::NAN] {
+        let options = SetOpOptions::default().with_approx_eps(Some(eps));
+        let out = list_set_operation(a.list()?, a.list()?, SetOperation::Union, options);
+        assert!(out.unwrap_err().to_string().contains("approx_eps"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_rolling() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[
+            Some(&[1, 2]),
+            Some(&[2, 3]),
+            None,
+            Some(&[3, 4]),
+            Some(&[4]),
+        ],
+    );
+
+    let out = list_set_rolling(&a, 2, SetOperation::Union)?;
+    let rows = (0..out.len()).map(|i| int_row(&out, i)).collect::<Vec<_>>();
+    let expected = [&[1, 2][..], &[1, 2, 3], &[2, 3], &[3, 4], &[3, 4]];
+    for (row, expected) in rows.iter().zip(expected) {
+        let expected = expected.iter().map(|&v| Some(v)).collect::<Vec<_>>();
+        assert_eq!(row.as_ref(), Some(&expected));
+    }
+
+    let out = list_set_rolling(&a, 2, SetOperation::Intersection)?;
+    let rows = (0..out.len()).map(|i| int_row(&out, i)).collect::<Vec<_>>();
+    let expected = [&[1, 2][..], &[2], &[2, 3], &[3, 4], &[4]];
+    for (row, expected) in rows.iter().zip(expected) {
+        let expected = expected.iter().map(|&v| Some(v)).collect::<Vec<_>>();
+        assert_eq!(row.as_ref(), Some(&expected));
+    }
+
+    let out = list_set_rolling(&a, 1, SetOperation::Union)?;
+    assert_eq!(int_row(&out, 2), None);
+
+    // The order follows the rows in the window, not the rows that have left it.
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[2, 1, 2]), Some(&[3, 1])]);
+    let out = list_set_rolling(&a, 2, SetOperation::Union)?;
+    assert_eq!(int_row(&out, 2), Some(vec![Some(2), Some(1), Some(3)]));
+    let out = list_set_rolling(&a, 2, SetOperation::Intersection)?;
+    assert_eq!(int_row(&out, 1), Some(vec![Some(1), Some(2)]));
+    assert_eq!(int_row(&out, 2), Some(vec![Some(1)]));
+
+    assert!(list_set_rolling(&a, 2, SetOperation::Difference).is_err());
+    let err = list_set_rolling(&a, 2, SetOperation::IsSubset).unwrap_err();
+    assert!(err.to_string().contains("boolean"));
+    assert!(list_set_rolling(&a, 0, SetOperation::Union).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sort_numeric() -> PolarsResult<()> {
+    let options = SetOpOptions {
+        sort_numeric: true,
+        ..Default::default()
+    };
+    let a = int_list("a", &[Some(&[3, 1]), Some(&[5])]);
+    let b = int_list("b", &[Some(&[2, 1, 0]), Some(&[4])]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(0), Some(1), Some(2), Some(3)])
+    );
+    assert_eq!(int_row(&out, 1), Some(vec![Some(4), Some(5)]));
+
+    let a = str_list("a", &[&["b", "a"]]);
+    let b = str_list("b", &[&["c"]]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        str_row(&out, 0),
+        &[
+            Some("b".to_string()),
+            Some("a".to_string()),
+            Some("c".to_string())
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_ignore_value() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, -1, 2]), Some(&[-1]), None]);
+    let b = int_list("b", &[Some(&[-1, 3]), Some(&[4]), Some(&[-1])]);
+    let sentinel = AnyValue::Int32(-1);
+    let out = list_set_operation_ignore_value(
+        &a,
+        &b,
+        SetOperation::Union,
+        Default::default(),
+        Some(&sentinel),
+    )?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1), Some(2), Some(3)]));
+    assert_eq!(int_row(&out, 1), Some(vec![Some(4)]));
+    assert_eq!(int_row(&out, 2), None);
+
+    // The options apply to the operands without the sentinel.
+    let options = SetOpOptions::default().with_swap_operands(true);
+    let out = list_set_operation_ignore_value(
+        &a,
+        &b,
+        SetOperation::Difference,
+        options,
+        Some(&sentinel),
+    )?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(3)]));
+    assert_eq!(int_row(&out, 1), Some(vec![Some(4)]));
+
+    let a = str_list("a", &[&["", "x"], &["y"]]);
+    let b = str_list("b", &[&["z", ""], &[""]]);
+    let out = list_set_operation_ignore_value(
+        &a,
+        &b,
+        SetOperation::Union,
+        Default::default(),
+        Some(&AnyValue::String("")),
+    )?;
+    assert_eq!(
+        str_row(&out, 0),
+        &[Some("x".to_string()), Some("z".to_string())]
+    );
+    assert_eq!(str_row(&out, 1), &[Some("y".to_string())]);
+
+    let err = list_set_operation_ignore_value(
+        &a,
+        &b,
+        SetOperation::Union,
+        Default::default(),
+        Some(&sentinel),
+    );
+    assert!(err.is_err());
+
+    // The error of the cast is kept, e.g. for a sentinel that overflows the inner dtype.
+    let a = Series::new("a", [Series::new("", [1u32, 2])]);
+    let a = a.list()?;
+    let cast_err =
+        Series::from_any_values_and_dtype("", &[sentinel.clone()], &DataType::UInt32, true)
+            .unwrap_err();
+    let err = list_set_operation_ignore_value(
+        a,
+        a,
+        SetOperation::Union,
+        Default::default(),
+        Some(&sentinel),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains(&cast_err.to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_broadcast_lhs_symmetric_difference() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 3])]);
+    let b = int_list(
+        "b",
+        &[Some(&[3, 4]), Some(&[]), Some(&[1, 2, 3]), Some(&[5])],
+    );
+    let out = list_set_operation(
+        &a,
+        &b,
+        SetOperation::SymmetricDifference,
+        Default::default(),
+    )?;
+    let expected = int_list(
+        "a",
+        &[
+            Some(&[1, 2, 4]),
+            Some(&[1, 2, 3]),
+            Some(&[]),
+            Some(&[1, 2, 3, 5]),
+        ],
+    );
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let a = str_list("a", &[&["x", "y"]]);
+    let b = str_list("b", &[&["y"], &["z"]]);
+    let out = list_set_operation(
+        &a,
+        &b,
+        SetOperation::SymmetricDifference,
+        Default::default(),
+    )?;
+    assert_eq!(str_row(&out, 0), &[Some("x".to_string())]);
+    assert_eq!(
+        str_row(&out, 1),
+        &[
+            Some("x".to_string()),
+            Some("y".to_string()),
+            Some("z".to_string())
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_properties() -> PolarsResult<()> {
+    use SetOperation::*;
+    for (set_op, commutative, empty_result_is_valid) in [
+        (Intersection, true, true),
+        (Union, true, true),
+        (Difference, false, true),
+        (SymmetricDifference, true, true),
+        (IsDisjoint, true, false),
+        (IsSubset, false, false),
+        (IsSuperset, false, false),
+        (IsEqual, true, false),
+    ] {
+        assert_eq!(set_op.commutative(), commutative, "{set_op}");
+        assert_eq!(
+            set_op.empty_result_is_valid(),
+            empty_result_is_valid,
+            "{set_op}"
+        );
+    }
+
+    // The properties match the operations themselves.
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[]), Some(&[3])]);
+    let b = int_list("b", &[Some(&[2, 3]), Some(&[]), Some(&[3, 4])]);
+    for set_op in [Intersection, Union, Difference, SymmetricDifference] {
+        let ab = list_set_operation(
+            &a,
+            &b,
+            set_op,
+            SetOpOptions::default().with_sort_numeric(true),
+        )?;
+        let ba = list_set_operation(
+            &b,
+            &a,
+            set_op,
+            SetOpOptions::default().with_sort_numeric(true),
+        )?;
+        let swapped_equal = (0..a.len()).all(|i| int_row(&ab, i) == int_row(&ba, i));
+        assert_eq!(swapped_equal, set_op.commutative(), "{set_op}");
+        assert!(ab.get_as_series(1).is_some());
+    }
+    for set_op in [IsDisjoint, IsSubset, IsSuperset, IsEqual] {
+        let ab = boolean_list_set_operation(&a, &b, set_op, true, Default::default())?;
+        let ba = boolean_list_set_operation(&b, &a, set_op, true, Default::default())?;
+        assert_eq!(
+            ab.get(1).is_some(),
+            set_op.empty_result_is_valid(),
+            "{set_op}"
+        );
+        let swapped_equal = ab.into_series().equals_missing(&ba.into_series());
+        assert_eq!(swapped_equal, set_op.commutative(), "{set_op}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_boolean_set_operation() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[Some(&[1, 2]), Some(&[1, 1]), Some(&[]), Some(&[]), None],
+    );
+    let b = int_list(
+        "b",
+        &[Some(&[3]), Some(&[2, 1]), Some(&[1]), Some(&[]), Some(&[1])],
+    );
+    let check = |set_op, tri_state, expected: &[Option<bool>]| -> PolarsResult<()> {
+        let out = boolean_list_set_operation(&a, &b, set_op, tri_state, Default::default())?;
+        assert_eq!(out.into_iter().collect::<Vec<_>>(), expected, "{set_op}");
+        Ok(())
+    };
+
+    let (t, f) = (Some(true), Some(false));
+    check(SetOperation::IsDisjoint, false, &[t, f, t, t, None])?;
+    check(SetOperation::IsSubset, false, &[f, t, t, t, None])?;
+    check(SetOperation::IsSuperset, false, &[f, f, f, t, None])?;
+    check(SetOperation::IsEqual, false, &[f, f, f, t, None])?;
+
+    // Results that only hold because of an empty operand are undefined.
+    check(SetOperation::IsDisjoint, true, &[t, f, None, None, None])?;
+    check(SetOperation::IsSubset, true, &[f, t, None, None, None])?;
+    check(SetOperation::IsSuperset, true, &[f, f, f, None, None])?;
+    check(SetOperation::IsEqual, true, &[f, f, f, None, None])?;
+
+    assert!(
+        boolean_list_set_operation(&a, &b, SetOperation::Union, false, Default::default()).is_err()
+    );
+    assert!(list_set_operation(&a, &b, SetOperation::IsSubset, Default::default()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_boolean_set_operation_null_members() -> PolarsResult<()> {
+    let list = |rows: &[&[Option<i32>]]| {
+        let rows = rows
+            .iter()
+            .map(|row| Series::new("", *row))
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    let a = list(&[
+        &[None],
+        &[None],
+        &[None, Some(1)],
+        &[Some(1)],
+        &[Some(1), None],
+        &[Some(1)],
+    ]);
+    let b = list(&[
+        &[Some(1), None],
+        &[Some(1), Some(2)],
+        &[None],
+        &[None],
+        &[Some(3)],
+        &[Some(1), None],
+    ]);
+    let is = |set_op, null_logic| -> PolarsResult<Vec<Option<bool>>> {
+        let out = boolean_list_set_operation(&a, &b, set_op, false, null_logic)?;
+        Ok(Vec::from(&out))
+    };
+
+    let (t, f) = (Some(true), Some(false));
+    // Nulls are members that equal each other.
+    let null_logic = NullLogic::SetMember;
+    assert_eq!(is(SetOperation::IsSubset, null_logic)?, &[t, f, f, f, f, t]);
+    assert_eq!(
+        is(SetOperation::IsSuperset, null_logic)?,
+        &[f, f, t, f, f, f]
+    );
+    assert_eq!(
+        is(SetOperation::IsDisjoint, null_logic)?,
+        &[f, t, f, t, t, f]
+    );
+    assert_eq!(is(SetOperation::IsEqual, null_logic)?, &[f, f, f, f, f, f]);
+
+    // Nulls are unknown values, only results that hold whatever they are remain.
+    let null_logic = NullLogic::ThreeValued;
+    let u = None;
+    assert_eq!(is(SetOperation::IsSubset, null_logic)?, &[u, u, u, u, f, t]);
+    assert_eq!(
+        is(SetOperation::IsSuperset, null_logic)?,
+        &[u, u, u, u, u, u]
+    );
+    assert_eq!(
+        is(SetOperation::IsDisjoint, null_logic)?,
+        &[u, u, u, u, u, f]
+    );
+    assert_eq!(is(SetOperation::IsEqual, null_logic)?, &[u, u, u, u, f, u]);
+
+    // Without null elements both logics agree.
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[]), None]);
+    let b = int_list("b", &[Some(&[2, 1, 2]), Some(&[3]), Some(&[1])]);
+    for set_op in [SetOperation::IsSubset, SetOperation::IsDisjoint] {
+        let member = boolean_list_set_operation(&a, &b, set_op, true, NullLogic::SetMember)?;
+        let three_valued =
+            boolean_list_set_operation(&a, &b, set_op, true, NullLogic::ThreeValued)?;
+        assert_eq!(Vec::from(&member), Vec::from(&three_valued));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_boolean_set_operation_is_equal() -> PolarsResult<()> {
+    // Lists of different lengths can still be equal sets because of duplicates, and the shorter
+    // list may be either operand.
+    let a = int_list(
+        "a",
+        &[
+            Some(&[1, 1, 2]),
+            Some(&[2, 1]),
+            Some(&[1, 2]),
+            Some(&[3, 1, 1]),
+        ],
+    );
+    let b = int_list(
+        "b",
+        &[
+            Some(&[2, 1]),
+            Some(&[1, 2, 2, 1]),
+            Some(&[1, 2, 3]),
+            Some(&[1, 1, 2]),
+        ],
+    );
+    let out = boolean_list_set_operation(&a, &b, SetOperation::IsEqual, false, Default::default())?;
+    assert_eq!(
+        Vec::from(&out),
+        &[Some(true), Some(true), Some(false), Some(false)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_output_kinds() -> PolarsResult<()> {
+    // Every operation builds only the output of its kind, for the string and the numeric
+    // kernels alike.
+    let strings = (
+        str_list("a", &[&["x", "y"], &["z"]]),
+        str_list("b", &[&["y"], &["z"]]),
+    );
+    let floats = |ca: &ListChunked| -> PolarsResult<ListChunked> {
+        Ok(ca
+            .cast(&DataType::List(Box::new(DataType::Float64)))?
+            .list()?
+            .clone())
+    };
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[3])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[3])]);
+    for (a, b) in [strings, (floats(&a)?, floats(&b)?)] {
+        for set_op in [
+            SetOperation::Intersection,
+            SetOperation::Union,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            let out = list_set_operation(&a, &b, set_op, Default::default())?;
+            assert_eq!(out.dtype(), a.dtype());
+            let lengths = out.lst_lengths().into_iter().collect::<Vec<_>>();
+            let expected = match set_op {
+                SetOperation::Intersection => [1, 1],
+                SetOperation::Union => [2, 1],
+                _ => [1, 0],
+            };
+            assert_eq!(lengths, expected.map(Some), "{set_op}");
+            assert!(boolean_list_set_operation(&a, &b, set_op, false, Default::default()).is_err());
+        }
+        for (set_op, expected) in [
+            (SetOperation::IsDisjoint, [false, false]),
+            (SetOperation::IsSubset, [false, true]),
+            (SetOperation::IsSuperset, [true, true]),
+            (SetOperation::IsEqual, [false, true]),
+        ] {
+            let out = boolean_list_set_operation(&a, &b, set_op, false, Default::default())?;
+            assert_eq!(Vec::from(&out), expected.map(Some), "{set_op}");
+            assert!(list_set_operation(&a, &b, set_op, Default::default()).is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_view_arrays() -> PolarsResult<()> {
+    // Strings of more than 12 bytes are stored in the data buffers rather than in the views.
+    let long = "a string that is not inlined";
+    let a = str_list("a", &[&["x", long, "y"], &[long]]);
+    let b = str_list(
+        "b",
+        &[&[long, "x"], &["a string that is not inlined either"]],
+    );
+    let binary = |ca: &ListChunked| -> PolarsResult<ListChunked> {
+        Ok(ca
+            .cast(&DataType::List(Box::new(DataType::Binary)))?
+            .list()?
+            .clone())
+    };
+    for (a, b, view_type) in [
+        (a.clone(), b.clone(), ArrowDataType::Utf8View),
+        (binary(&a)?, binary(&b)?, ArrowDataType::BinaryView),
+    ] {
+        let values_type = |ca: &ListChunked| {
+            ca.downcast_iter()
+                .next()
+                .unwrap()
+                .values()
+                .data_type()
+                .clone()
+        };
+        assert_eq!(values_type(&a), view_type);
+        let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+        assert_eq!(values_type(&out), view_type);
+        assert_eq!(
+            out.lst_lengths().into_iter().collect::<Vec<_>>(),
+            [Some(2), Some(0)]
+        );
+        let out = out.cast(&DataType::List(Box::new(DataType::String)))?;
+        assert_eq!(
+            str_row(out.list()?, 0),
+            &[Some("x".to_string()), Some(long.to_string())]
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-decimal")]
+fn test_set_operation_i128() -> PolarsResult<()> {
+    // 128-bit integers are stored as decimals of scale 0.
+    let big = i128::MAX - 1;
+    let list = |name: &str, rows: &[&[i128]]| -> PolarsResult<ListChunked> {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                Ok(Int128Chunked::from_slice("", row)
+                    .into_decimal(None, 0)?
+                    .into_series())
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        Ok(Series::new(name, rows).list()?.clone())
+    };
+    let a = list("a", &[&[big, 1, -big, big], &[i128::MIN, 0]])?;
+    let b = list("b", &[&[-big, i128::MAX], &[0]])?;
+    let row = |ca: &ListChunked, i: usize| -> Vec<Option<i128>> {
+        let s = ca.get_as_series(i).unwrap();
+        s.decimal().unwrap().0.into_iter().collect()
+    };
+
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.inner_dtype(), &DataType::Decimal(None, Some(0)));
+    assert_eq!(
+        row(&out, 0),
+        &[Some(big), Some(1), Some(-big), Some(i128::MAX)]
+    );
+    assert_eq!(row(&out, 1), &[Some(i128::MIN), Some(0)]);
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    assert_eq!(row(&out, 0), &[Some(big), Some(1)]);
+    assert_eq!(row(&out, 1), &[Some(i128::MIN)]);
+
+    // They take the numeric kernel, which can sort.
+    let options = SetOpOptions::default().with_sort_numeric(true);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        row(&out, 0),
+        &[Some(-big), Some(1), Some(big), Some(i128::MAX)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_child_field_names() -> PolarsResult<()> {
+    let list = |field_name: &str, is_nullable: bool, values: Vec<i64>| {
+        let field = ArrowField::new(field_name, ArrowDataType::Int64, is_nullable);
+        let len = values.len() as i64;
+        ListArray::<i64>::new(
+            ArrowDataType::LargeList(Box::new(field)),
+            vec![0, len].try_into().unwrap(),
+            PrimitiveArray::from_vec(values).boxed(),
+            None,
+        )
+    };
+    let a = list("item", true, vec![1, 2]);
+    let b = list("element", false, vec![2, 3]);
+
+    let out = array_set_operation(&a, &b, SetOperation::Union, &SetOpOptions::default(), false)?;
+    assert_eq!(out.values().len(), 3);
+
+    let a = unsafe { ListChunked::from_chunks("a", vec![a.boxed()]) };
+    let b = unsafe { ListChunked::from_chunks("b", vec![b.boxed()]) };
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    assert_eq!(out.get_as_series(0).unwrap().i64()?.get(0), Some(2));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_agg() -> PolarsResult<()> {
+    let df = DataFrame::new(vec![
+        Series::new("g", &[1i32, 2, 1, 2, 3, 1]),
+        int_list(
+            "a",
+            &[
+                Some(&[1, 2]),
+                Some(&[5]),
+                Some(&[2, 3]),
+                None,
+                None,
+                Some(&[2, 4]),
+            ],
+        )
+        .into_series(),
+    ])?;
+    let gb = df.group_by_stable(["g"])?;
+    let a = df.column("a")?.list()?;
+
+    let out = list_set_operation_agg(a, gb.get_groups(), SetOperation::Union, None, false)?;
+    let expected = int_list("a", &[Some(&[1, 2, 3, 4]), Some(&[5]), None]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let out = list_set_operation_agg(a, gb.get_groups(), SetOperation::Intersection, None, false)?;
+    let expected = int_list("a", &[Some(&[2]), Some(&[5]), None]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let out = list_set_operation_agg(a, gb.get_groups(), SetOperation::Difference, None, false)?;
+    let expected = int_list("a", &[Some(&[1]), Some(&[5]), None]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // The union of group 1 has 4 distinct elements.
+    let out = list_set_operation_agg(a, gb.get_groups(), SetOperation::Union, Some(4), false)?;
+    let expected = int_list("a", &[Some(&[1, 2, 3, 4]), Some(&[5]), None]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    let err = list_set_operation_agg(a, gb.get_groups(), SetOperation::Union, Some(3), false);
+    assert!(err.unwrap_err().to_string().contains("max_distinct"));
+    // The limit applies to the elements seen, not just to those in the output.
+    let err = list_set_operation_agg(
+        a,
+        gb.get_groups(),
+        SetOperation::Intersection,
+        Some(3),
+        false,
+    );
+    assert!(err.is_err());
+
+    // The union of a whole column with null elements, as a single group.
+    let a = Series::new(
+        "a",
+        [
+            Some(Series::new("", [Some(3), None, Some(1)])),
+            None,
+            Some(Series::new("", [Some(2), None, Some(3)])),
+        ],
+    );
+    let groups = GroupsProxy::Slice {
+        groups: vec![[0, a.len() as IdxSize]],
+        rolling: false,
+    };
+    for (nulls_last, expected) in [
+        (false, [None, Some(3), Some(1), Some(2)]),
+        (true, [Some(3), Some(1), Some(2), None]),
+    ] {
+        let out =
+            list_set_operation_agg(a.list()?, &groups, SetOperation::Union, None, nulls_last)?;
+        assert_eq!(
+            int_row(&out, 0),
+            Some(expected.to_vec()),
+            "nulls_last: {nulls_last}"
+        );
+    }
+    // The null element only takes part if it is in the output.
+    let out = list_set_operation_agg(a.list()?, &groups, SetOperation::Difference, None, false)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1)]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_swap_operands() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 3]), Some(&[4]), None]);
+    let b = int_list("b", &[Some(&[3, 5])]);
+    let options = SetOpOptions {
+        swap_operands: true,
+        ..Default::default()
+    };
+    for set_op in [SetOperation::Difference, SetOperation::Union] {
+        let out = list_set_operation(&a, &b, set_op, options)?;
+        let expected = list_set_operation(&b, &a, set_op, Default::default())?;
+        assert_eq!(out.name(), "a");
+        assert!(out.into_series().equals_missing(&expected.into_series()));
+    }
+    let out = list_set_operation(&a, &b, SetOperation::Difference, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(5)]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_small_domain() -> PolarsResult<()> {
+    // Rows with a small domain take the bitset path, the others and all float rows the hash
+    // set path. Both should give the same elements in the same order.
+    let build = |name, rows: &[Option<Vec<Option<i32>>>]| {
+        build_list(name, &rows.to_vec(), DataType::Int32, |row| {
+            Series::new("", row)
+        })
+    };
+    let a = build(
+        "a",
+        &[
+            Some(vec![Some(3), None, Some(1), Some(3), Some(2)]),
+            Some(vec![Some(1), Some(1_000_000), Some(-5)]),
+            Some(vec![None, Some(7)]),
+            Some(vec![]),
+        ],
+    );
+    let b = build(
+        "b",
+        &[
+            Some(vec![Some(2), Some(4), Some(4), None]),
+            Some(vec![Some(-5), Some(8)]),
+            Some(vec![Some(6), Some(7), Some(5)]),
+            Some(vec![Some(1)]),
+        ],
+    );
+    let as_float = |ca: &ListChunked| -> PolarsResult<ListChunked> {
+        Ok(ca
+            .cast(&DataType::List(Box::new(DataType::Float64)))?
+            .list()?
+            .clone())
+    };
+    let b_row = b.slice(2, 1);
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        for (a, b) in [(&a, &b), (&a, &b_row), (&b_row, &a)] {
+            let out = as_float(&list_set_operation(a, b, set_op, Default::default())?)?;
+            let expected =
+                list_set_operation(&as_float(a)?, &as_float(b)?, set_op, Default::default())?;
+            assert!(
+                out.clone()
+                    .into_series()
+                    .equals_missing(&expected.clone().into_series()),
+                "{set_op}: {out:?} != {expected:?}"
+            );
+
+            let hash_only = SetOpOptions::default().with_bitset_bits_per_element(0);
+            let out = list_set_operation(a, b, set_op, hash_only)?;
+            let expected = list_set_operation(a, b, set_op, Default::default())?;
+            assert!(out.into_series().equals_missing(&expected.into_series()));
+        }
+    }
+
+    // The threshold picks the path of a row: [0, 63] spans 64 bits over 2 elements.
+    let row = PrimitiveArray::from_slice([0i32, 63]);
+    let mut out = MutablePrimitiveArray::new();
+    let mut bitsets = Default::default();
+    let mut run = |bits| {
+        small_domain_set_operation(
+            &row,
+            0..1,
+            &row,
+            1..2,
+            SetOperation::Union,
+            &mut out,
+            &mut bitsets,
+            bits,
+        )
+    };
+    assert_eq!(run(32), Some(2));
+    assert_eq!(run(31), None);
+    assert_eq!(run(0), None);
+    Ok(())
+}
+
+#[test]
+fn test_list_reduce_pairwise() -> PolarsResult<()> {
+    // Jaccard similarity: the size of the intersection over the size of the union.
+    let jaccard = |a: &ListChunked, b: &ListChunked| -> PolarsResult<Float64Chunked> {
+        list_reduce_pairwise(
+            a,
+            b,
+            || (0, 0),
+            |(common, total), el| {
+                *common += (el.count_a > 0 && el.count_b > 0) as IdxSize;
+                *total += 1;
+            },
+            |(common, total)| Some(common as f64 / total as f64),
+        )
+    };
+    let a = str_list("a", &[&["a", "b", "b"], &["x"], &["a", "c", "d"]]);
+    let b = str_list("b", &[&["b", "c", "a", "d"]]);
+    let out = jaccard(&a, &b)?;
+    assert_eq!(out.name(), "a");
+    assert_eq!(Vec::from(&out), &[Some(0.5), Some(0.0), Some(0.75)]);
+
+    let a = int_list("a", &[Some(&[1, 2]), None]);
+    let b = int_list("b", &[Some(&[2]), Some(&[1])]);
+    assert_eq!(Vec::from(&jaccard(&a, &b)?), &[Some(0.5), None]);
+
+    // Sum of the elements only in `b`, which reads the values themselves.
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[])]);
+    let b = int_list("b", &[Some(&[2, 3, 4, 3]), Some(&[5])]);
+    let out: Int64Chunked = list_reduce_pairwise(
+        &a,
+        &b,
+        || 0,
+        |sum, el| {
+            if el.count_a == 0 {
+                *sum += el.value().extract::<i64>().unwrap();
+            }
+        },
+        Some,
+    )?;
+    assert_eq!(Vec::from(&out), &[Some(7), Some(5)]);
+
+    // Other inner dtypes go through the same dispatch as the set operations.
+    let a = Series::new(
+        "a",
+        [Series::new("", [true, true]), Series::new("", [false])],
+    );
+    let b = Series::new(
+        "b",
+        [
+            Series::new("", [false]),
+            Series::new("", [Some(false), None]),
+        ],
+    );
+    let out: IdxCa = list_reduce_pairwise(
+        a.list()?,
+        b.list()?,
+        || 0,
+        |n, el| *n += el.value().is_null() as IdxSize + 1,
+        Some,
+    )?;
+    assert_eq!(Vec::from(&out), &[Some(2), Some(3)]);
+    Ok(())
+}
+
+#[test]
+fn test_list_jaccard_distance() -> PolarsResult<()> {
+    let a = str_list(
+        "a",
+        &[&["a", "b", "b"], &["x"], &["a", "c", "d"], &[], &["y"]],
+    );
+    let b = str_list("b", &[&["b", "c", "a", "d"], &["x"], &["d"], &[], &[]]);
+    let similarity = list_jaccard_similarity(&a, &b, Default::default())?;
+    let distance = list_jaccard_distance(&a, &b, Default::default())?;
+    assert_eq!(distance.name(), "a");
+    assert_eq!(
+        Vec::from(&similarity),
+        &[Some(0.5), Some(1.0), Some(1.0 / 3.0), Some(1.0), Some(0.0)]
+    );
+    for (s, d) in similarity.into_iter().zip(&distance) {
+        assert_eq!(d, s.map(|s| 1.0 - s));
+    }
+    assert_eq!(distance.get(3), Some(0.0));
+
+    // Nulls are members, null rows propagate and an operand of length 1 is broadcast.
+    let a = Series::new("a", [Some(Series::new("", [Some(1), None])), None]);
+    let b = int_list("b", &[Some(&[1])]);
+    let distance = list_jaccard_distance(a.list()?, &b, Default::default())?;
+    assert_eq!(Vec::from(&distance), &[Some(0.5), None]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sliced_broadcast() -> PolarsResult<()> {
+    // The sliced operand keeps the values of all rows, but only its own row may be used.
+    let check = |a: &ListChunked, sliced: &ListChunked, single: &ListChunked| {
+        for set_op in [
+            SetOperation::Union,
+            SetOperation::Intersection,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            for swap in [false, true] {
+                let (a, sliced, single) = if swap {
+                    (sliced, a, single)
+                } else {
+                    (a, sliced, single)
+                };
+                let out = list_set_operation(a, sliced, set_op, Default::default()).unwrap();
+                let expected = if swap {
+                    list_set_operation(single, sliced, set_op, Default::default())
+                } else {
+                    list_set_operation(a, single, set_op, Default::default())
+                }
+                .unwrap();
+                assert!(
+                    out.into_series().equals_missing(&expected.into_series()),
+                    "{set_op}, swapped: {swap}"
+                );
+            }
+        }
+    };
+
+    let a = str_list("a", &[&["x", "y"], &["z"], &["w", "x"]]);
+    let b = str_list("b", &[&["w"], &["x", "z"], &["y"]]);
+    check(&a, &b.slice(1, 1), &str_list("b", &[&["x", "z"]]));
+
+    // Spread out elements take the hash path rather than bitsets.
+    let a = int_list("a", &[Some(&[1, 2000]), Some(&[3000]), Some(&[4000, 1])]);
+    let b = int_list("b", &[Some(&[4000]), Some(&[1, 3000]), Some(&[2000])]);
+    check(&a, &b.slice(1, 1), &int_list("b", &[Some(&[1, 3000])]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_equally_sliced() -> PolarsResult<()> {
+    // Operands of the same length are not rechunked, so their offsets don't start at zero.
+    let rows: [Option<&[i32]>; 6] = [
+        Some(&[1, 2]),
+        Some(&[3]),
+        Some(&[2000, 4, 4, 1]),
+        None,
+        Some(&[]),
+        Some(&[5, 3000, 5]),
+    ];
+    let a = int_list("a", &rows);
+    let b = int_list("b", &[rows[5], rows[4], rows[0], rows[1], rows[2], rows[3]]);
+    // Copies of the slices that own their values.
+    let owned = |ca: &ListChunked| {
+        let rows = (0..ca.len()).map(|i| int_row(ca, i)).collect::<Vec<_>>();
+        let rows = rows
+            .iter()
+            .map(|row| row.as_ref().map(|row| Series::new("", row)));
+        Series::new(ca.name(), rows.collect::<Vec<_>>())
+            .list()
+            .unwrap()
+            .clone()
+    };
+
+    for (a, b) in [
+        (a.slice(2, 3), b.slice(2, 3)),
+        (a.slice(1, 4), b.slice(2, 4)),
+    ] {
+        assert_ne!(a.downcast_iter().next().unwrap().offsets()[0], 0);
+        let (owned_a, owned_b) = (owned(&a), owned(&b));
+        for set_op in [
+            SetOperation::Union,
+            SetOperation::Intersection,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            for options in [
+                SetOpOptions::default(),
+                SetOpOptions::default().with_sort_numeric(true),
+                SetOpOptions::default().with_union_strategy(UnionStrategy::SortDedup),
+            ] {
+                let out = list_set_operation(&a, &b, set_op, options)?;
+                let expected = list_set_operation(&owned_a, &owned_b, set_op, options)?;
+                assert!(
+                    out.into_series().equals_missing(&expected.into_series()),
+                    "{set_op}, {options:?}"
+                );
+            }
+        }
+        for set_op in [SetOperation::IsSubset, SetOperation::IsEqual] {
+            let out = boolean_list_set_operation(&a, &b, set_op, false, Default::default())?;
+            let expected =
+                boolean_list_set_operation(&owned_a, &owned_b, set_op, false, Default::default())?;
+            assert!(out.into_series().equals_missing(&expected.into_series()));
+        }
+    }
+
+    let a = str_list("a", &[&["x"], &["y", "z", "y"], &["w"]]).slice(1, 2);
+    let b = str_list("b", &[&["w", "x"], &["z"], &["x", "w"]]).slice(1, 2);
+    let out = list_set_operation(
+        &a,
+        &b,
+        SetOperation::SymmetricDifference,
+        Default::default(),
+    )?;
+    assert_eq!(str_row(&out, 0), &[Some("y".to_string())]);
+    assert_eq!(str_row(&out, 1), &[Some("x".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_broadcast_rhs_union() -> PolarsResult<()> {
+    // A wide broadcast rhs with many duplicates, spread out so the hash set path is taken.
+    let wide = (0..1000).map(|i| (i % 7) * 1_000_000).collect::<Vec<_>>();
+    let a = int_list("a", &[Some(&[5, 0]), Some(&[]), None]);
+    let b = int_list("b", &[Some(&wide)]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let tail = (1..7).map(|i| Some(i * 1_000_000));
+    let expected = [Some(5), Some(0)].into_iter().chain(tail.clone()).collect();
+    assert_eq!(int_row(&out, 0), Some(expected));
+    let expected = std::iter::once(Some(0)).chain(tail).collect();
+    assert_eq!(int_row(&out, 1), Some(expected));
+    assert_eq!(int_row(&out, 2), None);
+
+    let a = str_list("a", &[&["b"], &["c", "a"]]);
+    let b = str_list("b", &[&["a", "b", "a", "b"]]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(
+        str_row(&out, 1),
+        &[
+            Some("c".to_string()),
+            Some("a".to_string()),
+            Some("b".to_string())
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_any_value_fallback() -> PolarsResult<()> {
+    // Booleans and nested lists have no dedicated path and are compared by their `AnyValue`.
+    let bools = |rows: &[&[Option<bool>]]| {
+        let rows = rows
+            .iter()
+            .map(|row| Series::new("", *row))
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    let a = bools(&[&[Some(true), None, Some(true)], &[Some(false)]]);
+    let b = bools(&[&[None, Some(false)], &[Some(false), Some(true)]]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let expected = bools(&[&[Some(true), None, Some(false)], &[Some(false), Some(true)]]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    let expected = bools(&[&[Some(true)], &[]]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let nested = |rows: &[&[&[i64]]]| {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let row = row.iter().map(|l| Series::new("", *l)).collect::<Vec<_>>();
+                Series::new("", row)
+            })
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    let a = nested(&[&[&[1, 2], &[3], &[1, 2]], &[&[]]]);
+    let b = nested(&[&[&[3], &[2, 1]], &[&[], &[4]]]);
+    let out = list_set_operation(
+        &a,
+        &b,
+        SetOperation::SymmetricDifference,
+        Default::default(),
+    )?;
+    let expected = nested(&[&[&[1, 2], &[2, 1]], &[&[4]]]);
+    assert_eq!(out.dtype(), expected.dtype());
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    let out =
+        boolean_list_set_operation(&a, &b, SetOperation::IsSubset, false, Default::default())?;
+    assert_eq!(Vec::from(&out), &[Some(false), Some(true)]);
+
+    #[cfg(feature = "dtype-struct")]
+    {
+        let structs = |rows: &[&[(i32, &str)]]| -> PolarsResult<ListChunked> {
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    let x = Series::new("x", row.iter().map(|v| v.0).collect::<Vec<_>>());
+                    let y = Series::new("y", row.iter().map(|v| v.1).collect::<Vec<_>>());
+                    StructChunked::new("", &[x, y]).map(|ca| ca.into_series())
+                })
+                .collect::<PolarsResult<Vec<_>>>()?;
+            Ok(Series::new("", rows).list()?.clone())
+        };
+        let a = structs(&[&[(1, "a"), (2, "b"), (1, "a")]])?;
+        let b = structs(&[&[(2, "b"), (1, "b")]])?;
+        let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+        let expected = structs(&[&[(2, "b")]])?;
+        assert!(out.into_series().equals_missing(&expected.into_series()));
+    }
+
+    #[cfg(feature = "dtype-duration")]
+    {
+        // Logical types of a numeric physical type keep using the fast path.
+        let duration = DataType::List(Box::new(DataType::Duration(TimeUnit::Milliseconds)));
+        let a = int_list("a", &[Some(&[1, 2, 3])]).cast(&duration)?;
+        let b = int_list("b", &[Some(&[3, 4])]).cast(&duration)?;
+        let out = list_set_operation(
+            a.list()?,
+            b.list()?,
+            SetOperation::Difference,
+            Default::default(),
+        )?;
+        assert_eq!(out.dtype(), &duration);
+        let expected = int_list("a", &[Some(&[1, 2])]).cast(&duration)?;
+        assert!(out.into_series().equals_missing(&expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_all_empty_keeps_inner_dtype() -> PolarsResult<()> {
+    for inner in [
+        DataType::Int32,
+        DataType::Float64,
+        DataType::String,
+        DataType::Binary,
+        DataType::Boolean,
+    ] {
+        let dtype = DataType::List(Box::new(inner.clone()));
+        let empty = |name: &str, n_rows: usize| -> PolarsResult<ListChunked> {
+            let rows = vec![Series::new_empty("", &inner); n_rows];
+            Ok(Series::new(name, rows).cast(&dtype)?.list()?.clone())
+        };
+        let a = empty("a", 3)?;
+        for b in [empty("b", 3)?, empty("b", 1)?] {
+            for set_op in [
+                SetOperation::Union,
+                SetOperation::Intersection,
+                SetOperation::Difference,
+                SetOperation::SymmetricDifference,
+            ] {
+                let out = list_set_operation(&a, &b, set_op, Default::default())?;
+                assert_eq!(out.dtype(), &dtype, "{set_op} on {inner}");
+                // The arrow type of the values must match as well, not just the logical one.
+                assert_eq!(out.chunks()[0].data_type(), a.chunks()[0].data_type());
+                assert_eq!(out.len(), 3);
+                assert!(out.into_iter().all(|row| row.unwrap().is_empty()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_union_sort_dedup() -> PolarsResult<()> {
+    let sort_dedup = SetOpOptions::default().with_union_strategy(UnionStrategy::SortDedup);
+    // The hash union with sorted output is the reference.
+    let sorted_hash = SetOpOptions::default().with_sort_numeric(true);
+    let check = |a: &ListChunked, b: &ListChunked| -> PolarsResult<()> {
+        let out = list_set_operation(a, b, SetOperation::Union, sort_dedup)?;
+        let expected = list_set_operation(a, b, SetOperation::Union, sorted_hash)?;
+        assert_eq!(out.dtype(), a.dtype());
+        assert!(out.into_series().equals_missing(&expected.into_series()));
+        Ok(())
+    };
+
+    let a = int_list(
+        "a",
+        &[Some(&[5, 1, 5, 3000]), Some(&[]), None, Some(&[-2, 7])],
+    );
+    let b = int_list(
+        "b",
+        &[Some(&[1, 2]), Some(&[4, 4]), Some(&[1]), Some(&[7, -9])],
+    );
+    check(&a, &b)?;
+    check(&b, &a)?;
+    check(&a, &int_list("b", &[Some(&[9, 1, 9])]))?;
+    let out = list_set_operation(&a, &b, SetOperation::Union, sort_dedup)?;
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(1), Some(2), Some(5), Some(3000)])
+    );
+
+    let list = |name: &str, rows: Vec<Vec<Option<f64>>>| {
+        let rows = rows
+            .into_iter()
+            .map(|row| Series::new("", row))
+            .collect::<Vec<_>>();
+        Series::new(name, rows).list().unwrap().clone()
+    };
+    let a = list(
+        "a",
+        vec![
+            vec![Some(f64::NAN), None, Some(1.5)],
+            vec![Some(f64::INFINITY), Some(2.0)],
+        ],
+    );
+    let b = list(
+        "b",
+        vec![
+            vec![Some(1.5), Some(f64::NAN), None],
+            vec![Some(-1.0), Some(2.0)],
+        ],
+    );
+    check(&a, &b)?;
+    let out = list_set_operation(&a, &b, SetOperation::Union, sort_dedup)?;
+    let row = out.get_as_series(0).unwrap();
+    let row = row.f64()?.into_iter().collect::<Vec<_>>();
+    assert_eq!(row[..2], [None, Some(1.5)]);
+    assert!(row[2].unwrap().is_nan() && row.len() == 3);
+
+    // Other operations and dtypes ignore the strategy.
+    let a = str_list("a", &[&["b", "a"]]);
+    let out = list_set_operation(&a, &a, SetOperation::Union, sort_dedup)?;
+    assert_eq!(str_row(&out, 0), &[Some("b".into()), Some("a".into())]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_extend() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2]), None, Some(&[3, 4]), Some(&[5])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[1]), Some(&[]), Some(&[5, 6])]);
+    let expected = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+
+    let mut out = Series::new_empty("out", a.dtype());
+    for (offset, len) in [(0, 3), (3, 1)] {
+        let (a, b) = (a.slice(offset, len), b.slice(offset, len));
+        list_set_operation_extend(&mut out, &a, &b, SetOperation::Union, Default::default())?;
+    }
+    assert_eq!(out.name(), "out");
+    assert!(out.equals_missing(&expected.into_series()));
+
+    let mut strings = Series::new_empty("out", &DataType::List(Box::new(DataType::String)));
+    let err = list_set_operation_extend(
+        &mut strings,
+        &a,
+        &b,
+        SetOperation::Union,
+        Default::default(),
+    );
+    assert!(matches!(err, Err(PolarsError::SchemaMismatch(_))));
+    assert!(strings.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sort_nulls_last() -> PolarsResult<()> {
+    let list = |name: &str, row: &[Option<i32>]| {
+        Series::new(name, [Series::new("", row)])
+            .list()
+            .unwrap()
+            .clone()
+    };
+    let a = list("a", &[Some(3), None, Some(1)]);
+    let b = list("b", &[Some(2), Some(3)]);
+    let options = SetOpOptions::default()
+        .with_sort_numeric(true)
+        .with_nulls_last(true);
+    for strategy in [UnionStrategy::Hash, UnionStrategy::SortDedup] {
+        let options = options.with_union_strategy(strategy);
+        let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+        assert_eq!(
+            int_row(&out, 0),
+            Some(vec![Some(1), Some(2), Some(3), None])
+        );
+    }
+    let out = list_set_operation(&a, &b, SetOperation::Difference, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1), None]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sorted_nulls_last() -> PolarsResult<()> {
+    let options = SetOpOptions::sorted_nulls_last();
+    assert_eq!(
+        options,
+        SetOpOptions::default()
+            .with_sort_numeric(true)
+            .with_nulls_last(true)
+    );
+
+    let rows = [Series::new("", [Some(3i32), None, Some(1)])];
+    let a = Series::new("a", rows).list()?.clone();
+    let b = int_list("b", &[Some(&[2, 1])]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(1), Some(2), Some(3), None])
+    );
+    let out = list_set_operation(&a, &b, SetOperation::SymmetricDifference, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2), Some(3), None]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_spill_wide_binary_rows() -> PolarsResult<()> {
+    // Rows with more distinct elements than the threshold are sorted rather than hashed, with
+    // the same output.
+    let wide = |offset: usize| -> Vec<Option<String>> {
+        (0..2 * SPILL_THRESHOLD)
+            .map(|i| (i % 7 != 0).then(|| format!("v{}", (i * 31 + offset) % 100_000)))
+            .collect()
+    };
+    let narrow = |v: &[&str]| v.iter().map(|v| Some(v.to_string())).collect::<Vec<_>>();
+    let rows_a: Rows<String> = vec![Some(wide(0)), Some(narrow(&["v1", "v2"])), None];
+    let rows_b: Rows<String> = vec![Some(narrow(&["v31", "x"])), Some(wide(17)), Some(wide(3))];
+    let list = |name: &str, rows: &Rows<String>| {
+        let rows = rows
+            .iter()
+            .map(|row| row.as_ref().map(|row| Series::new("", row)))
+            .collect::<Vec<_>>();
+        Series::new(name, rows).list().unwrap().clone()
+    };
+    let (a, b) = (list("a", &rows_a), list("b", &rows_b));
+    check_against_oracle(&a, &b, &rows_a, &rows_b, |ca, i| {
+        ca.get_as_series(i).map(|_| str_row(ca, i))
+    })?;
+
+    let options = SetOpOptions::default().with_preserve_left_duplicates(true);
+    for set_op in [SetOperation::Intersection, SetOperation::Difference] {
+        let out = list_set_operation(&a, &b, set_op, options)?;
+        for (i, (row_a, row_b)) in rows_a.iter().zip(&rows_b).enumerate() {
+            let (Some(row_a), Some(row_b)) = (row_a, row_b) else {
+                continue;
+            };
+            let expected = row_a
+                .iter()
+                .filter(|v| row_b.contains(v) == (set_op == SetOperation::Intersection))
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(str_row(&out, i), expected, "{set_op} row {i}");
+        }
+    }
+
+    // A wide broadcast operand is hashed once, and the rows of `a` are diffed against it.
+    let b = list("b", &vec![Some(wide(5))]);
+    for set_op in [SetOperation::Union, SetOperation::SymmetricDifference] {
+        let out = list_set_operation(&a, &b, set_op, Default::default())?;
+        for (i, row_a) in rows_a.iter().flatten().enumerate() {
+            let expected = oracle_set_operation(row_a, &wide(5), set_op);
+            assert_eq!(str_row(&out, i), expected, "{set_op} row {i}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_spill_wide_low_cardinality_rows() -> PolarsResult<()> {
+    // Only distinct elements count towards the threshold, so a wide row of few distinct values
+    // stays in the hash sets.
+    let wide = |offset: usize| -> Vec<Option<String>> {
+        (0..2 * SPILL_THRESHOLD)
+            .map(|i| (i % 11 != 0).then(|| format!("v{}", (i + offset) % 100)))
+            .collect()
+    };
+    let mut set = IndexSet::<_, ahash::RandomState>::default();
+    let row = wide(0);
+    assert!(!extend_or_spill(
+        &mut set,
+        &mut row.iter().map(|v| v.as_deref())
+    ));
+    assert_eq!(set.len(), 101);
+
+    let rows_a: Rows<String> = vec![Some(wide(0)), Some(wide(50))];
+    let rows_b: Rows<String> = vec![Some(wide(70)), Some(vec![Some("v3".into())])];
+    let list = |name: &str, rows: &Rows<String>| {
+        let rows = rows
+            .iter()
+            .map(|row| row.as_ref().map(|row| Series::new("", row)))
+            .collect::<Vec<_>>();
+        Series::new(name, rows).list().unwrap().clone()
+    };
+    let (a, b) = (list("a", &rows_a), list("b", &rows_b));
+    check_against_oracle(&a, &b, &rows_a, &rows_b, |ca, i| {
+        ca.get_as_series(i).map(|_| str_row(ca, i))
+    })
+}
+
+#[test]
+fn test_set_operation_checked() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2]), None, Some(&[3, 4, 3])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[5]), Some(&[4])]);
+    let out = list_set_operation_checked(&a, &b, SetOperation::Union, Default::default())?;
+    let expected = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // `ListArray` refuses to be constructed from corrupt parts, so check those directly.
+    assert!(check_offsets(&[0, 2, 2, 5], 5).is_ok());
+    assert!(check_offsets(&[3, 3], 3).is_ok());
+    let err = check_offsets(&[0, 2, 2, 5], 3).unwrap_err();
+    assert!(matches!(err, PolarsError::ComputeError(_)));
+    assert!(err
+        .to_string()
+        .contains("last offset 5 exceeds the length 3"));
+    let err = check_offsets(&[0, 3, 2], 3).unwrap_err();
+    assert!(err.to_string().contains("not monotonically increasing"));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_decreasing_offsets() -> PolarsResult<()> {
+    // Offsets that decrease but stay in bounds pass the checks of `ListArray`.
+    let list = |values: Box<dyn Array>, offsets: Vec<i64>| {
+        // SAFETY: deliberately corrupt, as an array imported over FFI may be.
+        let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+        ListArray::<i64>::new(
+            ListArray::<i64>::default_datatype(values.data_type().clone()),
+            offsets,
+            values,
+            None,
+        )
+    };
+    let options = SetOpOptions::default();
+    let ints = list(PrimitiveArray::from_slice([1i32, 2, 3]).boxed(), vec![0, 3]);
+    let corrupt_ints = list(
+        PrimitiveArray::from_slice([1i32, 2, 3]).boxed(),
+        vec![0, 3, 1],
+    );
+    let strs = list(
+        Utf8ViewArray::from_slice_values(["x", "y"]).boxed(),
+        vec![0, 2],
+    );
+    let corrupt_strs = list(
+        Utf8ViewArray::from_slice_values(["x", "y"]).boxed(),
+        vec![2, 0],
+    );
+    for (a, b) in [(&ints, &corrupt_ints), (&corrupt_strs, &strs)] {
+        for set_op in [SetOperation::Union, SetOperation::Difference] {
+            let err = array_set_operation(a, b, set_op, &options, false).unwrap_err();
+            assert!(err.to_string().contains("not monotonically increasing"));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_null_row_as_empty() -> PolarsResult<()> {
+    let options = SetOpOptions::default().with_null_row_as_empty(true);
+    let a = int_list("a", &[None, Some(&[1, 2]), None, Some(&[3])]);
+    let b = int_list("b", &[Some(&[2, 1]), None, None, Some(&[3, 4])]);
+    for (set_op, expected) in [
+        (SetOperation::Union, [&[2, 1][..], &[1, 2], &[], &[3, 4]]),
+        (SetOperation::Intersection, [&[], &[], &[], &[3]]),
+        (SetOperation::Difference, [&[], &[1, 2], &[], &[]]),
+        (
+            SetOperation::SymmetricDifference,
+            [&[2, 1], &[1, 2], &[], &[4]],
+        ),
+    ] {
+        let out = list_set_operation(&a, &b, set_op, options)?;
+        let expected = int_list("a", &expected.map(Some));
+        assert!(
+            out.into_series().equals_missing(&expected.into_series()),
+            "{set_op}"
+        );
+        // Null rows propagate by default.
+        let out = list_set_operation(&a, &b, set_op, Default::default())?;
+        assert_eq!(out.null_count(), 3);
+    }
+
+    // A broadcast null row.
+    let out = list_set_operation(&a, &int_list("b", &[None]), SetOperation::Union, options)?;
+    let expected = int_list("a", &[Some(&[]), Some(&[1, 2]), Some(&[]), Some(&[3])]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // Null rows that still span elements don't contribute them.
+    let arr = ListArray::new(
+        ListArray::<i64>::default_datatype(ArrowDataType::Int32),
+        OffsetsBuffer::try_from(vec![0i64, 2, 3])?,
+        PrimitiveArray::from_vec(vec![1i32, 2, 3]).boxed(),
+        Some(Bitmap::from([false, true])),
+    );
+    let a = ListChunked::with_chunk("a", arr);
+    let b = int_list("b", &[Some(&[2]), Some(&[2])]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2)]));
+    assert_eq!(int_row(&out, 1), Some(vec![Some(3), Some(2)]));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_runs() -> PolarsResult<()> {
+    let (x, y, z): (&[i32], &[i32], &[i32]) = (&[1, 2], &[2, 3, 3], &[]);
+    let a = int_list(
+        "a",
+        &[
+            Some(x),
+            Some(x),
+            Some(x),
+            None,
+            None,
+            Some(y),
+            Some(y),
+            Some(z),
+            Some(x),
+        ],
+    );
+    let b = int_list(
+        "b",
+        &[
+            Some(y),
+            Some(y),
+            Some(z),
+            Some(y),
+            Some(y),
+            Some(x),
+            Some(x),
+            Some(z),
+            Some(x),
+        ],
+    );
+    let s = Series::new("a", [Some(Series::new("", ["p", "q"])), None, None]);
+    let t = Series::new("b", [Series::new("", ["q"])]);
+    for set_op in [
+        SetOperation::Union,
+        SetOperation::Intersection,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        for (a, b) in [
+            (&a, &b),
+            (&a, &a.slice(0, 1)),
+            (&b.slice(2, 1), &a),
+            (s.list()?, t.list()?),
+        ] {
+            let out = list_set_operation_runs(a, b, set_op, Default::default())?;
+            let expected = list_set_operation(a, b, set_op, Default::default())?;
+            assert_eq!(out.name(), expected.name());
+            assert!(
+                out.into_series().equals_missing(&expected.into_series()),
+                "{set_op}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_to_len() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2])]);
+    let b = int_list("b", &[Some(&[2, 3])]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.len(), 1);
+
+    // Both operands are broadcast to the expected length.
+    let out = list_set_operation_to_len(&a, &b, SetOperation::Union, Default::default(), 3)?;
+    assert_eq!(out.name(), "a");
+    let expected = Some(vec![Some(1), Some(2), Some(3)]);
+    assert_eq!(
+        (0..3).map(|i| int_row(&out, i)).collect::<Vec<_>>(),
+        [expected.clone(), expected.clone(), expected]
+    );
+    let null = int_list("b", &[None]);
+    let out = list_set_operation_to_len(&a, &null, SetOperation::Union, Default::default(), 2)?;
+    assert_eq!(out.null_count(), 2);
+
+    let c = int_list("c", &[Some(&[1]), Some(&[4])]);
+    let out = list_set_operation_to_len(&a, &c, SetOperation::Difference, Default::default(), 2)?;
+    assert_eq!(int_row(&out, 1), Some(vec![Some(1), Some(2)]));
+    let out = list_set_operation_to_len(&a, &b, SetOperation::Union, Default::default(), 0)?;
+    assert!(out.is_empty());
+
+    let err =
+        list_set_operation_to_len(&c, &b, SetOperation::Union, Default::default(), 3).unwrap_err();
+    assert!(matches!(err, PolarsError::ShapeMismatch(_)));
+    assert!(err
+        .to_string()
+        .contains("lhs of length 2 can't be broadcast to the expected length 3"));
+    Ok(())
+}
+
+#[test]
+fn test_new_offsets_capacity() {
+    // Every combination of broadcasting: the offsets never have to grow.
+    for (len_a, len_b) in [(4, 4), (1, 4), (4, 1), (1, 1), (0, 0), (0, 1), (1, 0)] {
+        let offsets_a = vec![0i64; len_a + 1];
+        let offsets_b = vec![0i64; len_b + 1];
+        let mut offsets = new_offsets(&offsets_a, &offsets_b);
+        let capacity = offsets.capacity();
+        let n_rows = std::cmp::max(len_a, len_b);
+        offsets.resize(n_rows + 1, 0);
+        assert_eq!(offsets.capacity(), capacity, "lhs={len_a} rhs={len_b}");
+    }
+}
+
+#[test]
+fn test_set_operation_masked() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[Some(&[1, 2]), Some(&[3]), Some(&[4, 5]), None, Some(&[6])],
+    );
+    let b = int_list(
+        "b",
+        &[Some(&[2]), Some(&[3]), Some(&[5]), Some(&[1]), Some(&[7])],
+    );
+    let mask = BooleanChunked::new(
+        "mask",
+        &[Some(true), Some(false), None, Some(true), Some(true)],
+    );
+
+    let out = list_set_operation_masked(&a, &b, SetOperation::Union, Default::default(), &mask)?;
+    assert_eq!(out.dtype(), a.dtype());
+    assert_eq!(out.name(), "a");
+    let expected = int_list("a", &[Some(&[1, 2]), None, None, None, Some(&[6, 7])]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // The computed rows match the unmasked operation, also with a broadcast operand.
+    let b = int_list("b", &[Some(&[2, 6])]);
+    let out =
+        list_set_operation_masked(&a, &b, SetOperation::Difference, Default::default(), &mask)?;
+    let expected = int_list("a", &[Some(&[1]), None, None, None, Some(&[])]);
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let nothing = BooleanChunked::full("mask", false, a.len());
+    let out = list_set_operation_masked(&a, &b, SetOperation::Union, Default::default(), &nothing)?;
+    assert_eq!(out.dtype(), a.dtype());
+    assert_eq!(out.null_count(), a.len());
+
+    let short = BooleanChunked::full("mask", true, 2);
+    assert!(
+        list_set_operation_masked(&a, &b, SetOperation::Union, Default::default(), &short).is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sequence_mode() -> PolarsResult<()> {
+    let options = SetOpOptions::default().with_sequence_mode(true);
+    let a = int_list("a", &[Some(&[1, 2, 3, 4]), Some(&[1, 1]), None, Some(&[])]);
+    let b = int_list("b", &[Some(&[2, 4, 3]), Some(&[1]), Some(&[1]), Some(&[5])]);
+    let row = |set_op, i| {
+        let out = list_set_operation(&a, &b, set_op, options).unwrap();
+        int_row(&out, i).map(|row| row.into_iter().flatten().collect::<Vec<_>>())
+    };
+    // Of the common subsequences of length 2, ties are broken by deleting from `a` first.
+    assert_eq!(row(SetOperation::Intersection, 0), Some(vec![2, 4]));
+    assert_eq!(row(SetOperation::Difference, 0), Some(vec![1, 3]));
+    assert_eq!(row(SetOperation::Union, 0), Some(vec![1, 2, 3, 4, 3]));
+    assert_eq!(
+        row(SetOperation::SymmetricDifference, 0),
+        Some(vec![1, 3, 3])
+    );
+    // Duplicates are elements of the sequence.
+    assert_eq!(row(SetOperation::Intersection, 1), Some(vec![1]));
+    assert_eq!(row(SetOperation::Difference, 1), Some(vec![1]));
+    assert_eq!(row(SetOperation::Union, 2), None);
+    assert_eq!(row(SetOperation::Union, 3), Some(vec![5]));
+
+    let a = str_list("a", &[&["a", "b", "c", "d"], &["x", "y"]]);
+    let b = str_list("b", &[&["b", "d", "c"]]);
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, options)?;
+    assert_eq!(str_row(&out, 0), &[Some("b".into()), Some("d".into())]);
+    assert_eq!(str_row(&out, 1), Vec::<Option<String>>::new());
+    let swapped = options.with_swap_operands(true);
+    let out = list_set_operation(&a, &b, SetOperation::Difference, swapped)?;
+    assert_eq!(str_row(&out, 0), &[Some("d".into())]);
+
+    let bools = Series::new("", [Series::new("", [true])]);
+    let err = list_set_operation(bools.list()?, bools.list()?, SetOperation::Union, options);
+    assert!(err.unwrap_err().to_string().contains("sequence mode"));
+
+    // A pair of rows whose diff table would exceed the per-row limit is rejected.
+    let wide = Series::new("", [Series::new("", (0..12_000i32).collect::<Vec<_>>())]);
+    let err = list_set_operation(wide.list()?, wide.list()?, SetOperation::Union, options);
+    assert!(err.unwrap_err().to_string().contains("table cells per row"));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_at() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[Some(&[1, 2]), Some(&[3]), Some(&[4, 5]), None, Some(&[6])],
+    );
+    let b = int_list(
+        "b",
+        &[Some(&[2]), Some(&[3]), Some(&[5]), Some(&[1]), Some(&[7])],
+    );
+    let indices = IdxCa::new("idx", &[Some(4), None, Some(0), Some(4)]);
+    let out = list_set_operation_at(&a, &b, SetOperation::Union, Default::default(), &indices)?;
+    let expected = int_list("a", &[Some(&[1, 2]), None, None, None, Some(&[6, 7])]);
+    assert!(out
+        .clone()
+        .into_series()
+        .equals_missing(&expected.into_series()));
+
+    // The untouched rows can be taken from an earlier result.
+    let earlier = list_set_operation(&a, &a, SetOperation::Union, Default::default())?;
+    let mask = BooleanChunked::new("mask", &[true, false, false, false, true]);
+    let merged = out.zip_with(&mask, &earlier)?;
+    assert_eq!(int_row(&merged, 1), Some(vec![Some(3)]));
+    assert_eq!(int_row(&merged, 4), Some(vec![Some(6), Some(7)]));
+
+    let out_of_bounds = IdxCa::new("idx", &[5]);
+    let err = list_set_operation_at(
+        &a,
+        &b,
+        SetOperation::Union,
+        Default::default(),
+        &out_of_bounds,
+    );
+    assert!(err.unwrap_err().to_string().contains("out of bounds"));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_pooled() -> PolarsResult<()> {
+    let ops = [
+        SetOperation::Union,
+        SetOperation::Intersection,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ];
+    // A broadcast operand leaves its elements in the pooled sets, which must not leak into
+    // the next call. The elements are spread out so that the rows use the hash sets.
+    let cases = [
+        (
+            int_list("a", &[Some(&[1, 2000, 3000]), None, Some(&[])]),
+            int_list("b", &[Some(&[2000, 9000])]),
+        ),
+        (
+            int_list("a", &[Some(&[7000])]),
+            int_list("b", &[Some(&[1, 7000]), Some(&[]), Some(&[4000, 4000])]),
+        ),
+        (
+            int_list("a", &[Some(&[5, 1000, 5]), Some(&[2000])]),
+            int_list("b", &[Some(&[1000]), Some(&[3, 2000])]),
+        ),
+    ];
+    let check = || -> PolarsResult<()> {
+        for _ in 0..3 {
+            for (a, b) in &cases {
+                for set_op in ops {
+                    let out = list_set_operation_pooled(a, b, set_op, Default::default())?;
+                    let expected = list_set_operation(a, b, set_op, Default::default())?;
+                    assert!(out.into_series().equals_missing(&expected.into_series()));
+                }
+            }
+        }
+        Ok(())
+    };
+    check()?;
+    std::thread::scope(|s| {
+        let handles = [(); 4].map(|_| s.spawn(check));
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })
+}
+
+#[test]
+fn test_set_operation_preserve_left_duplicates() -> PolarsResult<()> {
+    let options = SetOpOptions {
+        preserve_left_duplicates: true,
+        ..Default::default()
+    };
+    let a = int_list("a", &[Some(&[1, 1, 2, 3, 1]), Some(&[4, 4]), None]);
+    let b = int_list("b", &[Some(&[2, 3]), Some(&[]), Some(&[1])]);
+
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1)]));
+    let out = list_set_operation(&a, &b, SetOperation::Difference, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1), Some(1), Some(1)]));
+    assert_eq!(int_row(&out, 1), Some(vec![Some(4), Some(4)]));
+    assert_eq!(int_row(&out, 2), None);
+
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2), Some(3)]));
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2), Some(3)]));
+    let b = int_list("b", &[Some(&[1])]);
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, options)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(1), Some(1), Some(1)]));
+    assert_eq!(int_row(&out, 1), Some(vec![]));
+
+    // Other operations are not affected.
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    let expected = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let a = str_list("a", &[&["x", "y", "x", "z"]]);
+    let b = str_list("b", &[&["z"], &["y", "x"]]);
+    let out = list_set_operation(&a, &b, SetOperation::Difference, options)?;
+    let x = || Some("x".to_string());
+    assert_eq!(str_row(&out, 0), &[x(), Some("y".to_string()), x()]);
+    assert_eq!(str_row(&out, 1), &[Some("z".to_string())]);
+
+    // The `AnyValue` path.
+    let a = Series::new("a", [Series::new("", [true, true, false])]);
+    let b = Series::new("b", [Series::new("", [false])]);
+    let out = list_set_operation(a.list()?, b.list()?, SetOperation::Difference, options)?;
+    let expected = Series::new("a", [Series::new("", [true, true])]);
+    assert!(out.into_series().equals_missing(&expected));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_preserves_chunks() -> PolarsResult<()> {
+    let mut a = int_list("a", &[Some(&[1, 2]), Some(&[3])]);
+    a.append(&int_list("", &[Some(&[4]), None, Some(&[5, 6])]))?;
+    let mut b = int_list("b", &[Some(&[2]), Some(&[3])]);
+    b.append(&int_list("", &[Some(&[4]), Some(&[]), Some(&[6])]))?;
+
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.chunk_lengths().collect::<Vec<_>>(), &[2, 3]);
+    let expected = list_set_operation(
+        &a.rechunk(),
+        &b.rechunk(),
+        SetOperation::Union,
+        Default::default(),
+    )?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let out =
+        boolean_list_set_operation(&a, &b, SetOperation::IsSuperset, false, Default::default())?;
+    assert_eq!(out.chunk_lengths().collect::<Vec<_>>(), &[2, 3]);
+    assert_eq!(
+        Vec::from(&out),
+        &[Some(true), Some(true), Some(true), None, Some(true)]
+    );
+
+    // A single chunk operand is split along the chunks of the other one.
+    let out = list_set_operation(
+        &a,
+        &b.rechunk(),
+        SetOperation::Difference,
+        Default::default(),
+    )?;
+    assert_eq!(out.chunk_lengths().collect::<Vec<_>>(), &[2, 3]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_chunks_of_every_kernel() -> PolarsResult<()> {
+    // Every pair of chunks resolves its kernel on its own, for each kind of inner values.
+    let chunked = |name: &str, rows: &[Option<&[i32]>], dtype: &DataType| {
+        let cast = |ca: ListChunked| -> PolarsResult<ListChunked> {
+            let dtype = DataType::List(Box::new(dtype.clone()));
+            Ok(ca.cast(&dtype)?.list()?.clone())
+        };
+        let mut out = cast(int_list(name, &rows[..2]))?;
+        out.append(&cast(int_list(name, &rows[2..]))?)?;
+        PolarsResult::Ok(out)
+    };
+    let rows_a: &[Option<&[i32]>] = &[Some(&[1, 2, 2]), None, Some(&[0, 3]), Some(&[]), Some(&[4])];
+    let rows_b: &[Option<&[i32]>] = &[Some(&[2]), Some(&[1]), Some(&[3, 0]), Some(&[1]), None];
+    for dtype in [
+        DataType::String,
+        DataType::Binary,
+        DataType::Int32,
+        DataType::Float64,
+        DataType::Boolean,
+    ] {
+        let a = chunked("a", rows_a, &dtype)?;
+        let b = chunked("b", rows_b, &dtype)?;
+        assert_eq!(a.chunks().len(), 2);
+        for set_op in [
+            SetOperation::Union,
+            SetOperation::Intersection,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            let out = list_set_operation(&a, &b, set_op, Default::default())?;
+            assert_eq!(out.chunks().len(), 2);
+            let expected =
+                list_set_operation(&a.rechunk(), &b.rechunk(), set_op, Default::default())?;
+            assert!(
+                out.into_series().equals_missing(&expected.into_series()),
+                "{dtype}, {set_op}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-date")]
+fn test_set_operation_single_row_logical() -> PolarsResult<()> {
+    // Both operands are broadcast, which takes the broadcast paths of the kernels.
+    let date = DataType::List(Box::new(DataType::Date));
+    let a = int_list("a", &[Some(&[1, 2, 3])]).cast(&date)?;
+    let b = int_list("b", &[Some(&[3, 4])]).cast(&date)?;
+    for (set_op, expected) in [
+        (SetOperation::Union, &[1, 2, 3, 4][..]),
+        (SetOperation::Intersection, &[3]),
+        (SetOperation::Difference, &[1, 2]),
+        (SetOperation::SymmetricDifference, &[1, 2, 4]),
+    ] {
+        for swap in [false, true] {
+            let (a, b) = if swap { (&b, &a) } else { (&a, &b) };
+            let out = list_set_operation(a.list()?, b.list()?, set_op, Default::default())?;
+            assert_eq!(out.dtype(), &date, "{set_op}");
+            assert_eq!(out.len(), 1);
+            if !swap {
+                let expected = int_list("a", &[Some(expected)]).cast(&date)?;
+                assert!(out.into_series().equals_missing(&expected), "{set_op}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-datetime")]
+fn test_set_operation_datetime_time_zones() -> PolarsResult<()> {
+    let datetimes = |name: &str, rows: &[Option<&[i32]>], tu: TimeUnit, tz: Option<&str>| {
+        let ca = int_list(name, rows).cast(&DataType::List(Box::new(DataType::Int64)))?;
+        let dtype = DataType::List(Box::new(DataType::Datetime(tu, tz.map(String::from))));
+        // SAFETY: datetimes are physically stored as i64.
+        Ok::<_, PolarsError>(unsafe {
+            ListChunked::from_chunks_and_dtype(name, ca.chunks().clone(), dtype)
+        })
+    };
+    let us = TimeUnit::Microseconds;
+    // The physical values are instants in UTC, whatever the time zone.
+    let a = datetimes("a", &[Some(&[1, 2, 3]), None], us, Some("UTC"))?;
+    let b = datetimes(
+        "b",
+        &[Some(&[3, 4]), Some(&[1])],
+        us,
+        Some("America/New_York"),
+    )?;
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let expected = datetimes("a", &[Some(&[1, 2, 3, 4]), None], us, Some("UTC"))?;
+    assert_eq!(out.dtype(), expected.dtype());
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let a = datetimes("a", &[Some(&[1, 2, 3])], us, Some("Europe/Amsterdam"))?;
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    let expected = datetimes("a", &[Some(&[1, 2]), Some(&[2, 3])], us, Some("UTC"))?;
+    assert_eq!(out.dtype(), expected.dtype());
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // The same zone is kept as is.
+    let out = list_set_operation(&b, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.dtype(), b.dtype());
+
+    // Naive datetimes can't be compared with time-zone-aware ones.
+    let naive = datetimes("b", &[Some(&[1])], us, None)?;
+    assert!(list_set_operation(&a, &naive, SetOperation::Union, Default::default()).is_err());
+
+    // Mismatched time units are an error unless the rhs is cast.
+    let ms = datetimes("b", &[Some(&[0, 1])], TimeUnit::Milliseconds, Some("UTC"))?;
+    let err = list_set_operation(&a, &ms, SetOperation::Union, Default::default()).unwrap_err();
+    assert!(err.to_string().contains("cast_time_unit"));
+    let options = SetOpOptions::default().with_cast_time_unit(true);
+    let out = list_set_operation(&a, &ms, SetOperation::Union, options)?;
+    let expected = datetimes("a", &[Some(&[1, 2, 3, 0, 1000])], us, Some("UTC"))?;
+    assert_eq!(out.dtype(), expected.dtype());
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sorted() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 3, 5]), Some(&[2]), None, Some(&[])]);
+    let b = int_list("b", &[Some(&[2, 3, 6]), Some(&[]), Some(&[1]), Some(&[4])]);
+    let out = list_set_operation_sorted(&a, &b, SetOperation::Union)?;
+    assert_eq!(out.dtype(), a.dtype());
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(1), Some(2), Some(3), Some(5), Some(6)])
+    );
+    assert_eq!(int_row(&out, 1), Some(vec![Some(2)]));
+    assert_eq!(int_row(&out, 2), None);
+    assert_eq!(int_row(&out, 3), Some(vec![Some(4)]));
+
+    // Floats are merged in their total order, where NaN is the largest value.
+    let floats = |name: &str, row: &[f64]| {
+        Series::new(name, [Series::new("", row)])
+            .list()
+            .unwrap()
+            .clone()
+    };
+    let a = floats("a", &[-1.0, 0.5, f64::NAN]);
+    let b = floats("b", &[0.5, f64::INFINITY, f64::NAN]);
+    let out = list_set_operation_sorted(&a, &b, SetOperation::SymmetricDifference)?;
+    let row = out.get_as_series(0).unwrap();
+    assert_eq!(Vec::from(row.f64()?), &[Some(-1.0), Some(f64::INFINITY)]);
+
+    let bools = Series::new("", [Series::new("", [true])]);
+    let err = list_set_operation_sorted(bools.list()?, bools.list()?, SetOperation::Union);
+    assert!(err.unwrap_err().to_string().contains("bool"));
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_sorted_intersection_widths() -> PolarsResult<()> {
+    use std::collections::BTreeSet;
+
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    // Widths around the block size and the threshold of the SIMD path, so that blocks and
+    // scalar tails of every length are hit, also by the scalar fallback without `simd`.
+    let mut rng = SmallRng::seed_from_u64(0);
+    for width in [1, 7, 8, 9, 16, 31, 32, 33, 40, 64, 65, 100, 250] {
+        let mut sorted_row = || {
+            let row = (0..width)
+                .map(|_| rng.gen_range(0..2 * width as i64).min(255))
+                .collect::<BTreeSet<_>>();
+            row.into_iter().map(Some).collect::<Vec<_>>()
+        };
+        let mut rows_a = (0..20).map(|_| sorted_row()).collect::<Vec<_>>();
+        let rows_b = (0..20).map(|_| sorted_row()).collect::<Vec<_>>();
+        // A null element takes the row off the SIMD path.
+        rows_a[3].insert(0, None);
+        let build = |name: &str, rows: &[Vec<Option<i64>>]| {
+            let rows = rows
+                .iter()
+                .map(|row| Series::new("", row))
+                .collect::<Vec<_>>();
+            Series::new(name, rows).list().unwrap().clone()
+        };
+        let (a, b) = (build("a", &rows_a), build("b", &rows_b));
+        #[allow(unused_mut)]
+        let mut inner_dtypes = vec![DataType::Int64, DataType::Int32];
+        #[cfg(feature = "dtype-u8")]
+        inner_dtypes.push(DataType::UInt8);
+        for inner in inner_dtypes {
+            let list_dtype = DataType::List(Box::new(inner.clone()));
+            let a = a.cast(&list_dtype)?;
+            let b = b.cast(&list_dtype)?;
+            let out = list_set_operation_sorted(a.list()?, b.list()?, SetOperation::Intersection)?;
+            let out = out.cast(&DataType::List(Box::new(DataType::Int64)))?;
+            for (i, (row_a, row_b)) in rows_a.iter().zip(&rows_b).enumerate() {
+                let expected = row_a
+                    .iter()
+                    .filter(|v| row_b.contains(v))
+                    .copied()
+                    .collect::<Vec<_>>();
+                let row = out.list()?.get_as_series(i).unwrap();
+                let row = row.i64()?.into_iter().collect::<Vec<_>>();
+                assert_eq!(row, expected, "row {i} of width {width} as {inner}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_by() -> PolarsResult<()> {
+    let a = DataFrame::new(vec![
+        Series::new("id", &[1i32, 2, 3]),
+        str_list("tags", &[&["x", "y"], &["z"], &["w"]]).into_series(),
+    ])?;
+    let b = DataFrame::new(vec![
+        Series::new("id", &[2i32, 1]),
+        str_list("tags", &[&["z", "q"], &["y"]]).into_series(),
+    ])?;
+
+    let out = list_set_operation_by(
+        &a,
+        &b,
+        &["id"],
+        "tags",
+        SetOperation::Intersection,
+        Default::default(),
+    )?;
+    assert_eq!(out.height(), 2);
+    assert_eq!(out.get_column_names(), &["id", "tags", "tags_intersection"]);
+    let ids = out
+        .column("id")?
+        .i32()?
+        .into_no_null_iter()
+        .collect::<Vec<_>>();
+    let result = out.column("tags_intersection")?.list()?;
+    for (row, id) in ids.into_iter().enumerate() {
+        let expected = match id {
+            1 => vec![Some("y".to_string())],
+            2 => vec![Some("z".to_string())],
+            _ => unreachable!(),
+        };
+        assert_eq!(str_row(result, row), expected);
+    }
+
+    // The options are applied to the matched rows.
+    let options = SetOpOptions::default().with_swap_operands(true);
+    let out = list_set_operation_by(&a, &b, &["id"], "tags", SetOperation::Difference, options)?;
+    let out = out.sort(["id"], Default::default())?;
+    let result = out.column("tags_difference")?.list()?;
+    assert_eq!(str_row(result, 0), Vec::<Option<String>>::new());
+    assert_eq!(str_row(result, 1), &[Some("q".to_string())]);
+
+    // A join key is coalesced by the join, so it has no list of `b` to compare with.
+    let on = ["id", "tags"];
+    let err = list_set_operation_by(&a, &b, &on, "tags", SetOperation::Union, options);
+    assert!(err.unwrap_err().to_string().contains("join key 'tags'"));
+    Ok(())
+}
+
+fn int_list(name: &str, rows: &[Option<&[i32]>]) -> ListChunked {
+    let rows = rows
+        .iter()
+        .map(|row| row.map(|row| Series::new("", row)))
+        .collect::<Vec<_>>();
+    Series::new(name, rows)
+        .cast(&DataType::List(Box::new(DataType::Int32)))
+        .unwrap()
+        .list()
+        .unwrap()
+        .clone()
+}
+
+fn int_row(ca: &ListChunked, idx: usize) -> Option<Vec<Option<i32>>> {
+    ca.get_as_series(idx)
+        .map(|s| s.i32().unwrap().into_iter().collect())
+}
+
+#[test]
+fn test_list_venn() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[4]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[Some(&[3, 5, 1]), Some(&[]), Some(&[1]), Some(&[6, 6])],
+    );
+
+    let (both, only_a, only_b) = list_venn(&a, &b)?;
+    assert_eq!(both.dtype(), a.dtype());
+
+    let union = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    for row in 0..a.len() {
+        let (Some(both), Some(only_a), Some(only_b)) = (
+            int_row(&both, row),
+            int_row(&only_a, row),
+            int_row(&only_b, row),
+        ) else {
+            assert!(union.get_as_series(row).is_none());
+            continue;
+        };
+        let mut parts = [both.clone(), only_a.clone(), only_b.clone()].concat();
+        let mut union = int_row(&union, row).unwrap();
+        parts.sort();
+        union.sort();
+        assert_eq!(parts, union);
+        assert!(both
+            .iter()
+            .all(|v| !only_a.contains(v) && !only_b.contains(v)));
+        assert!(only_a.iter().all(|v| !only_b.contains(v)));
+    }
+
+    assert_eq!(int_row(&both, 0), Some(vec![Some(1), Some(3)]));
+    assert_eq!(int_row(&only_a, 0), Some(vec![Some(2)]));
+    assert_eq!(int_row(&only_b, 0), Some(vec![Some(5)]));
+    assert_eq!(int_row(&only_b, 3), Some(vec![Some(6)]));
+
+    // Broadcast the rhs.
+    let b = int_list("b", &[Some(&[2, 4])]);
+    let (both, only_a, only_b) = list_venn(&a, &b)?;
+    assert_eq!(both.len(), 4);
+    assert_eq!(int_row(&both, 1), Some(vec![Some(4)]));
+    assert_eq!(int_row(&only_a, 0), Some(vec![Some(1), Some(3)]));
+    assert_eq!(int_row(&only_b, 3), Some(vec![Some(2), Some(4)]));
+    Ok(())
+}
+
+#[test]
+fn test_list_set_diff_both() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[4]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[Some(&[3, 5, 1]), Some(&[]), Some(&[1]), Some(&[6, 6])],
+    );
+    let (removed, added) = list_set_diff_both(&a, &b)?;
+    assert_eq!(int_row(&removed, 0), Some(vec![Some(2)]));
+    assert_eq!(int_row(&added, 0), Some(vec![Some(5)]));
+    assert_eq!(int_row(&removed, 2), None);
+    assert_eq!(int_row(&added, 2), None);
+
+    // Both sides together reconstruct the symmetric difference, also with a broadcast operand.
+    for b in [b.clone(), int_list("b", &[Some(&[4, 2, 7])])] {
+        let (removed, added) = list_set_diff_both(&a, &b)?;
+        let expected = list_set_operation(
+            &a,
+            &b,
+            SetOperation::SymmetricDifference,
+            Default::default(),
+        )?;
+        for row in 0..a.len() {
+            let parts = int_row(&removed, row)
+                .zip(int_row(&added, row))
+                .map(|(removed, added)| [removed, added].concat());
+            assert_eq!(parts, int_row(&expected, row), "row {row}");
+        }
+    }
+    Ok(())
+}
+#[test]
+#[cfg(feature = "dtype-u8")]
+fn test_list_union_with_provenance() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[4, 7]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[Some(&[3, 5, 1]), Some(&[8, 9]), Some(&[1]), Some(&[6, 6])],
+    );
+
+    let (union, provenance) = list_union_with_provenance(&a, &b)?;
+    let expected = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert!(union.into_series().equals_missing(&expected.into_series()));
+    assert_eq!(
+        provenance.dtype(),
+        &DataType::List(Box::new(DataType::UInt8))
+    );
+    let tags = |row| {
+        provenance
+            .get_as_series(row)
+            .map(|s| s.u8().unwrap().into_no_null_iter().collect::<Vec<_>>())
+    };
+    // Overlapping rows.
+    assert_eq!(tags(0), Some(vec![3, 1, 3, 2]));
+    // Disjoint rows.
+    assert_eq!(tags(1), Some(vec![1, 1, 2, 2]));
+    assert_eq!(tags(2), None);
+    assert_eq!(tags(3), Some(vec![2]));
+    Ok(())
+}
+
+#[test]
+fn test_list_set_operation_long() -> PolarsResult<()> {
+    let list = |name: &str, rows: Vec<Option<Vec<Option<i32>>>>| {
+        let rows = rows
+            .into_iter()
+            .map(|row| row.map(|row| Series::new("", row)))
+            .collect::<Vec<_>>();
+        Series::new(name, rows).list().unwrap().clone()
+    };
+    let a = list(
+        "a",
+        vec![
+            Some(vec![Some(1), Some(2), Some(2), None]),
+            Some(vec![Some(4)]),
+            None,
+            Some(vec![]),
+        ],
+    );
+    let b = list(
+        "b",
+        vec![
+            Some(vec![None, Some(5), Some(1)]),
+            Some(vec![]),
+            Some(vec![Some(1)]),
+            Some(vec![]),
+        ],
+    );
+
+    let out = list_set_operation_long(&a, &b)?;
+    let expected = DataFrame::new(vec![
+        Series::new("row_index", [0 as IdxSize, 0, 0, 0, 1]),
+        Series::new("element", [Some(1i32), Some(2), None, Some(5), Some(4)]),
+        Series::new("in_a", [true, true, true, false, true]),
+        Series::new("in_b", [true, false, true, true, false]),
+    ])?;
+    assert!(out.equals_missing(&expected));
+
+    // Broadcast the rhs, the row index is the one of the output.
+    let b = list("b", vec![Some(vec![Some(4)])]);
+    let out = list_set_operation_long(&a, &b)?;
+    let row_index = out.column("row_index")?.idx()?;
+    assert_eq!(
+        Vec::from(row_index),
+        &[Some(0), Some(0), Some(0), Some(0), Some(1), Some(3)]
+    );
+    let in_b = out.column("in_b")?.bool()?;
+    assert_eq!(
+        Vec::from(in_b),
+        &[false, false, false, true, true, true].map(Some)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_with_key() -> PolarsResult<()> {
+    let a = str_list("a", &[&["Foo", "bar", "FOO"], &["x"]]);
+    let b = str_list("b", &[&["foo", "baz"], &["X", "y"]]);
+    fn lowercase(v: &[u8]) -> Cow<[u8]> {
+        Cow::Owned(v.to_ascii_lowercase())
+    }
+
+    let out = list_set_operation_with_key(&a, &b, SetOperation::Union, lowercase)?;
+    assert_eq!(
+        str_row(&out, 0),
+        &[
+            Some("Foo".to_string()),
+            Some("bar".to_string()),
+            Some("baz".to_string())
+        ]
+    );
+    assert_eq!(
+        str_row(&out, 1),
+        &[Some("x".to_string()), Some("y".to_string())]
+    );
+
+    let out = list_set_operation_with_key(&a, &b, SetOperation::Intersection, lowercase)?;
+    assert_eq!(str_row(&out, 0), &[Some("Foo".to_string())]);
+    let out = list_set_operation_with_key(&a, &b, SetOperation::Difference, lowercase)?;
+    assert_eq!(str_row(&out, 0), &[Some("bar".to_string())]);
+    let out = list_set_operation_with_key(&a, &b, SetOperation::SymmetricDifference, lowercase)?;
+    assert_eq!(str_row(&out, 1), &[Some("y".to_string())]);
+
+    // Without canonicalization the key path matches the regular kernels.
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        let keyed =
+            list_set_operation_with_key(&a, &b, set_op, |v| -> Cow<[u8]> { Cow::Borrowed(v) })?;
+        let expected = list_set_operation(&a, &b, set_op, Default::default())?;
+        assert!(keyed.into_series().equals_missing(&expected.into_series()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_boolean_set_operation_with_key() -> PolarsResult<()> {
+    let a = str_list("a", &[&["FOO"], &["Foo", "bar"], &["x"], &[]]);
+    let b = str_list(
+        "b",
+        &[&["foo", "bar"], &["BAR", "foo", "FOO"], &["y"], &["z"]],
+    );
+    fn lowercase(v: &[u8]) -> Cow<[u8]> {
+        Cow::Owned(v.to_ascii_lowercase())
+    }
+    let is = |set_op, tri_state| {
+        boolean_list_set_operation_with_key(
+            &a,
+            &b,
+            set_op,
+            tri_state,
+            Default::default(),
+            lowercase,
+        )
+        .map(|out| Vec::from(&out))
+    };
+
+    assert_eq!(
+        is(SetOperation::IsSubset, false)?,
+        &[Some(true), Some(true), Some(false), Some(true)]
+    );
+    assert_eq!(
+        is(SetOperation::IsSubset, true)?,
+        &[Some(true), Some(true), Some(false), None]
+    );
+    assert_eq!(
+        is(SetOperation::IsSuperset, false)?,
+        &[Some(false), Some(true), Some(false), Some(false)]
+    );
+    assert_eq!(
+        is(SetOperation::IsEqual, false)?,
+        &[Some(false), Some(true), Some(false), Some(false)]
+    );
+    assert_eq!(
+        is(SetOperation::IsDisjoint, false)?,
+        &[Some(false), Some(false), Some(true), Some(true)]
+    );
+    // Without case folding the elements differ.
+    let out =
+        boolean_list_set_operation(&a, &b, SetOperation::IsSubset, false, Default::default())?;
+    assert_eq!(out.get(0), Some(false));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-struct")]
+fn test_set_operation_by_struct_field() -> PolarsResult<()> {
+    let objects = |rows: &[&[(i32, &str)]]| -> PolarsResult<ListChunked> {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let id = Series::new("id", row.iter().map(|v| v.0).collect::<Vec<_>>());
+                let name = Series::new("name", row.iter().map(|v| v.1).collect::<Vec<_>>());
+                StructChunked::new("", &[id, name]).map(|ca| ca.into_series())
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        Ok(Series::new("", rows).list()?.clone())
+    };
+    let a = objects(&[&[(1, "a"), (2, "b"), (1, "a2")], &[(3, "c")]])?;
+    let b = objects(&[&[(2, "b2"), (4, "d")], &[(3, "c2")]])?;
+
+    let out = list_set_operation_by_struct_field(&a, &b, SetOperation::Union, "id")?;
+    let expected = objects(&[&[(1, "a"), (2, "b"), (4, "d")], &[(3, "c")]])?;
+    assert_eq!(out.dtype(), a.dtype());
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    let out = list_set_operation_by_struct_field(&a, &b, SetOperation::Difference, "id")?;
+    let expected = objects(&[&[(1, "a")], &[]])?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+
+    // Without a key the whole structs are compared.
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    assert_eq!(
+        out.lst_lengths().into_iter().collect::<Vec<_>>(),
+        &[Some(0), Some(0)]
+    );
+
+    let err = list_set_operation_by_struct_field(&a, &b, SetOperation::Union, "missing");
+    assert!(matches!(err, Err(PolarsError::StructFieldNotFound(_))));
+    let ints = int_list("a", &[Some(&[1])]);
+    assert!(list_set_operation_by_struct_field(&ints, &ints, SetOperation::Union, "id").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_is_empty() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[
+            Some(&[1, 2]),
+            Some(&[1, 1]),
+            Some(&[]),
+            None,
+            Some(&[3]),
+            Some(&[]),
+        ],
+    );
+    let b = int_list(
+        "b",
+        &[
+            Some(&[3]),
+            Some(&[1]),
+            Some(&[]),
+            Some(&[1]),
+            Some(&[3, 4]),
+            Some(&[5]),
+        ],
+    );
+
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        let out = list_set_operation_is_empty(&a, &b, set_op)?;
+        let expected = list_set_operation(&a, &b, set_op, Default::default())?;
+        let expected = expected
+            .into_iter()
+            .map(|s| s.map(|s| s.is_empty()))
+            .collect::<Vec<_>>();
+        assert_eq!(out.into_iter().collect::<Vec<_>>(), expected, "{set_op}");
+    }
+
+    let out = list_set_operation_is_empty(&a, &b, SetOperation::Intersection)?;
+    assert_eq!(
+        Vec::from(&out),
+        &[
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+            Some(false),
+            Some(true)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_empty_and_null_rows() -> PolarsResult<()> {
+    // Every combination of a valid empty row, a null row and a non-empty row.
+    let a = int_list(
+        "a",
+        &[
+            Some(&[]),
+            Some(&[]),
+            None,
+            None,
+            Some(&[1]),
+            Some(&[1]),
+            None,
+            Some(&[]),
+        ],
+    );
+    let b = int_list(
+        "b",
+        &[
+            Some(&[]),
+            None,
+            Some(&[]),
+            None,
+            Some(&[]),
+            None,
+            Some(&[1]),
+            Some(&[1]),
+        ],
+    );
+    let expected_validity = [true, false, false, false, true, false, false, true];
+    let as_str = DataType::List(Box::new(DataType::String));
+    let operands = [
+        (a.clone(), b.clone()),
+        (
+            a.cast(&as_str)?.list()?.clone(),
+            b.cast(&as_str)?.list()?.clone(),
+        ),
+    ];
+    for (a, b) in &operands {
+        for set_op in [
+            SetOperation::Intersection,
+            SetOperation::Union,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            let hashed = list_set_operation(a, b, set_op, Default::default())?;
+            let merged = list_set_operation_sorted(a, b, set_op)?;
+            for out in [hashed, merged] {
+                for (i, &valid) in expected_validity.iter().enumerate() {
+                    let row = out.get_as_series(i);
+                    assert_eq!(row.is_some(), valid, "{set_op} of row {i}");
+                }
+                // Two valid empty rows give a valid empty row, not a null one.
+                assert_eq!(out.get_as_series(0).unwrap().len(), 0, "{set_op}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_dos_resistant_hashing() -> PolarsResult<()> {
+    let a = str_list("a", &[&["x", "y", "y", " z"], &[], &["x"]]);
+    let b = str_list("b", &[&["z", "y"], &["x"], &[]]);
+    let binary = DataType::List(Box::new(DataType::Binary));
+    let (a_bin, b_bin) = (a.cast(&binary)?, b.cast(&binary)?);
+    let operands = [
+        (a.clone(), b.clone()),
+        (a_bin.list()?.clone(), b_bin.list()?.clone()),
+    ];
+    // The hasher doesn't change the output, including its order.
+    for (a, b) in &operands {
+        for set_op in [
+            SetOperation::Intersection,
+            SetOperation::Union,
+            SetOperation::Difference,
+            SetOperation::SymmetricDifference,
+        ] {
+            for options in [
+                SetOpOptions::default(),
+                SetOpOptions::default().with_trim(true),
+            ] {
+                let expected = list_set_operation(a, b, set_op, options)?;
+                let options = options.with_dos_resistant_hashing(true);
+                let out = list_set_operation(a, b, set_op, options)?;
+                assert!(
+                    out.into_series().equals_missing(&expected.into_series()),
+                    "{set_op}"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_validity() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[3])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[])]);
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        // Null-free inputs don't allocate an output validity.
+        let out = list_set_operation(&a, &b, set_op, Default::default())?;
+        assert!(out.downcast_iter().all(|arr| arr.validity().is_none()));
+        let out = list_set_operation(&a, &b.slice(0, 1), set_op, Default::default())?;
+        assert!(out.downcast_iter().all(|arr| arr.validity().is_none()));
+
+        // A broadcast null row masks out every output row.
+        let null = int_list("b", &[None]);
+        let out = list_set_operation(&a, &null, set_op, Default::default())?;
+        assert_eq!(out.len(), 2);
+        assert_eq!(out.null_count(), 2);
+        let out = list_set_operation(&null, &a, set_op, Default::default())?;
+        assert_eq!(out.len(), 2);
+        assert_eq!(out.null_count(), 2);
+        let out = list_set_operation_is_empty(&a, &null, set_op)?;
+        assert_eq!(out.null_count(), 2);
+    }
+    let (both, _, _) = list_venn(&int_list("a", &[None]), &a)?;
+    assert_eq!(both.null_count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_chain() -> PolarsResult<()> {
+    let a = int_list(
+        "a",
+        &[Some(&[1, 2, 3, 3, 4]), Some(&[1, 5]), None, Some(&[])],
+    );
+    let b = int_list("b", &[Some(&[4, 3, 2]), Some(&[5]), Some(&[1]), Some(&[2])]);
+    let c = int_list("c", &[Some(&[3, 6]), Some(&[]), Some(&[1]), Some(&[2, 7])]);
+    let ops = [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ];
+    let defaults = SetOpOptions::default();
+
+    // `(a ∩ b) \ c` and every other chain match two sequential calls, also with a broadcast
+    // operand in any position.
+    let c_broadcast = int_list("c", &[Some(&[2, 5, 8])]);
+    let a_broadcast = int_list("a", &[Some(&[5, 2, 5])]);
+    for (a, b, c) in [(&a, &b, &c), (&a, &b, &c_broadcast), (&a_broadcast, &b, &c)] {
+        for op1 in ops {
+            for op2 in ops {
+                let out = list_set_operation_chain(a, b, op1, c, op2)?;
+                let ab = list_set_operation(a, b, op1, defaults)?;
+                let expected = list_set_operation(&ab, c, op2, defaults)?;
+                assert!(
+                    out.into_series().equals_missing(&expected.into_series()),
+                    "({op1} then {op2})"
+                );
+            }
+        }
+    }
+    let out = list_set_operation_chain(
+        &a,
+        &b,
+        SetOperation::Intersection,
+        &c,
+        SetOperation::Difference,
+    )?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2), Some(4)]));
+    assert_eq!(int_row(&out, 2), None);
+
+    let err = list_set_operation_chain(&a, &b, SetOperation::IsSubset, &c, SetOperation::Union);
+    assert!(err.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_merge_overlapping_sets() -> PolarsResult<()> {
+    // The first and last row share 3, the second row is separate.
+    let a = int_list("a", &[Some(&[1, 2, 3]), Some(&[7, 8]), Some(&[3, 4, 4])]);
+    let out = merge_overlapping_sets(&a)?;
+    assert_eq!(out.len(), 2);
+    assert_eq!(out.null_count(), 0);
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(1), Some(2), Some(3), Some(4)])
+    );
+    assert_eq!(int_row(&out, 1), Some(vec![Some(7), Some(8)]));
+
+    // Rows are merged transitively, null rows are ignored and empty rows are sets of their own.
+    let a = int_list(
+        "a",
+        &[
+            Some(&[5]),
+            Some(&[1, 2]),
+            None,
+            Some(&[]),
+            Some(&[3, 4]),
+            Some(&[2, 3]),
+        ],
+    );
+    let out = merge_overlapping_sets(&a)?;
+    assert_eq!(out.len(), 3);
+    assert_eq!(int_row(&out, 0), Some(vec![Some(5)]));
+    assert_eq!(
+        int_row(&out, 1),
+        Some(vec![Some(1), Some(2), Some(3), Some(4)])
+    );
+    assert_eq!(int_row(&out, 2), Some(vec![]));
+    Ok(())
+}
+
+#[test]
+fn test_set_at_least_k() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 3, 3]), Some(&[1]), None]);
+    let b = int_list("b", &[Some(&[2, 3, 4]), Some(&[]), Some(&[1])]);
+    let c = int_list("c", &[Some(&[3, 4, 5]), Some(&[1, 1]), Some(&[1])]);
+    let columns = [&a, &b, &c];
+
+    let out = list_set_at_least_k(&columns, 1)?;
+    assert_eq!(
+        int_row(&out, 0),
+        Some(vec![Some(1), Some(2), Some(3), Some(4), Some(5)])
+    );
+    assert_eq!(int_row(&out, 1), Some(vec![Some(1)]));
+    assert_eq!(int_row(&out, 2), None);
+
+    let out = list_set_at_least_k(&columns, 2)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(2), Some(3), Some(4)]));
+    // Duplicates within a single list only count once.
+    assert_eq!(int_row(&out, 1), Some(vec![Some(1)]));
+
+    let out = list_set_at_least_k(&columns, 3)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(3)]));
+    assert_eq!(int_row(&out, 1), Some(vec![]));
+    let intersection = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    let intersection = list_set_operation(
+        &intersection,
+        &c,
+        SetOperation::Intersection,
+        Default::default(),
+    )?;
+    assert!(out
+        .into_series()
+        .equals_missing(&intersection.into_series()));
+
+    // Unit-length columns are broadcast.
+    let d = int_list("d", &[Some(&[1, 4])]);
+    let out = list_set_at_least_k(&[&b, &d], 2)?;
+    assert_eq!(int_row(&out, 0), Some(vec![Some(4)]));
+    assert_eq!(int_row(&out, 2), Some(vec![Some(1)]));
+
+    assert!(list_set_at_least_k(&columns, 0).is_err());
+    assert!(list_set_at_least_k(&columns, 4).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-struct")]
+fn test_list_set_stats() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[1, 1]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[
+            Some(&[3, 4, 2, 4, 5]),
+            Some(&[2, 3]),
+            Some(&[1]),
+            Some(&[7]),
+        ],
+    );
+    let out = list_set_stats(&a, &b)?;
+    assert_eq!(out.name(), "a");
+    let field = |name: &str| -> Vec<Option<IdxSize>> {
+        Vec::from(out.field_by_name(name).unwrap().idx().unwrap())
+    };
+    // Overlapping, disjoint, null and empty rows.
+    assert_eq!(field("len_a"), &[Some(4), Some(2), None, Some(0)]);
+    assert_eq!(field("len_b"), &[Some(5), Some(2), None, Some(1)]);
+    assert_eq!(field("distinct_a"), &[Some(3), Some(1), None, Some(0)]);
+    assert_eq!(field("distinct_b"), &[Some(4), Some(2), None, Some(1)]);
+    assert_eq!(field("intersection"), &[Some(2), Some(0), None, Some(0)]);
+    assert_eq!(field("union"), &[Some(5), Some(3), None, Some(1)]);
+    assert_eq!(out.null_count(), 1);
+
+    // Null elements are counted and a string operand of length 1 is broadcast.
+    let a = Series::new("a", [Series::new("", [Some("x"), None, None])]);
+    let b = str_list("b", &[&["x", "y"], &["z"]]);
+    let out = list_set_stats(a.list()?, &b)?;
+    let field = |name: &str| Vec::from(out.field_by_name(name).unwrap().idx().unwrap());
+    assert_eq!(field("distinct_a"), &[Some(2), Some(2)]);
+    assert_eq!(field("intersection"), &[Some(1), Some(0)]);
+    assert_eq!(field("union"), &[Some(3), Some(3)]);
+    Ok(())
+}
+
+#[test]
+fn test_list_set_region_counts() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[1, 1]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[
+            Some(&[3, 4, 2, 4, 5]),
+            Some(&[2, 3]),
+            Some(&[1]),
+            Some(&[7]),
+        ],
+    );
+    let out = list_set_region_counts(&a, &b)?;
+    assert_eq!(
+        out.get_column_names(),
+        &["a", "b", "intersection", "a_only", "b_only"]
+    );
+    let column = |name: &str| -> Vec<Option<IdxSize>> {
+        Vec::from(out.column(name).unwrap().idx().unwrap())
+    };
+    // Overlapping, disjoint, null and empty rows.
+    assert_eq!(column("a"), &[Some(3), Some(1), None, Some(0)]);
+    assert_eq!(column("b"), &[Some(4), Some(2), None, Some(1)]);
+    assert_eq!(column("intersection"), &[Some(2), Some(0), None, Some(0)]);
+    assert_eq!(column("a_only"), &[Some(1), Some(1), None, Some(0)]);
+    assert_eq!(column("b_only"), &[Some(2), Some(2), None, Some(1)]);
+    Ok(())
+}
+
+#[test]
+fn test_list_set_change_counts() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3]), Some(&[1, 1]), None, Some(&[])]);
+    let b = int_list(
+        "b",
+        &[Some(&[3, 4, 2, 5]), Some(&[2]), Some(&[1]), Some(&[7])],
+    );
+    let counts = |set_op| -> PolarsResult<_> {
+        let (removed, added) = list_set_change_counts(&a, &b, set_op)?;
+        assert_eq!((removed.name(), added.name()), ("removed", "added"));
+        Ok((Vec::from(&removed), Vec::from(&added)))
+    };
+
+    let (removed, added) = counts(SetOperation::Difference)?;
+    assert_eq!(removed, &[Some(2), Some(0), None, Some(0)]);
+    assert_eq!(added, &[Some(0), Some(0), None, Some(0)]);
+    let (removed, added) = counts(SetOperation::Union)?;
+    assert_eq!(removed, &[Some(0), Some(0), None, Some(0)]);
+    assert_eq!(added, &[Some(2), Some(1), None, Some(1)]);
+
+    // The counts agree with the materialized result for every operation.
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        let out = list_set_operation(&a, &b, set_op, Default::default())?;
+        let (removed, added) = counts(set_op)?;
+        for row in 0..a.len() {
+            let (Some(row_a), Some(row_out)) = (a.get_as_series(row), out.get_as_series(row))
+            else {
+                assert_eq!((removed[row], added[row]), (None, None));
+                continue;
+            };
+            let set = |s: &Series| s.i32().unwrap().into_iter().collect::<PlHashSet<_>>();
+            let (set_a, set_out) = (set(&row_a), set(&row_out));
+            let n_removed = set_a.difference(&set_out).count() as IdxSize;
+            let n_added = set_out.difference(&set_a).count() as IdxSize;
+            assert_eq!(removed[row], Some(n_removed), "{set_op} of row {row}");
+            assert_eq!(added[row], Some(n_added), "{set_op} of row {row}");
+        }
+    }
+    assert!(list_set_change_counts(&a, &b, SetOperation::IsSubset).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-struct")]
+fn test_list_value_counts() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2, 3, 3, 1, 3]), None, Some(&[])]);
+    let out = list_value_counts(&a)?;
+    assert_eq!(out.len(), 3);
+    assert_eq!(out.null_count(), 1);
+    assert_eq!(out.get_as_series(2).unwrap().len(), 0);
+
+    let row = out.get_as_series(0).unwrap();
+    let row = row.struct_()?;
+    let values = row.field_by_name("value")?;
+    let counts = row.field_by_name("count")?;
+    assert_eq!(Vec::from(values.i32()?), &[Some(3), Some(1), Some(2)]);
+    assert_eq!(Vec::from(counts.idx()?), &[Some(3), Some(2), Some(2)]);
+
+    let a = Series::new(
+        "a",
+        [Series::new(
+            "",
+            [Some("x"), None, Some("y"), Some("y"), None],
+        )],
+    );
+    let out = list_value_counts(a.list()?)?;
+    let row = out.get_as_series(0).unwrap();
+    let row = row.struct_()?;
+    let values = row.field_by_name("value")?;
+    let counts = row.field_by_name("count")?;
+    assert_eq!(Vec::from(values.str()?), &[None, Some("y"), Some("x")]);
+    assert_eq!(Vec::from(counts.idx()?), &[Some(2), Some(2), Some(1)]);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-struct")]
+fn test_list_value_counts_map() -> PolarsResult<()> {
+    use arrow::array::StructArray;
+
+    let a = int_list(
+        "a",
+        &[Some(&[1, 2, 2, 3, 3, 1, 3]), None, Some(&[]), Some(&[4])],
+    );
+    let maps = list_value_counts_map(&a)?;
+    let counts = list_value_counts(&a)?;
+    assert_eq!(maps.len(), 1);
+    let map = &maps[0];
+    let ArrowDataType::Map(field, false) = map.data_type() else {
+        panic!("expected a map, got {:?}", map.data_type())
+    };
+    assert_eq!(field.name, "entries");
+    let ArrowDataType::Struct(fields) = field.data_type() else {
+        panic!("expected struct entries, got {:?}", field.data_type())
+    };
+    let names = fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, &["key", "value"]);
+
+    // The entries of every row hold the values and counts of the struct output.
+    assert_eq!(map.len(), counts.len());
+    for i in 0..map.len() {
+        let Some(expected) = counts.get_as_series(i) else {
+            assert!(map.is_null(i));
+            continue;
+        };
+        let expected = expected.struct_()?;
+        let entries = map.value(i);
+        let entries = entries.as_any().downcast_ref::<StructArray>().unwrap();
+        let keys = Series::try_from(("", entries.values()[0].clone()))?;
+        let values = Series::try_from(("", entries.values()[1].clone()))?;
+        assert!(keys.equals_missing(&expected.field_by_name("value")?));
+        assert!(values.equals_missing(&expected.field_by_name("count")?));
+    }
+
+    // Arrow doesn't allow null keys.
+    let with_null = Series::new("a", [Series::new("", [Some(1i32), None])]);
+    let err = list_value_counts_map(with_null.list()?).unwrap_err();
+    assert!(err.to_string().contains("map key"));
+    Ok(())
+}
+
+type Rows<T> = Vec<Option<Vec<Option<T>>>>;
+
+fn n_distinct<T: Hash + Eq>(row: &[Option<T>]) -> usize {
+    row.iter().collect::<PlHashSet<_>>().len()
+}
+
+/// Assert the algebraic identities between the set operations of `a` and `b`, where `rows_a`
+/// and `rows_b` are the rows the columns were built from.
+fn check_set_identities<T: Hash + Eq>(
+    a: &ListChunked,
+    b: &ListChunked,
+    rows_a: &Rows<T>,
+    rows_b: &Rows<T>,
+) -> PolarsResult<()> {
+    let union = list_set_operation(a, b, SetOperation::Union, Default::default())?;
+    let intersection = list_set_operation(a, b, SetOperation::Intersection, Default::default())?;
+    let a_min_b = list_set_operation(a, b, SetOperation::Difference, Default::default())?;
+    let b_min_a = list_set_operation(b, a, SetOperation::Difference, Default::default())?;
+    let sym_diff = list_set_operation(a, b, SetOperation::SymmetricDifference, Default::default())?;
+    let differences =
+        list_set_operation(&a_min_b, &b_min_a, SetOperation::Union, Default::default())?;
+
+    let is = |a, b, set_op| boolean_list_set_operation(a, b, set_op, false, Default::default());
+    let is_disjoint = is(&a_min_b, &intersection, SetOperation::IsDisjoint)?;
+    let is_equal = is(&sym_diff, &differences, SetOperation::IsEqual)?;
+    let a_subset_b = is(a, b, SetOperation::IsSubset)?;
+    let b_superset_a = is(b, a, SetOperation::IsSuperset)?;
+    let a_disjoint_b = is(a, b, SetOperation::IsDisjoint)?;
+
+    let union_len = union.lst_lengths();
+    let intersection_len = intersection.lst_lengths();
+    let a_min_b_len = a_min_b.lst_lengths();
+    for i in 0..union.len() {
+        let row_a = &rows_a[if rows_a.len() == 1 { 0 } else { i }];
+        let row_b = &rows_b[if rows_b.len() == 1 { 0 } else { i }];
+        let (Some(row_a), Some(row_b)) = (row_a, row_b) else {
+            assert_eq!(union.get_as_series(i), None);
+            assert_eq!(a_subset_b.get(i), None);
+            continue;
+        };
+        let (n_a, n_b) = (n_distinct(row_a), n_distinct(row_b));
+        let (n_union, n_intersection) =
+            (union_len.get(i).unwrap(), intersection_len.get(i).unwrap());
+        let n_a_min_b = a_min_b_len.get(i).unwrap();
+
+        assert_eq!(n_union as usize, n_a + n_b - n_intersection as usize);
+        assert_eq!(is_disjoint.get(i), Some(true));
+        assert_eq!(is_equal.get(i), Some(true));
+        assert_eq!(a_subset_b.get(i), Some(n_a_min_b == 0));
+        assert_eq!(b_superset_a.get(i), a_subset_b.get(i));
+        assert_eq!(a_disjoint_b.get(i), Some(n_intersection == 0));
+    }
+    Ok(())
+}
+
+/// A straightforward implementation of a set operation on a single pair of rows to check the
+/// kernels against: distinct elements in order of first occurrence, `a` before `b`.
+fn oracle_set_operation<T: Hash + Eq + Clone>(
+    a: &[Option<T>],
+    b: &[Option<T>],
+    set_op: SetOperation,
+) -> Vec<Option<T>> {
+    let set_a = PlHashSet::<&Option<T>>::from_iter(a);
+    let set_b = PlHashSet::<&Option<T>>::from_iter(b);
+    let mut seen = PlHashSet::new();
+    let mut out = Vec::new();
+    let mut emit = |v: &Option<T>, keep: bool| {
+        if keep && seen.insert(v.clone()) {
+            out.push(v.clone());
+        }
+    };
+    match set_op {
+        SetOperation::Intersection => a.iter().for_each(|v| emit(v, set_b.contains(v))),
+        SetOperation::Union => a.iter().chain(b).for_each(|v| emit(v, true)),
+        SetOperation::Difference => a.iter().for_each(|v| emit(v, !set_b.contains(v))),
+        SetOperation::SymmetricDifference => {
+            a.iter().for_each(|v| emit(v, !set_b.contains(v)));
+            b.iter().for_each(|v| emit(v, !set_a.contains(v)));
+        },
+        op => unreachable!("boolean operation {op} has no list output"),
+    }
+    out
+}
+
+/// The boolean counterpart of [`oracle_set_operation`].
+fn oracle_is<T: Hash + Eq>(a: &[Option<T>], b: &[Option<T>], set_op: SetOperation) -> bool {
+    let set_a = PlHashSet::<&Option<T>>::from_iter(a);
+    let set_b = PlHashSet::<&Option<T>>::from_iter(b);
+    match set_op {
+        SetOperation::IsDisjoint => set_a.is_disjoint(&set_b),
+        SetOperation::IsSubset => set_a.is_subset(&set_b),
+        SetOperation::IsSuperset => set_a.is_superset(&set_b),
+        SetOperation::IsEqual => set_a == set_b,
+        op => unreachable!("{op} is not a boolean operation"),
+    }
+}
+
+/// Check every [`SetOperation`] on `a` and `b` against the oracles row by row,
+/// where `rows_a` and `rows_b` are the rows the columns were built from and `get_row` reads a
+/// row of a list output back.
+fn check_against_oracle<T: Hash + Eq + Clone + std::fmt::Debug>(
+    a: &ListChunked,
+    b: &ListChunked,
+    rows_a: &Rows<T>,
+    rows_b: &Rows<T>,
+    get_row: impl Fn(&ListChunked, usize) -> Option<Vec<Option<T>>>,
+) -> PolarsResult<()> {
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+        SetOperation::IsDisjoint,
+        SetOperation::IsSubset,
+        SetOperation::IsSuperset,
+        SetOperation::IsEqual,
+    ] {
+        let (list_out, bool_out) = if set_op.is_boolean() {
+            (
+                None,
+                Some(boolean_list_set_operation(
+                    a,
+                    b,
+                    set_op,
+                    false,
+                    Default::default(),
+                )?),
+            )
+        } else {
+            (
+                Some(list_set_operation(a, b, set_op, Default::default())?),
+                None,
+            )
+        };
+        for i in 0..std::cmp::max(a.len(), b.len()) {
+            let row_a = &rows_a[if rows_a.len() == 1 { 0 } else { i }];
+            let row_b = &rows_b[if rows_b.len() == 1 { 0 } else { i }];
+            let expected = row_a.as_ref().zip(row_b.as_ref());
+            if let Some(out) = &list_out {
+                let expected = expected.map(|(a, b)| oracle_set_operation(a, b, set_op));
+                assert_eq!(get_row(out, i), expected, "{set_op} of row {i}");
+            }
+            if let Some(out) = &bool_out {
+                let expected = expected.map(|(a, b)| oracle_is(a, b, set_op));
+                assert_eq!(out.get(i), expected, "{set_op} of row {i}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check [`list_set_operation_sorted`] against the sorted output of [`oracle_set_operation`],
+/// where `build` creates a list column of sorted and deduplicated rows.
+fn check_sorted_against_oracle<T: Hash + Ord + Clone + std::fmt::Debug>(
+    mut rows_a: Rows<T>,
+    mut rows_b: Rows<T>,
+    build: impl Fn(&str, &Rows<T>) -> ListChunked,
+    get_row: impl Fn(&ListChunked, usize) -> Option<Vec<Option<T>>>,
+) -> PolarsResult<()> {
+    for row in rows_a.iter_mut().chain(rows_b.iter_mut()).flatten() {
+        row.sort();
+        row.dedup();
+    }
+    let (a, b) = (build("a", &rows_a), build("b", &rows_b));
+    for set_op in [
+        SetOperation::Intersection,
+        SetOperation::Union,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        let out = list_set_operation_sorted(&a, &b, set_op)?;
+        for i in 0..std::cmp::max(a.len(), b.len()) {
+            let row_a = &rows_a[if rows_a.len() == 1 { 0 } else { i }];
+            let row_b = &rows_b[if rows_b.len() == 1 { 0 } else { i }];
+            let expected = row_a.as_ref().zip(row_b.as_ref()).map(|(a, b)| {
+                let mut row = oracle_set_operation(a, b, set_op);
+                row.sort();
+                row
+            });
+            assert_eq!(get_row(&out, i), expected, "{set_op} of row {i}");
+        }
+    }
+    Ok(())
+}
+
+fn rows_strategy<T: std::fmt::Debug>(
+    element: impl Strategy<Value = T> + Clone,
+    n_rows: std::ops::Range<usize>,
+) -> impl Strategy<Value = Rows<T>> {
+    let row = prop::collection::vec(prop::option::weighted(0.9, element), 0..6);
+    prop::collection::vec(prop::option::weighted(0.9, row), n_rows)
+}
+
+/// Both operands have the same length, or one of them has a single row.
+fn operands_strategy<T: std::fmt::Debug + Clone>(
+    element: impl Strategy<Value = T> + Clone,
+) -> impl Strategy<Value = (Rows<T>, Rows<T>)> {
+    (1..8usize, 0..3usize).prop_flat_map(move |(n_rows, broadcast)| {
+        let len_a = if broadcast == 1 { 1 } else { n_rows };
+        let len_b = if broadcast == 2 { 1 } else { n_rows };
+        (
+            rows_strategy(element.clone(), len_a..len_a + 1),
+            rows_strategy(element.clone(), len_b..len_b + 1),
+        )
+    })
+}
+
+fn build_list<T>(
+    name: &str,
+    rows: &Rows<T>,
+    inner: DataType,
+    new_row: impl Fn(&[Option<T>]) -> Series,
+) -> ListChunked {
+    let rows = rows
+        .iter()
+        .map(|row| row.as_deref().map(&new_row))
+        .collect::<Vec<_>>();
+    let s = Series::new(name, rows);
+    s.cast(&DataType::List(Box::new(inner)))
+        .unwrap()
+        .list()
+        .unwrap()
+        .clone()
+}
+
+proptest! {
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_identities_int((rows_a, rows_b) in operands_strategy(0..8i32)) {
+        let a = build_list("a", &rows_a, DataType::Int32, |row| Series::new("", row));
+        let b = build_list("b", &rows_b, DataType::Int32, |row| Series::new("", row));
+        check_set_identities(&a, &b, &rows_a, &rows_b).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_identities_str(
+        (rows_a, rows_b) in operands_strategy(prop::sample::select(
+            vec!["", "a", "b", "c", "a string that doesn't fit in a view"],
+        ).prop_map(String::from))
+    ) {
+        let a = build_list("a", &rows_a, DataType::String, |row| Series::new("", row));
+        let b = build_list("b", &rows_b, DataType::String, |row| Series::new("", row));
+        check_set_identities(&a, &b, &rows_a, &rows_b).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_oracle_int((rows_a, rows_b) in operands_strategy(0..8i32)) {
+        // Small domains take the bitset path.
+        let a = build_list("a", &rows_a, DataType::Int32, |row| Series::new("", row));
+        let b = build_list("b", &rows_b, DataType::Int32, |row| Series::new("", row));
+        check_against_oracle(&a, &b, &rows_a, &rows_b, int_row).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_oracle_wide_int(
+        (rows_a, rows_b) in operands_strategy((0..8i64).prop_map(|v| v << 40))
+    ) {
+        let a = build_list("a", &rows_a, DataType::Int64, |row| Series::new("", row));
+        let b = build_list("b", &rows_b, DataType::Int64, |row| Series::new("", row));
+        let get_row = |ca: &ListChunked, i| {
+            ca.get_as_series(i).map(|s| s.i64().unwrap().into_iter().collect())
+        };
+        check_against_oracle(&a, &b, &rows_a, &rows_b, get_row).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_oracle_float(
+        (rows_a, rows_b) in operands_strategy(prop::sample::select(
+            vec![0.5f64, -1.0, 1e300, f64::INFINITY, f64::NAN],
+        ).prop_map(f64::to_bits))
+    ) {
+        // Floats are compared by their bits, all of the values above have a single
+        // representation.
+        let new_row = |row: &[Option<u64>]| {
+            Series::new("", row.iter().map(|v| v.map(f64::from_bits)).collect::<Vec<_>>())
+        };
+        let a = build_list("a", &rows_a, DataType::Float64, new_row);
+        let b = build_list("b", &rows_b, DataType::Float64, new_row);
+        let get_row = |ca: &ListChunked, i| {
+            ca.get_as_series(i)
+                .map(|s| s.f64().unwrap().into_iter().map(|v| v.map(f64::to_bits)).collect())
+        };
+        check_against_oracle(&a, &b, &rows_a, &rows_b, get_row).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_oracle_str(
+        (rows_a, rows_b) in operands_strategy(prop::sample::select(
+            vec!["", "a", "b", "c", "a string that doesn't fit in a view"],
+        ).prop_map(String::from))
+    ) {
+        let get_row = |ca: &ListChunked, i| ca.get_as_series(i).map(|_| str_row(ca, i));
+        let a = build_list("a", &rows_a, DataType::String, |row| Series::new("", row));
+        let b = build_list("b", &rows_b, DataType::String, |row| Series::new("", row));
+        check_against_oracle(&a, &b, &rows_a, &rows_b, get_row).unwrap();
+
+        let as_binary = |row: &[Option<String>]| {
+            Series::new("", row).cast(&DataType::Binary).unwrap()
+        };
+        let a = build_list("a", &rows_a, DataType::Binary, as_binary);
+        let b = build_list("b", &rows_b, DataType::Binary, as_binary);
+        let get_row = |ca: &ListChunked, i| {
+            ca.get_as_series(i).map(|s| {
+                s.binary()
+                    .unwrap()
+                    .into_iter()
+                    .map(|v| v.map(|v| String::from_utf8(v.to_vec()).unwrap()))
+                    .collect()
+            })
+        };
+        check_against_oracle(&a, &b, &rows_a, &rows_b, get_row).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_sorted_oracle_int(
+        (rows_a, rows_b) in operands_strategy(prop::sample::select(vec![-3i64, 0, 5, 1 << 40]))
+    ) {
+        let build = |name: &str, rows: &Rows<i64>| {
+            build_list(name, rows, DataType::Int64, |row| Series::new("", row))
+        };
+        let get_row = |ca: &ListChunked, i| {
+            ca.get_as_series(i).map(|s| s.i64().unwrap().into_iter().collect())
+        };
+        check_sorted_against_oracle(rows_a, rows_b, build, get_row).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri and proptest do not work well
+    fn test_set_sorted_oracle_str(
+        (rows_a, rows_b) in operands_strategy(prop::sample::select(
+            vec!["", "a", "b", "c", "a string that doesn't fit in a view"],
+        ).prop_map(String::from))
+    ) {
+        let build = |name: &str, rows: &Rows<String>| {
+            build_list(name, rows, DataType::String, |row| Series::new("", row))
+        };
+        let get_row = |ca: &ListChunked, i| ca.get_as_series(i).map(|_| str_row(ca, i));
+        check_sorted_against_oracle(rows_a, rows_b, build, get_row).unwrap();
+    }
+}
+
+#[test]
+fn test_set_operation_limit() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2]), None, Some(&[3]), Some(&[4, 5])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[1]), Some(&[3, 6]), Some(&[])]);
+    let full = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    for limit in [0, 2, 4, 10] {
+        let options = SetOpOptions::default().with_limit(Some(limit));
+        let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+        assert_eq!(out.len(), std::cmp::min(limit, a.len()));
+        assert!(out
+            .into_series()
+            .equals_missing(&full.slice(0, limit).into_series()));
+
+        // A broadcast operand.
+        let out = list_set_operation(&a, &b.slice(2, 1), SetOperation::Union, options)?;
+        assert_eq!(out.len(), std::cmp::min(limit, a.len()));
+    }
+
+    // The lengths of the operands are checked as a whole.
+    let options = SetOpOptions::default().with_limit(Some(1));
+    assert!(list_set_operation(&a, &b.slice(0, 2), SetOperation::Union, options).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_masked_limit() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2]), Some(&[3]), Some(&[4, 5]), Some(&[6])]);
+    let b = int_list("b", &[Some(&[2]), Some(&[3]), Some(&[5]), Some(&[7])]);
+    let mask = BooleanChunked::new("mask", &[true, false, true, true]);
+    let full = list_set_operation_masked(&a, &b, SetOperation::Union, Default::default(), &mask)?;
+    for limit in [0, 1, 3, 10] {
+        let options = SetOpOptions::default().with_limit(Some(limit));
+        let out = list_set_operation_masked(&a, &b, SetOperation::Union, options, &mask)?;
+        assert!(out
+            .into_series()
+            .equals_missing(&full.slice(0, limit).into_series()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_runs_limit() -> PolarsResult<()> {
+    let (x, y): (&[i32], &[i32]) = (&[1, 2], &[2, 3]);
+    let a = int_list("a", &[Some(x), Some(x), Some(y), Some(y), Some(x)]);
+    let b = int_list("b", &[Some(y), Some(y), Some(y), Some(y), Some(y)]);
+    let full = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    for limit in [0, 1, 3, 10] {
+        let options = SetOpOptions::default().with_limit(Some(limit));
+        let out = list_set_operation_runs(&a, &b, SetOperation::Difference, options)?;
+        assert!(out
+            .into_series()
+            .equals_missing(&full.slice(0, limit).into_series()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_to_len_limit() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2])]);
+    let b = int_list("b", &[Some(&[2, 3])]);
+    let c = int_list("c", &[Some(&[1]), Some(&[4]), Some(&[2])]);
+    let expected = Some(vec![Some(1), Some(2), Some(3)]);
+    for limit in [0, 2, 10] {
+        let options = SetOpOptions::default().with_limit(Some(limit));
+        // The single output row of two broadcast operands is repeated up to the limit.
+        let out = list_set_operation_to_len(&a, &b, SetOperation::Union, options, 3)?;
+        assert_eq!(out.len(), std::cmp::min(limit, 3));
+        assert!((0..out.len()).all(|i| int_row(&out, i) == expected));
+
+        let out = list_set_operation_to_len(&a, &c, SetOperation::Union, options, 3)?;
+        let full = list_set_operation(&a, &c, SetOperation::Union, Default::default())?;
+        assert!(out
+            .into_series()
+            .equals_missing(&full.slice(0, limit).into_series()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_abs_normalize() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[-1, 1, 2]), Some(&[3, -4])]);
+    let b = int_list("b", &[Some(&[-2, 5]), Some(&[4, -3, 6])]);
+    let options = SetOpOptions::default().with_abs_normalize(true);
+    let rows = |out: ListChunked| -> Vec<Vec<Option<i32>>> {
+        out.into_iter()
+            .map(|row| Vec::from(row.unwrap().i32().unwrap()))
+            .collect()
+    };
+
+    // Elements keep the sign of their first occurrence.
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        rows(out),
+        [
+            vec![Some(-1), Some(2), Some(5)],
+            vec![Some(3), Some(-4), Some(6)]
+        ]
+    );
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, options)?;
+    assert_eq!(rows(out), [vec![Some(2)], vec![Some(3), Some(-4)]]);
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.get_as_series(0).unwrap().len(), 5);
+
+    // The magnitude of the extremes of an integer type, and floats.
+    let a = Series::new("a", [Series::new("", [i64::MIN, i64::MAX, -i64::MAX])]);
+    let out = list_set_operation(a.list()?, a.list()?, SetOperation::Union, options)?;
+    assert_eq!(out.get_as_series(0).unwrap().len(), 2);
+    let a = Series::new("a", [Series::new("", [-0.5f32, 0.5, -0.0, 0.0])]);
+    let out = list_set_operation(a.list()?, a.list()?, SetOperation::Union, options)?;
+    let row = out.get_as_series(0).unwrap();
+    assert_eq!(Vec::from(row.f32()?), &[Some(-0.5), Some(-0.0)]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_strip_accents() -> PolarsResult<()> {
+    // "é" as a single code point and as an "e" followed by a combining accent.
+    let a = str_list("a", &[&["caf\u{e9}", "na\u{ef}ve", "x"]]);
+    let b = str_list("b", &[&["cafe", " nai\u{308}ve", "Caf\u{e9}"]]);
+    let row = |out: ListChunked| -> Vec<Option<String>> {
+        let row = out.get_as_series(0).unwrap();
+        let row = row.str().unwrap().into_iter();
+        row.map(|v| v.map(String::from)).collect()
+    };
+    let strip = SetOpOptions::default().with_strip_accents(true);
+
+    // Elements keep the original of their first occurrence.
+    let out = list_set_operation(&a, &b, SetOperation::Union, strip)?;
+    assert_eq!(
+        row(out),
+        ["caf\u{e9}", "na\u{ef}ve", "x", " nai\u{308}ve", "Caf\u{e9}"].map(|v| Some(v.into()))
+    );
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, strip.with_trim(true))?;
+    assert_eq!(
+        row(out),
+        ["caf\u{e9}", "na\u{ef}ve"].map(|v| Some(v.into()))
+    );
+
+    // Without the option the accented variants stay distinct.
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    assert!(row(out).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_strip_accents_trim() -> PolarsResult<()> {
+    let a = str_list("a", &[&[" caf\u{e9} ", "x\t"]]);
+    let b = str_list("b", &[&["cafe  ", " y"]]);
+    let options = SetOpOptions::default()
+        .with_trim(true)
+        .with_strip_accents(true);
+
+    // The output holds the trimmed elements, as without `strip_accents`.
+    let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+    assert_eq!(
+        str_row(&out, 0),
+        ["caf\u{e9}", "x", "y"].map(|v| Some(v.into()))
+    );
+    let out = list_set_operation(&b, &a, SetOperation::SymmetricDifference, options)?;
+    assert_eq!(str_row(&out, 0), ["y", "x"].map(|v| Some(v.into())));
+    Ok(())
+}
+
+#[test]
+fn test_list_to_membership_vector() -> PolarsResult<()> {
+    let a = Series::new(
+        "a",
+        [
+            Some(Series::new("", ["c", "a", "x"])),
+            None,
+            Some(Series::new("", Vec::<&str>::new())),
+            Some(Series::new("", ["b", "b"])),
+        ],
+    );
+    let vocab = Series::new("vocab", ["a", "b", "c"]);
+    let out = list_to_membership_vector(a.list()?, &vocab)?;
+    assert_eq!(out.name(), "a");
+    assert_eq!(out.inner_dtype(), &DataType::Boolean);
+    let rows = out
+        .into_iter()
+        .map(|row| row.map(|row| Vec::from(row.bool().unwrap())))
+        .collect::<Vec<_>>();
+    let flags = |flags: [bool; 3]| Some(flags.map(Some).to_vec());
+    assert_eq!(
+        rows,
+        [
+            flags([true, false, true]),
+            None,
+            flags([false, false, false]),
+            flags([false, true, false]),
+        ]
+    );
+
+    // The vocabulary is cast to the inner dtype and may flag null elements.
+    let a = Series::new("a", [Series::new("", [Some(2i32), None])]);
+    let vocab = Series::new("vocab", [Some(1i64), None, Some(2)]);
+    let out = list_to_membership_vector(a.list()?, &vocab)?;
+    let row = out.get_as_series(0).unwrap();
+    assert_eq!(
+        Vec::from(row.bool()?),
+        &[Some(false), Some(true), Some(true)]
+    );
+
+    let vocab = Series::new("vocab", ["a", "a"]);
+    let a = str_list("a", &[&["a"]]);
+    assert!(list_to_membership_vector(&a, &vocab).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-categorical")]
+fn test_list_to_membership_vector_categorical() -> PolarsResult<()> {
+    // The vocabulary gets its own rev-map when it is cast, in which the categories are in
+    // another order than in the rev-map of `a`.
+    let dtype = DataType::List(Box::new(DataType::Categorical(None, Default::default())));
+    let a = str_list("a", &[&["x", "w"], &["y"], &[]]).cast(&dtype)?;
+    let vocab = Series::new("vocab", ["y", "x", "z"]);
+    let out = list_to_membership_vector(a.list()?, &vocab)?;
+    let rows = out
+        .into_iter()
+        .map(|row| Vec::from(row.unwrap().bool().unwrap()))
+        .collect::<Vec<_>>();
+    let flags = |flags: [bool; 3]| flags.map(Some).to_vec();
+    assert_eq!(
+        rows,
+        [
+            flags([false, true, false]),
+            flags([true, false, false]),
+            flags([false, false, false]),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_casing_resolution() -> PolarsResult<()> {
+    let a = str_list("a", &[&["Foo", "Bar"], &["x"]]);
+    let b = str_list("b", &[&["foo", "foo", "BAR"], &["X", "X"]]);
+    let union = |resolution| -> PolarsResult<Vec<Vec<String>>> {
+        let options = SetOpOptions::default().with_casing_resolution(resolution);
+        let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+        Ok(out
+            .into_iter()
+            .map(|row| {
+                let row = row.unwrap();
+                let row = row.str().unwrap().into_no_null_iter();
+                row.map(String::from).collect()
+            })
+            .collect())
+    };
+
+    assert_eq!(
+        union(Some(CasingResolution::First))?,
+        [vec!["Foo", "Bar"], vec!["x"]]
+    );
+    // "Foo" once against "foo" twice, and a tie between "Bar" and "BAR".
+    assert_eq!(
+        union(Some(CasingResolution::MostFrequent))?,
+        [vec!["foo", "Bar"], vec!["X"]]
+    );
+    assert_eq!(
+        union(Some(CasingResolution::Lowercase))?,
+        [vec!["foo", "bar"], vec!["x"]]
+    );
+    assert_eq!(
+        union(None)?,
+        [vec!["Foo", "Bar", "foo", "BAR"], vec!["x", "X"]]
+    );
+
+    // The variants are counted after trimming, so " foo" and "foo\t" are the same casing.
+    let a = str_list("a", &[&[" Foo", " foo"]]);
+    let b = str_list("b", &[&["foo\t", "Bar "]]);
+    for (resolution, expected) in [
+        (CasingResolution::MostFrequent, ["foo", "Bar"]),
+        (CasingResolution::Lowercase, ["foo", "bar"]),
+    ] {
+        let options = SetOpOptions::default()
+            .with_trim(true)
+            .with_casing_resolution(Some(resolution));
+        let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+        assert_eq!(str_row(&out, 0), expected.map(|v| Some(v.into())));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_zero_chunks() -> PolarsResult<()> {
+    let dtype = DataType::List(Box::new(DataType::Int32));
+    // SAFETY: there are no chunks to disagree with the dtype.
+    let empty = unsafe { ListChunked::from_chunks_and_dtype("a", vec![], dtype.clone()) };
+    assert_eq!(empty.chunks().len(), 0);
+    let b = int_list("b", &[Some(&[1, 2])]);
+
+    for (a, b) in [(&empty, &empty), (&empty, &b), (&b.clear(), &empty)] {
+        for set_op in [SetOperation::Union, SetOperation::Intersection] {
+            let out = list_set_operation(a, b, set_op, Default::default())?;
+            assert_eq!(out.len(), 0);
+            assert_eq!(out.dtype(), &dtype);
+        }
+        let out =
+            boolean_list_set_operation(a, b, SetOperation::IsSubset, false, Default::default())?;
+        assert_eq!(out.len(), 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_empty_vs_unit() -> PolarsResult<()> {
+    // An empty operand can only be broadcast against, so a unit-length one next to it must not
+    // be broadcast to a row either.
+    let unit = int_list("a", &[Some(&[1, 2])]);
+    let empty = unit.clear();
+    let unit_str = str_list("a", &[&["x"]]);
+    let empty_str = unit_str.clear();
+    fn identity(v: &[u8]) -> Cow<[u8]> {
+        Cow::Borrowed(v)
+    }
+    for (a, b, a_str, b_str) in [
+        (&empty, &unit, &empty_str, &unit_str),
+        (&unit, &empty, &unit_str, &empty_str),
+    ] {
+        let (only_a, both, only_b) = list_venn(a, b)?;
+        assert_eq!([only_a.len(), both.len(), only_b.len()], [0, 0, 0]);
+        let (only_a, only_b) = list_set_diff_both(a, b)?;
+        assert_eq!([only_a.len(), only_b.len()], [0, 0]);
+        assert_eq!(list_set_operation_long(a, b)?.height(), 0);
+        #[cfg(feature = "dtype-u8")]
+        {
+            let (out, provenance) = list_union_with_provenance(a, b)?;
+            assert_eq!([out.len(), provenance.len()], [0, 0]);
+        }
+        #[cfg(feature = "dtype-struct")]
+        assert_eq!(list_set_stats(a, b)?.len(), 0);
+        assert_eq!(list_set_region_counts(a, b)?.height(), 0);
+        let (removed, added) = list_set_change_counts(a, b, SetOperation::Union)?;
+        assert_eq!([removed.len(), added.len()], [0, 0]);
+        let out = list_set_operation_chain(a, b, SetOperation::Union, a, SetOperation::Difference)?;
+        assert_eq!(out.len(), 0);
+        assert_eq!(list_set_at_least_k(&[a, b, b], 2)?.len(), 0);
+
+        let out: IdxCa = list_reduce_pairwise(a, b, || 0, |n, _| *n += 1, Some)?;
+        assert_eq!(out.len(), 0);
+        assert_eq!(list_jaccard_similarity(a, b, Default::default())?.len(), 0);
+        assert_eq!(list_jaccard_distance(a, b, Default::default())?.len(), 0);
+
+        for set_op in [SetOperation::Union, SetOperation::Intersection] {
+            assert_eq!(
+                list_set_operation(a, b, set_op, Default::default())?.len(),
+                0
+            );
+            assert_eq!(list_set_operation_is_empty(a, b, set_op)?.len(), 0);
+            assert_eq!(list_set_operation_sorted(a, b, set_op)?.len(), 0);
+            let out = list_set_operation_runs(a, b, set_op, Default::default())?;
+            assert_eq!(out.len(), 0);
+            #[cfg(feature = "list_sets_roaring")]
+            assert_eq!(list_set_operation_roaring(a, b, set_op)?.len(), 0);
+
+            let out = list_set_operation_with_key(a_str, b_str, set_op, identity)?;
+            assert_eq!(out.len(), 0);
+        }
+        let out =
+            boolean_list_set_operation(a, b, SetOperation::IsSubset, false, Default::default())?;
+        assert_eq!(out.len(), 0);
+        let out = boolean_list_set_operation_with_key(
+            a_str,
+            b_str,
+            SetOperation::IsSubset,
+            false,
+            Default::default(),
+            identity,
+        )?;
+        assert_eq!(out.len(), 0);
+    }
+
+    #[cfg(feature = "dtype-struct")]
+    {
+        let row = StructChunked::new("", &[Series::new("id", [1i32])])?.into_series();
+        let unit = Series::new("a", [row]).list()?.clone();
+        let empty = unit.clear();
+        for (a, b) in [(&empty, &unit), (&unit, &empty)] {
+            let out = list_set_operation_by_struct_field(a, b, SetOperation::Union, "id")?;
+            assert_eq!(out.len(), 0);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-struct")]
+fn test_set_operation_struct_elements() -> PolarsResult<()> {
+    // Structs with a nested struct field and nulls at every level.
+    type Element<'a> = Option<(Option<i32>, Option<&'a str>)>;
+    let structs = |rows: &[&[Element]]| -> PolarsResult<ListChunked> {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let x = Series::new(
+                    "x",
+                    row.iter().map(|v| v.and_then(|v| v.0)).collect::<Vec<_>>(),
+                );
+                let y = Series::new(
+                    "y",
+                    row.iter().map(|v| v.and_then(|v| v.1)).collect::<Vec<_>>(),
+                );
+                let inner = StructChunked::new("inner", &[y])?.into_series();
+                let s = StructChunked::new("", &[x, inner])?.into_series();
+                let validity = row.iter().map(Option::is_some).collect::<Bitmap>();
+                Series::from_arrow("", s.to_arrow(0, true).with_validity(Some(validity)))
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        Ok(Series::new("", rows).list()?.clone())
+    };
+    let a = structs(&[&[
+        Some((Some(1), Some("a"))),
+        Some((Some(1), Some("a"))),
+        Some((Some(1), None)),
+        Some((None, None)),
+        None,
+        Some((Some(2), Some("b"))),
+    ]])?;
+    let b = structs(&[&[
+        Some((Some(1), None)),
+        Some((Some(1), Some("b"))),
+        Some((None, None)),
+        Some((Some(1), Some("a"))),
+    ]])?;
+
+    // Field-for-field identical structs are deduplicated. A null struct is a struct of which
+    // all fields are null.
+    let out = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.get_as_series(0).unwrap().len(), 5);
+    let out = list_set_operation(&a, &b, SetOperation::Intersection, Default::default())?;
+    let expected = structs(&[&[
+        Some((Some(1), Some("a"))),
+        Some((Some(1), None)),
+        Some((None, None)),
+    ]])?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    let out = list_set_operation(&a, &b, SetOperation::Difference, Default::default())?;
+    let expected = structs(&[&[Some((Some(2), Some("b")))]])?;
+    assert!(out.into_series().equals_missing(&expected.into_series()));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "list_sets_roaring")]
+fn test_set_operation_roaring() -> PolarsResult<()> {
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut column = |len: usize| {
+        let rows = (0..50)
+            .map(|_| {
+                let width = rng.gen_range(0..len);
+                let row = (0..width)
+                    .map(|_| (rng.gen_range(0..10) > 0).then(|| rng.gen_range(-1000..1000)))
+                    .collect::<Vec<Option<i32>>>();
+                (rng.gen_range(0..10) > 0).then(|| Series::new("", row))
+            })
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    let (a, b) = (column(2000), column(2000));
+    // The hash sets, sorted, are the oracle.
+    let sorted = SetOpOptions::default().with_sort_numeric(true);
+    for set_op in [
+        SetOperation::Union,
+        SetOperation::Intersection,
+        SetOperation::Difference,
+        SetOperation::SymmetricDifference,
+    ] {
+        for b in [b.clone(), b.slice(1, 1)] {
+            let expected = list_set_operation(&a, &b, set_op, sorted)?;
+            let out = list_set_operation_roaring(&a, &b, set_op)?;
+            assert!(out.into_series().equals_missing(&expected.into_series()));
+
+            let (a, b) = (
+                a.cast(&DataType::List(Box::new(DataType::UInt32)))?,
+                b.cast(&DataType::List(Box::new(DataType::UInt32)))?,
+            );
+            let expected = list_set_operation(a.list()?, b.list()?, set_op, sorted)?;
+            let out = list_set_operation_roaring(a.list()?, b.list()?, set_op)?;
+            assert!(out.into_series().equals_missing(&expected.into_series()));
+        }
+    }
+
+    let a = int_list("a", &[Some(&[1])]).cast(&DataType::List(Box::new(DataType::Int64)))?;
+    assert!(list_set_operation_roaring(a.list()?, a.list()?, SetOperation::Union).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_all_null_rows() -> PolarsResult<()> {
+    // Columns of null rows only have no values at all and offsets that are all 0.
+    let n_rows = 3;
+    let inner_dtypes = [
+        DataType::Int32,
+        DataType::Float64,
+        DataType::String,
+        DataType::Binary,
+        DataType::Boolean,
+    ];
+    for inner in inner_dtypes {
+        let a = ListChunked::full_null_with_dtype("a", n_rows, &inner);
+        let arr = a.downcast_iter().next().unwrap();
+        assert_eq!(arr.values().len(), 0);
+        assert!(arr.offsets().iter().all(|&o| o == 0));
+        let b = a.clone().with_name("b");
+        let b_broadcast = ListChunked::full_null_with_dtype("b", 1, &inner);
+
+        for b in [&b, &b_broadcast] {
+            for set_op in [
+                SetOperation::Intersection,
+                SetOperation::Union,
+                SetOperation::Difference,
+                SetOperation::SymmetricDifference,
+            ] {
+                let mut options = vec![
+                    SetOpOptions::default(),
+                    SetOpOptions::default().with_sort_numeric(true),
+                ];
+                // Sequence mode and sorted operations do not support booleans, whether or not
+                // there are values.
+                if inner != DataType::Boolean {
+                    options.push(SetOpOptions::default().with_sequence_mode(true));
+                }
+                for options in options {
+                    let out = list_set_operation(&a, b, set_op, options)?;
+                    assert_eq!(out.len(), n_rows, "{inner} {set_op}");
+                    assert_eq!(out.null_count(), n_rows, "{inner} {set_op}");
+                }
+                if inner != DataType::Boolean {
+                    let out = list_set_operation_sorted(&a, b, set_op)?;
+                    assert_eq!(out.null_count(), n_rows, "{inner} {set_op}");
+                }
+            }
+            for set_op in [
+                SetOperation::IsSubset,
+                SetOperation::IsSuperset,
+                SetOperation::IsDisjoint,
+                SetOperation::IsEqual,
+            ] {
+                let out = boolean_list_set_operation(&a, b, set_op, false, Default::default())?;
+                assert_eq!(out.len(), n_rows, "{inner} {set_op}");
+                assert_eq!(out.null_count(), n_rows, "{inner} {set_op}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "dtype-categorical")]
+fn test_set_operation_categorical_threshold() -> PolarsResult<()> {
+    // The union holds the 3 distinct elements "x", "y" and "z".
+    let a = str_list("a", &[&["x", "y"], &["y"], &["x"]]);
+    let b = str_list("b", &[&["z"]]);
+    let union = |threshold| {
+        let options = SetOpOptions::default().with_categorical_threshold(threshold);
+        list_set_operation(&a, &b, SetOperation::Union, options)
+    };
+    let plain = union(None)?;
+    assert_eq!(plain.inner_dtype(), &DataType::String);
+
+    let encoded = union(Some(4))?;
+    assert!(matches!(encoded.inner_dtype(), DataType::Categorical(_, _)));
+    let decoded = encoded.cast(&DataType::List(Box::new(DataType::String)))?;
+    assert!(decoded.equals_missing(&plain.clone().into_series()));
+    // The threshold is exclusive.
+    assert_eq!(union(Some(3))?.inner_dtype(), &DataType::String);
+
+    // Other dtypes are returned as is.
+    let ints = int_list("a", &[Some(&[1])]);
+    let options = SetOpOptions::default().with_categorical_threshold(Some(usize::MAX));
+    let out = list_set_operation(&ints, &ints, SetOperation::Union, options)?;
+    assert_eq!(out.inner_dtype(), &DataType::Int32);
+    Ok(())
+}
+
+#[test]
+#[cfg(all(feature = "list_sets_collation", feature = "dtype-categorical"))]
+fn test_set_operation_collated() -> PolarsResult<()> {
+    use icu_collator::{Collator, CollatorOptions};
+
+    let a = str_list("a", &[&["zebra", "éclair", "apple"], &["Äpfel"]]);
+    let b = str_list("b", &[&["eclat", "apple"], &["Zucker", "apfel"]]);
+    // The root collation, which orders accented letters next to their base letter.
+    let collator = Collator::try_new(&Default::default(), CollatorOptions::new()).unwrap();
+    let union = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let collated =
+        list_set_operation_collated(&a, &b, SetOperation::Union, Default::default(), &collator)?;
+
+    let byte_sorted = |row: usize| {
+        let mut row = str_row(&union, row);
+        row.sort();
+        row
+    };
+    let strings = |row: &[&str]| row.iter().map(|v| Some(v.to_string())).collect::<Vec<_>>();
+    assert_eq!(
+        byte_sorted(0),
+        strings(&["apple", "eclat", "zebra", "éclair"])
+    );
+    assert_eq!(
+        str_row(&collated, 0),
+        strings(&["apple", "éclair", "eclat", "zebra"])
+    );
+    assert_eq!(byte_sorted(1), strings(&["Zucker", "apfel", "Äpfel"]));
+    assert_eq!(
+        str_row(&collated, 1),
+        strings(&["apfel", "Äpfel", "Zucker"])
+    );
+
+    // Nulls are sorted like in the numeric sorted output, and encoding happens last.
+    let a = Series::new("", &[Some("b"), None, Some("a")]);
+    let a = ListChunked::full("a", &a, 1);
+    let options = SetOpOptions::default()
+        .with_nulls_last(true)
+        .with_dictionary_encode(true);
+    let out = list_set_operation_collated(&a, &a, SetOperation::Union, options, &collator)?;
+    assert!(matches!(out.inner_dtype(), DataType::Categorical(_, _)));
+    let out = out.cast(&DataType::List(Box::new(DataType::String)))?;
+    assert_eq!(
+        str_row(out.list()?, 0),
+        &[Some("a".to_string()), Some("b".to_string()), None]
+    );
+
+    let ints = int_list("a", &[Some(&[1])]);
+    let out = list_set_operation_collated(
+        &ints,
+        &ints,
+        SetOperation::Union,
+        Default::default(),
+        &collator,
+    );
+    assert!(out.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_symmetric_difference_order() -> PolarsResult<()> {
+    // The elements of `a` in order of first occurrence, then those of `b`. The second row has
+    // too wide a domain for the bitset path.
+    let a = int_list(
+        "a",
+        &[Some(&[5, 3, 1, 3, 2]), Some(&[9_000_000, 0, -9_000_000])],
+    );
+    let b = int_list("b", &[Some(&[4, 2, 0, 4, 6]), Some(&[7, 0, 8])]);
+    let expected = [vec![5, 3, 1, 4, 0, 6], vec![9_000_000, -9_000_000, 7, 8]];
+    let to_strings = |ca: &ListChunked| -> PolarsResult<ListChunked> {
+        Ok(ca
+            .cast(&DataType::List(Box::new(DataType::String)))?
+            .list()?
+            .clone())
+    };
+    let as_i32 = |s: Series| -> Vec<i32> {
+        let s = s.cast(&DataType::Int32).unwrap();
+        s.i32().unwrap().into_no_null_iter().collect()
+    };
+
+    let symmetric_difference = |a: &ListChunked, b: &ListChunked, options| {
+        list_set_operation(a, b, SetOperation::SymmetricDifference, options)
+    };
+    let options = [
+        SetOpOptions::default(),
+        SetOpOptions::default().with_trim(true),
+        SetOpOptions::default().with_dos_resistant_hashing(true),
+    ];
+    for options in options {
+        for (a, b) in [(a.clone(), b.clone()), (to_strings(&a)?, to_strings(&b)?)] {
+            let out = symmetric_difference(&a, &b, options)?;
+            for (row, expected) in expected.iter().enumerate() {
+                assert_eq!(&as_i32(out.get_as_series(row).unwrap()), expected, "{row}");
+            }
+            // A broadcast operand doesn't change the order.
+            for (row, expected) in expected.iter().enumerate() {
+                let (a_row, b_row) = (a.slice(row as i64, 1), b.slice(row as i64, 1));
+                for (a, b) in [(&a_row, &b), (&a, &b_row)] {
+                    let out = symmetric_difference(a, b, options)?;
+                    let out = as_i32(out.get_as_series(row).unwrap());
+                    assert_eq!(&out, expected, "broadcast {row}");
+                }
+            }
+        }
+    }
+
+    // Swapping the operands swaps the order.
+    let options = SetOpOptions::default().with_swap_operands(true);
+    let out = symmetric_difference(&a, &b, options)?;
+    assert_eq!(as_i32(out.get_as_series(0).unwrap()), &[4, 0, 6, 5, 3, 1]);
+    Ok(())
+}
+
+#[test]
+fn test_set_operation_binary_null_bytes() -> PolarsResult<()> {
+    use arrow::array::BinaryArray;
+
+    let list = |rows: &[&[&[u8]]]| {
+        let rows = rows
+            .iter()
+            .map(|row| Series::new("", *row))
+            .collect::<Vec<_>>();
+        Series::new("", rows).list().unwrap().clone()
+    };
+    let row = |ca: &ListChunked| -> Vec<Vec<u8>> {
+        let s = ca.get_as_series(0).unwrap();
+        let values = s.binary().unwrap().into_no_null_iter();
+        values.map(|v| v.to_vec()).collect()
+    };
+    let a = list(&[&[b"a\0b", b"a\0c", b"a\0b", b"\0", b"a"]]);
+    let b = list(&[&[b"a\0c", b"\0\0", b"a\0"]]);
+    // Elements that only differ after a null byte are distinct.
+    let union: Vec<Vec<u8>> = [&b"a\0b"[..], b"a\0c", b"\0", b"a", b"\0\0", b"a\0"]
+        .iter()
+        .map(|v| v.to_vec())
+        .collect();
+    let intersection = vec![b"a\0c".to_vec()];
+
+    let options = [
+        SetOpOptions::default(),
+        SetOpOptions::default().with_trim(true),
+        SetOpOptions::default().with_dos_resistant_hashing(true),
+    ];
+    for options in options {
+        let out = list_set_operation(&a, &b, SetOperation::Union, options)?;
+        assert_eq!(row(&out), union);
+        let out = list_set_operation(&a, &b, SetOperation::Intersection, options)?;
+        assert_eq!(row(&out), intersection);
+    }
+    let sorted = |ca: &ListChunked| {
+        ca.lst_unique()
+            .unwrap()
+            .lst_sort(Default::default())
+            .unwrap()
+    };
+    let out = list_set_operation_sorted(&sorted(&a), &sorted(&b), SetOperation::Intersection)?;
+    assert_eq!(row(&out), intersection);
+
+    // Binary values with i32 offsets on both sides.
+    let offset_list = |values: &[&[u8]]| {
+        let values = BinaryArray::<i32>::from_slice(values).boxed();
+        let dtype = ListArray::<i64>::default_datatype(values.data_type().clone());
+        let offsets = vec![0, values.len() as i64].try_into().unwrap();
+        ListArray::<i64>::new(dtype, offsets, values, None)
+    };
+    let out = array_set_operation(
+        &offset_list(&[b"a\0b", b"a\0c", b"a\0b"]),
+        &offset_list(&[b"a\0c", b"a\0"]),
+        SetOperation::Union,
+        &Default::default(),
+        false,
+    )?;
+    assert_eq!(out.values().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_list_set_operation_exploded() -> PolarsResult<()> {
+    let a = int_list("a", &[Some(&[1, 2, 2]), Some(&[3]), None, Some(&[4, 5])]);
+    let b = int_list("b", &[Some(&[2, 6]), Some(&[3]), Some(&[1]), Some(&[])]);
+    let out = list_set_operation_exploded(&a, &b, SetOperation::Difference, Default::default())?;
+    assert_eq!(out.get_column_names(), &["list_index", "value"]);
+    assert_eq!(out.column("value")?.dtype(), &DataType::Int32);
+    // The empty second row and the null third row have no output rows.
+    let list_index = Vec::from(out.column("list_index")?.idx()?);
+    assert_eq!(list_index, &[Some(0), Some(3), Some(3)]);
+    let value = Vec::from(out.column("value")?.i32()?);
+    assert_eq!(value, &[Some(1), Some(4), Some(5)]);
+
+    // Imploding by the list index gives back the non-empty rows.
+    let union = list_set_operation(&a, &b, SetOperation::Union, Default::default())?;
+    let out = list_set_operation_exploded(&a, &b, SetOperation::Union, Default::default())?;
+    assert_eq!(out.height(), 6);
+    for (row, expected) in union.into_iter().enumerate() {
+        let mask = out.column("list_index")?.idx()?.equal(row as IdxSize);
+        let values = out.column("value")?.filter(&mask)?;
+        match expected {
+            Some(expected) => assert!(values.equals_missing(&expected.with_name("value"))),
+            None => assert!(values.is_empty()),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_list_jaccard_empty_similarity() -> PolarsResult<()> {
+    // An empty and a non-empty list, two empty lists, a null row and two empty lists again.
+    let a = int_list("a", &[Some(&[]), Some(&[]), None, Some(&[])]);
+    let b = int_list("b", &[Some(&[1]), Some(&[]), Some(&[]), Some(&[])]);
+    for (empty_similarity, expected) in [
+        (EmptySimilarity::One, Some(1.0)),
+        (EmptySimilarity::Zero, Some(0.0)),
+        (EmptySimilarity::Null, None),
+    ] {
+        let similarity = list_jaccard_similarity(&a, &b, empty_similarity)?;
+        assert_eq!(
+            Vec::from(&similarity),
+            &[Some(0.0), expected, None, expected]
+        );
+        let distance = list_jaccard_distance(&a, &b, empty_similarity)?;
+        let expected = expected.map(|s| 1.0 - s);
+        assert_eq!(Vec::from(&distance), &[Some(1.0), expected, None, expected]);
+    }
+    assert_eq!(EmptySimilarity::default(), EmptySimilarity::One);
+    Ok(())
+}
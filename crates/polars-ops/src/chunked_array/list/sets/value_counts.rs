@@ -0,0 +1,116 @@
+use arrow::array::{Array, MapArray, StructArray};
+use arrow::offset::OffsetsBuffer;
+use polars_core::prelude::*;
+
+use super::downcast_values;
+use super::pairwise::{span, with_many_set_values, ListIdxBuilder, ManyKernel, SetValues};
+
+struct ValueCounts<'a> {
+    offsets: &'a [i64],
+}
+
+impl ManyKernel for ValueCounts<'_> {
+    type Output = (ListIdxBuilder, Vec<IdxSize>);
+
+    fn call<V: SetValues>(self, values: &[&V]) -> PolarsResult<Self::Output> {
+        let values = values[0];
+        let n_rows = self.offsets.len() - 1;
+        let mut out = ListIdxBuilder::new(n_rows);
+        let mut out_counts = Vec::with_capacity(values.len());
+        // Element -> (index of its first occurrence, count).
+        let mut counts = PlIndexMap::default();
+        let mut sorted = vec![];
+
+        for row in 0..n_rows {
+            counts.clear();
+            for i in span(self.offsets, row) {
+                // SAFETY: the offsets of a list array are in bounds of its values.
+                let key = unsafe { values.key_unchecked(i) };
+                counts.entry(key).or_insert((i, 0 as IdxSize)).1 += 1;
+            }
+            sorted.clear();
+            sorted.extend(counts.values().copied());
+            // Stable sort, so ties keep their order of first appearance.
+            sorted.sort_by(|(_, l), (_, r)| r.cmp(l));
+            for &(i, count) in &sorted {
+                out.push(i);
+                out_counts.push(count);
+            }
+            out.finish_row();
+        }
+        Ok((out, out_counts))
+    }
+}
+
+/// Count the occurrences of every distinct element per row.
+///
+/// Returns a `List(Struct{value, count})` column, sorted by descending count where ties keep
+/// their order of first appearance. Null elements are counted like any other value and null
+/// rows stay null.
+pub fn list_value_counts(a: &ListChunked) -> PolarsResult<ListChunked> {
+    let inner_dtype = a.inner_dtype();
+    let chunks = a
+        .downcast_iter()
+        .map(|arr| {
+            let kernel = ValueCounts {
+                offsets: arr.offsets().as_slice(),
+            };
+            let (builder, counts) = with_many_set_values(&[arr.values().as_ref()], kernel)?;
+            let counts = IdxArr::from_vec(counts).boxed();
+
+            // SAFETY: we gather from the values of `arr` itself.
+            let arr = unsafe {
+                builder.finish_with(arr.values().as_ref(), arr.validity().cloned(), |values| {
+                    let fields = vec![
+                        ArrowField::new("value", values.data_type().clone(), true),
+                        ArrowField::new("count", counts.data_type().clone(), false),
+                    ];
+                    StructArray::new(ArrowDataType::Struct(fields), vec![values, counts], None)
+                        .boxed()
+                })
+            };
+            Ok(arr.boxed())
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+
+    let dtype = DataType::List(Box::new(DataType::Struct(vec![
+        Field::new("value", inner_dtype.clone()),
+        Field::new("count", IDX_DTYPE),
+    ])));
+    // SAFETY: the struct fields hold the physical values of `a` and the counts.
+    Ok(unsafe { ListChunked::from_chunks_and_dtype(a.name(), chunks, dtype) })
+}
+
+/// Count the occurrences of every distinct element per row like [`list_value_counts`], but emit
+/// every chunk as an Arrow `Map` array, for consumers that expect map-typed columns.
+///
+/// The entries of the maps are `Struct{key, value}` with the element as key and its count as
+/// value, in the same order as the output of [`list_value_counts`]. Arrow doesn't allow null
+/// keys, so a null element is an error.
+pub fn list_value_counts_map(a: &ListChunked) -> PolarsResult<Vec<MapArray>> {
+    let counts = list_value_counts(a)?;
+    counts
+        .downcast_iter()
+        .map(|arr| {
+            let entries = downcast_values::<StructArray>(arr.values().as_ref())?;
+            let (keys, values) = (&entries.values()[0], &entries.values()[1]);
+            polars_ensure!(
+                keys.null_count() == 0,
+                ComputeError: "list value counts can't be emitted as a Map: a null element can't be a map key"
+            );
+            let fields = vec![
+                ArrowField::new("key", keys.data_type().clone(), false),
+                ArrowField::new("value", values.data_type().clone(), false),
+            ];
+            let entries = StructArray::new(
+                ArrowDataType::Struct(fields),
+                vec![keys.clone(), values.clone()],
+                None,
+            );
+            let field = ArrowField::new("entries", entries.data_type().clone(), false);
+            let dtype = ArrowDataType::Map(Box::new(field), false);
+            let offsets = OffsetsBuffer::<i32>::try_from(arr.offsets())?;
+            MapArray::try_new(dtype, offsets, entries.boxed(), arr.validity().cloned())
+        })
+        .collect()
+}
@@ -0,0 +1,226 @@
+use arrow::array::BooleanArray;
+use arrow::bitmap::{Bitmap, MutableBitmap};
+use arrow::compute::concatenate::concatenate;
+use arrow::compute::take::take_unchecked;
+use polars_core::prelude::*;
+use polars_core::utils::align_chunks_binary;
+
+use super::pairwise::{row_spans, with_set_values, ListIdxBuilder, PairKernel, SetValues};
+use super::{combine_validities, prepare_operands};
+
+struct Venn<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    /// Whether to collect the intersection, which is left empty otherwise.
+    keep_both: bool,
+}
+
+impl PairKernel for Venn<'_> {
+    type Output = [ListIdxBuilder; 3];
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let n_rows = std::cmp::max(self.offsets_a.len(), self.offsets_b.len()) - 1;
+        let mut out = [(); 3].map(|_| ListIdxBuilder::new(n_rows));
+        let mut set_a = PlIndexMap::default();
+        let mut set_b = PlIndexMap::default();
+
+        for (span_a, span_b) in row_spans(self.offsets_a, self.offsets_b) {
+            set_a.clear();
+            set_b.clear();
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            for i in span_a {
+                set_a.entry(unsafe { a.key_unchecked(i) }).or_insert(i);
+            }
+            for i in span_b {
+                set_b
+                    .entry(unsafe { b.key_unchecked(i) })
+                    .or_insert(a.len() + i);
+            }
+
+            let [both, only_a, only_b] = &mut out;
+            for (k, &i) in &set_a {
+                if set_b.contains_key(k) {
+                    if self.keep_both {
+                        both.push(i)
+                    }
+                } else {
+                    only_a.push(i)
+                }
+            }
+            for (k, &i) in &set_b {
+                if !set_a.contains_key(k) {
+                    only_b.push(i)
+                }
+            }
+            out.iter_mut().for_each(|builder| builder.finish_row());
+        }
+        Ok(out)
+    }
+}
+
+/// Split the elements of every row of `a` and `b` in one pass into those found in both, those
+/// only found in `a` and those only found in `b`.
+///
+/// Returns `(a ∩ b, a \ b, b \ a)`. Elements keep their order of first appearance and null
+/// rows in either operand result in null rows in all outputs.
+pub fn list_venn(
+    a: &ListChunked,
+    b: &ListChunked,
+) -> PolarsResult<(ListChunked, ListChunked, ListChunked)> {
+    let [both, only_a, only_b] = venn(a, b, true)?;
+    Ok((both, only_a, only_b))
+}
+
+/// Split the symmetric difference of every row of `a` and `b` in one pass into the elements
+/// only found in `a` and those only found in `b`, e.g. the elements removed and added between
+/// two snapshots.
+///
+/// Returns `(a \ b, b \ a)`, which together make up the [`SetOperation::SymmetricDifference`]
+/// of `a` and `b`. This is [`list_venn`] without the intersection.
+///
+/// [`SetOperation::SymmetricDifference`]: super::SetOperation::SymmetricDifference
+pub fn list_set_diff_both(
+    a: &ListChunked,
+    b: &ListChunked,
+) -> PolarsResult<(ListChunked, ListChunked)> {
+    let [_, only_a, only_b] = venn(a, b, false)?;
+    Ok((only_a, only_b))
+}
+
+fn venn(a: &ListChunked, b: &ListChunked, keep_both: bool) -> PolarsResult<[ListChunked; 3]> {
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let mut chunks: [Vec<ArrayRef>; 3] = Default::default();
+    for (arr_a, arr_b) in a.downcast_iter().zip(b.downcast_iter()) {
+        let venn = Venn {
+            offsets_a: arr_a.offsets().as_slice(),
+            offsets_b: arr_b.offsets().as_slice(),
+            keep_both,
+        };
+        let builders = with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), venn)?;
+
+        let values = concatenate(&[arr_a.values().as_ref(), arr_b.values().as_ref()])?;
+        let validity = combine_validities(arr_a, arr_b);
+        for (builder, chunks) in builders.into_iter().zip(chunks.iter_mut()) {
+            // SAFETY: `values` are the values of `a` followed by the values of `b`.
+            let arr = unsafe { builder.finish(values.as_ref(), validity.clone()) };
+            chunks.push(arr.boxed());
+        }
+    }
+
+    // SAFETY: the output values are gathered from `a` and `b`, so the dtype is unchanged.
+    Ok(chunks.map(|chunks| unsafe { a.with_chunks(chunks) }))
+}
+
+struct VennLong<'a> {
+    offsets_a: &'a [i64],
+    offsets_b: &'a [i64],
+    validity: Option<&'a Bitmap>,
+}
+
+/// The rows of [`list_set_operation_long`] of a single chunk: the list row, the index of the
+/// element in the values of `a` followed by those of `b`, and the membership flags.
+#[derive(Default)]
+struct LongRows {
+    rows: Vec<IdxSize>,
+    idx: Vec<IdxSize>,
+    in_a: MutableBitmap,
+    in_b: MutableBitmap,
+}
+
+impl PairKernel for VennLong<'_> {
+    type Output = LongRows;
+
+    fn call<V: SetValues>(self, a: &V, b: &V) -> PolarsResult<Self::Output> {
+        let mut out = LongRows::default();
+        let mut set_a = PlIndexMap::default();
+        let mut set_b = PlIndexMap::default();
+
+        for (row, (span_a, span_b)) in row_spans(self.offsets_a, self.offsets_b).enumerate() {
+            if matches!(self.validity, Some(v) if !v.get_bit(row)) {
+                continue;
+            }
+            set_a.clear();
+            set_b.clear();
+            // SAFETY: the offsets of a list array are in bounds of its values.
+            for i in span_a {
+                set_a.entry(unsafe { a.key_unchecked(i) }).or_insert(i);
+            }
+            for i in span_b {
+                set_b
+                    .entry(unsafe { b.key_unchecked(i) })
+                    .or_insert(a.len() + i);
+            }
+
+            let mut push = |i: usize, in_a: bool, in_b: bool| {
+                out.rows.push(row as IdxSize);
+                out.idx.push(i as IdxSize);
+                out.in_a.push(in_a);
+                out.in_b.push(in_b);
+            };
+            for (k, &i) in &set_a {
+                push(i, true, set_b.contains_key(k));
+            }
+            for (k, &i) in &set_b {
+                if !set_a.contains_key(k) {
+                    push(i, false, true);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// The [`list_venn`] decomposition of `a` and `b` in long format, with one row per distinct
+/// element of every pair of lists.
+///
+/// The output has the columns:
+/// * `row_index`: the index of the row of the lists, of dtype [`IDX_DTYPE`],
+/// * `element`: the element, of the inner dtype of `a`,
+/// * `in_a` and `in_b`: whether the element occurs in the list of `a` and `b` respectively.
+///
+/// The elements of a row are in order of first appearance, those of `a` before those only
+/// found in `b`. A null element is an element like any other, so it gives a row with a null
+/// `element`. Null rows in either operand and rows where both lists are empty give no rows.
+pub fn list_set_operation_long(a: &ListChunked, b: &ListChunked) -> PolarsResult<DataFrame> {
+    let (a, b) = prepare_operands(a, b)?;
+    let (a, b) = align_chunks_binary(&a, &b);
+
+    let mut row_index = Vec::new();
+    let mut elements = Vec::new();
+    let (mut in_a, mut in_b) = (Vec::new(), Vec::new());
+    let mut offset = 0;
+    for (arr_a, arr_b) in a.downcast_iter().zip(b.downcast_iter()) {
+        let validity = combine_validities(arr_a, arr_b);
+        let kernel = VennLong {
+            offsets_a: arr_a.offsets().as_slice(),
+            offsets_b: arr_b.offsets().as_slice(),
+            validity: validity.as_ref(),
+        };
+        let out = with_set_values(arr_a.values().as_ref(), arr_b.values().as_ref(), kernel)?;
+
+        let values = concatenate(&[arr_a.values().as_ref(), arr_b.values().as_ref()])?;
+        let idx = IdxArr::from_vec(out.idx);
+        // SAFETY: the indices point into the values of `a` followed by the values of `b`.
+        elements.push(unsafe { take_unchecked(values.as_ref(), &idx) });
+        row_index.extend(out.rows.into_iter().map(|row| row + offset));
+        in_a.push(BooleanArray::from_data_default(out.in_a.into(), None));
+        in_b.push(BooleanArray::from_data_default(out.in_b.into(), None));
+        offset += std::cmp::max(arr_a.len(), arr_b.len()) as IdxSize;
+    }
+
+    let element = if elements.is_empty() {
+        Series::new_empty("element", a.inner_dtype())
+    } else {
+        // SAFETY: the elements are gathered from the values of `a` and `b`, which are of the
+        // inner dtype of `a`.
+        unsafe { Series::from_chunks_and_dtype_unchecked("element", elements, a.inner_dtype()) }
+    };
+    DataFrame::new(vec![
+        IdxCa::from_vec("row_index", row_index).into_series(),
+        element,
+        BooleanChunked::from_chunk_iter("in_a", in_a).into_series(),
+        BooleanChunked::from_chunk_iter("in_b", in_b).into_series(),
+    ])
+}
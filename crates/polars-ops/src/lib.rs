@@ -1,6 +1,7 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![cfg_attr(feature = "nightly", feature(unicode_internals))]
 #![cfg_attr(feature = "nightly", allow(internal_features))]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 extern crate core;
 
 pub mod chunked_array;
@@ -94,7 +94,13 @@ impl ListFunction {
             Unique(_) => mapper.with_same_dtype(),
             Length => mapper.with_dtype(IDX_DTYPE),
             #[cfg(feature = "list_sets")]
-            SetOperation(_) => mapper.with_same_dtype(),
+            SetOperation(s) => {
+                if s.is_boolean() {
+                    mapper.with_dtype(DataType::Boolean)
+                } else {
+                    mapper.with_same_dtype()
+                }
+            },
             #[cfg(feature = "list_any_all")]
             Any => mapper.with_dtype(DataType::Boolean),
             #[cfg(feature = "list_any_all")]
@@ -576,6 +582,17 @@ pub(super) fn set_operation(s: &[Series], set_type: SetOperation) -> PolarsResul
     let s0 = &s[0];
     let s1 = &s[1];
 
+    if set_type.is_boolean() {
+        return boolean_list_set_operation(
+            s0.list()?,
+            s1.list()?,
+            set_type,
+            false,
+            Default::default(),
+        )
+        .map(|ca| ca.into_series());
+    }
+
     if s0.len() == 0 || s1.len() == 0 {
         return match set_type {
             SetOperation::Intersection => {
@@ -593,10 +610,13 @@ pub(super) fn set_operation(s: &[Series], set_type: SetOperation) -> PolarsResul
                     Ok(s0.clone())
                 }
             },
+            _ => unreachable!(),
         };
     }
 
-    list_set_operation(s0.list()?, s1.list()?, set_type).map(|ca| ca.into_series())
+    // The plain set semantics, of which the insertion order is what Python polars relies on.
+    let options = SetOpOptions::default();
+    list_set_operation(s0.list()?, s1.list()?, set_type, options).map(|ca| ca.into_series())
 }
 
 #[cfg(feature = "list_any_all")]
@@ -399,4 +399,32 @@ impl ListNameSpace {
         let other = other.into();
         self.set_operation(other, SetOperation::SymmetricDifference)
     }
+
+    /// Return whether both list arrays have no elements in common.
+    #[cfg(feature = "list_sets")]
+    pub fn set_is_disjoint<E: Into<Expr>>(self, other: E) -> Expr {
+        let other = other.into();
+        self.set_operation(other, SetOperation::IsDisjoint)
+    }
+
+    /// Return whether the list array is a SUBSET of the other list array.
+    #[cfg(feature = "list_sets")]
+    pub fn set_is_subset<E: Into<Expr>>(self, other: E) -> Expr {
+        let other = other.into();
+        self.set_operation(other, SetOperation::IsSubset)
+    }
+
+    /// Return whether the list array is a SUPERSET of the other list array.
+    #[cfg(feature = "list_sets")]
+    pub fn set_is_superset<E: Into<Expr>>(self, other: E) -> Expr {
+        let other = other.into();
+        self.set_operation(other, SetOperation::IsSuperset)
+    }
+
+    /// Return whether both list arrays hold the same SET of elements.
+    #[cfg(feature = "list_sets")]
+    pub fn set_is_equal<E: Into<Expr>>(self, other: E) -> Expr {
+        let other = other.into();
+        self.set_operation(other, SetOperation::IsEqual)
+    }
 }
@@ -0,0 +1,27 @@
+use polars_core::utils::try_get_supertype;
+use polars_ops::chunked_array::list::{boolean_list_set_operation, list_set_operation};
+
+use super::*;
+
+pub(super) fn list_set_operation_impl(
+    mut df: DataFrame,
+    lhs: &str,
+    rhs: &str,
+    set_op: SetOperation,
+    output: &str,
+) -> PolarsResult<DataFrame> {
+    let a = df.column(lhs)?;
+    let b = df.column(rhs)?;
+    // Both columns come from the same batch, so there is nothing to broadcast here.
+    let dtype = try_get_supertype(a.dtype(), b.dtype())?;
+    let a = a.cast(&dtype)?;
+    let b = b.cast(&dtype)?;
+    let out = if set_op.is_boolean() {
+        boolean_list_set_operation(a.list()?, b.list()?, set_op, false, Default::default())?
+            .into_series()
+    } else {
+        list_set_operation(a.list()?, b.list()?, set_op, Default::default())?.into_series()
+    };
+    df.with_column(out.with_name(output))?;
+    Ok(df)
+}
@@ -1,5 +1,7 @@
 mod count;
 mod dsl;
+#[cfg(feature = "list_sets")]
+mod list_sets;
 #[cfg(feature = "merge_sorted")]
 mod merge_sorted;
 #[cfg(feature = "python")]
@@ -15,6 +17,8 @@ use std::sync::Arc;
 
 pub use dsl::*;
 use polars_core::prelude::*;
+#[cfg(feature = "list_sets")]
+use polars_ops::chunked_array::list::SetOperation;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String as SmartString;
@@ -101,6 +105,16 @@ pub enum FunctionNode {
         schema: CachedSchema,
         offset: Option<IdxSize>,
     },
+    // Applies a list 'set' operation between two list columns and adds the result as `output`
+    #[cfg(feature = "list_sets")]
+    ListSetOperation {
+        lhs: Arc<str>,
+        rhs: Arc<str>,
+        set_op: SetOperation,
+        output: Arc<str>,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        schema: CachedSchema,
+    },
 }
 
 impl Eq for FunctionNode {}
@@ -128,6 +142,23 @@ impl PartialEq for FunctionNode {
             (RowIndex { name: l, .. }, RowIndex { name: r, .. }) => l == r,
             #[cfg(feature = "merge_sorted")]
             (MergeSorted { column: l }, MergeSorted { column: r }) => l == r,
+            #[cfg(feature = "list_sets")]
+            (
+                ListSetOperation {
+                    lhs: lhs_l,
+                    rhs: rhs_l,
+                    set_op: set_op_l,
+                    output: output_l,
+                    ..
+                },
+                ListSetOperation {
+                    lhs: lhs_r,
+                    rhs: rhs_r,
+                    set_op: set_op_r,
+                    output: output_r,
+                    ..
+                },
+            ) => lhs_l == lhs_r && rhs_l == rhs_r && set_op_l == set_op_r && output_l == output_r,
             _ => false,
         }
     }
@@ -173,6 +204,19 @@ impl Hash for FunctionNode {
                 name.hash(state);
                 offset.hash(state);
             },
+            #[cfg(feature = "list_sets")]
+            FunctionNode::ListSetOperation {
+                lhs,
+                rhs,
+                set_op,
+                output,
+                schema: _,
+            } => {
+                lhs.hash(state);
+                rhs.hash(state);
+                set_op.hash(state);
+                output.hash(state);
+            },
         }
     }
 }
@@ -191,6 +235,8 @@ impl FunctionNode {
             #[cfg(feature = "python")]
             OpaquePython { streamable, .. } => *streamable,
             RowIndex { .. } => false,
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { .. } => true,
         }
     }
 
@@ -215,6 +261,8 @@ impl FunctionNode {
             #[cfg(feature = "merge_sorted")]
             MergeSorted { .. } => true,
             RowIndex { .. } | Count { .. } => false,
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { .. } => false,
             Pipeline { .. } => unimplemented!(),
         }
     }
@@ -234,6 +282,8 @@ impl FunctionNode {
             #[cfg(feature = "merge_sorted")]
             MergeSorted { .. } => true,
             RowIndex { .. } => true,
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { .. } => true,
             Pipeline { .. } => unimplemented!(),
         }
     }
@@ -245,6 +295,8 @@ impl FunctionNode {
             Explode { columns, .. } => Cow::Borrowed(columns.as_ref()),
             #[cfg(feature = "merge_sorted")]
             MergeSorted { column, .. } => Cow::Owned(vec![column.clone()]),
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { lhs, rhs, .. } => Cow::Owned(vec![lhs.clone(), rhs.clone()]),
             _ => Cow::Borrowed(&[]),
         }
     }
@@ -299,6 +351,14 @@ impl FunctionNode {
                 df.melt2(args)
             },
             RowIndex { name, offset, .. } => df.with_row_index(name.as_ref(), *offset),
+            #[cfg(feature = "list_sets")]
+            ListSetOperation {
+                lhs,
+                rhs,
+                set_op,
+                output,
+                ..
+            } => list_sets::list_set_operation_impl(df, lhs, rhs, *set_op, output),
         }
     }
 }
@@ -342,6 +402,8 @@ impl Display for FunctionNode {
             Explode { .. } => write!(f, "EXPLODE"),
             Melt { .. } => write!(f, "MELT"),
             RowIndex { .. } => write!(f, "WITH ROW INDEX"),
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { set_op, .. } => write!(f, "LIST SET OPERATION: {set_op}"),
         }
     }
 }
@@ -15,6 +15,11 @@ impl FunctionNode {
                 let mut guard = schema.lock().unwrap();
                 *guard = None;
             },
+            #[cfg(feature = "list_sets")]
+            ListSetOperation { schema, .. } => {
+                let mut guard = schema.lock().unwrap();
+                *guard = None;
+            },
             _ => {},
         }
     }
@@ -99,6 +104,14 @@ impl FunctionNode {
             },
             Explode { schema, columns } => explode_schema(schema, input_schema, columns),
             Melt { schema, args } => melt_schema(args, schema, input_schema),
+            #[cfg(feature = "list_sets")]
+            ListSetOperation {
+                lhs,
+                rhs,
+                set_op,
+                output,
+                schema,
+            } => list_set_operation_schema(schema, input_schema, lhs, rhs, *set_op, output),
         }
     }
 }
@@ -217,3 +230,36 @@ fn rename_schema<'a>(
     *guard = Some(schema.clone());
     Ok(Cow::Owned(schema))
 }
+
+#[cfg(feature = "list_sets")]
+fn list_set_operation_schema<'a>(
+    cached_schema: &CachedSchema,
+    input_schema: &'a SchemaRef,
+    lhs: &str,
+    rhs: &str,
+    set_op: SetOperation,
+    output: &str,
+) -> PolarsResult<Cow<'a, SchemaRef>> {
+    let mut guard = cached_schema.lock().unwrap();
+    if let Some(schema) = &*guard {
+        return Ok(Cow::Owned(schema.clone()));
+    }
+    let (dtype_lhs, dtype_rhs) = (input_schema.try_get(lhs)?, input_schema.try_get(rhs)?);
+    for (name, dtype) in [(lhs, dtype_lhs), (rhs, dtype_rhs)] {
+        polars_ensure!(
+            matches!(dtype, DataType::List(_)),
+            InvalidOperation: "list 'set' operation expects `List` columns, got column '{}' of dtype {}",
+            name, dtype
+        );
+    }
+    let dtype = if set_op.is_boolean() {
+        DataType::Boolean
+    } else {
+        try_get_supertype(dtype_lhs, dtype_rhs)?
+    };
+    let mut schema = (**input_schema).clone();
+    schema.with_column(output.into(), dtype);
+    let schema_ref = Arc::new(schema);
+    *guard = Some(schema_ref.clone());
+    Ok(Cow::Owned(schema_ref))
+}
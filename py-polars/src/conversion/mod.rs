@@ -1076,9 +1076,13 @@ impl<'py> FromPyObject<'py> for Wrap<SetOperation> {
             "difference" => SetOperation::Difference,
             "intersection" => SetOperation::Intersection,
             "symmetric_difference" => SetOperation::SymmetricDifference,
+            "is_disjoint" => SetOperation::IsDisjoint,
+            "is_subset" => SetOperation::IsSubset,
+            "is_superset" => SetOperation::IsSuperset,
+            "is_equal" => SetOperation::IsEqual,
             v => {
                 return Err(PyValueError::new_err(format!(
-                    "set operation must be one of {{'union', 'difference', 'intersection', 'symmetric_difference'}}, got {v}",
+                    "set operation must be one of {{'union', 'difference', 'intersection', 'symmetric_difference', 'is_disjoint', 'is_subset', 'is_superset', 'is_equal'}}, got {v}",
                 )))
             }
         };
@@ -250,6 +250,10 @@ impl PyExpr {
             SetOperation::Difference => e.set_difference(other.inner),
             SetOperation::Union => e.union(other.inner),
             SetOperation::SymmetricDifference => e.set_symmetric_difference(other.inner),
+            SetOperation::IsDisjoint => e.set_is_disjoint(other.inner),
+            SetOperation::IsSubset => e.set_is_subset(other.inner),
+            SetOperation::IsSuperset => e.set_is_superset(other.inner),
+            SetOperation::IsEqual => e.set_is_equal(other.inner),
         }
         .into()
     }
@@ -538,6 +538,20 @@ pub(crate) fn into_py(py: Python<'_>, plan: &IR) -> PyResult<PyObject> {
                     scan_type: _,
                     alias: _,
                 } => return Err(PyNotImplementedError::new_err("function count")),
+                FunctionNode::ListSetOperation {
+                    lhs,
+                    rhs,
+                    set_op,
+                    output,
+                    schema: _,
+                } => (
+                    "list_set_operation",
+                    lhs.to_string(),
+                    rhs.to_string(),
+                    set_op.to_string(),
+                    output.to_string(),
+                )
+                    .to_object(py),
             },
         }
         .into_py(py),